@@ -0,0 +1,482 @@
+use crate::caps::edit_distance;
+use std::error::Error;
+
+/// Architectures a provider binary can be built for, as reported by `std::env::consts::ARCH` on a host
+const ARCHITECTURES: &[&str] = &[
+    "x86",
+    "x86_64",
+    "arm",
+    "aarch64",
+    "mips",
+    "mips64",
+    "powerpc",
+    "powerpc64",
+    "riscv64",
+    "s390x",
+    "sparc64",
+];
+
+/// Operating systems a provider binary can be built for, as reported by `std::env::consts::OS` on a host
+const OPERATING_SYSTEMS: &[&str] = &[
+    "linux",
+    "macos",
+    "ios",
+    "windows",
+    "android",
+    "freebsd",
+    "netbsd",
+    "openbsd",
+    "dragonfly",
+    "solaris",
+];
+
+/// Maximum edit distance for an unknown target to be considered a typo of a known one
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Universal Mach-O binaries share their magic number with Java class files, which follow it with
+/// a version of at least 45 where a universal binary has its (much smaller) number of architectures
+const MAX_UNIVERSAL_ARCHITECTURES: u32 = 30;
+
+/// Target of a native binary as read from its headers. Object formats do not always record the
+/// operating system precisely, so every operating system the binary could be built for is kept,
+/// most likely first
+#[derive(Debug, PartialEq)]
+pub(crate) struct DetectedTarget {
+    pub(crate) arch: &'static str,
+    pub(crate) oses: &'static [&'static str],
+}
+
+impl DetectedTarget {
+    /// The canonical `<arch>-<os>` target for this binary
+    pub(crate) fn target(&self) -> String {
+        format!("{}-{}", self.arch, self.oses[0])
+    }
+
+    fn matches(&self, target: &str) -> bool {
+        self.oses
+            .iter()
+            .any(|os| format!("{}-{}", self.arch, os) == target)
+    }
+}
+
+/// Determines the target to store a provider binary under. The target is detected from the binary's
+/// ELF, Mach-O or PE headers, and an explicit target must be a known target that agrees with them.
+/// Universal Mach-O binaries contain several architectures, so their target must always be explicit
+pub(crate) fn resolve_target(
+    arch: Option<&str>,
+    binary: &[u8],
+    path: &str,
+) -> Result<String, Box<dyn Error>> {
+    if let Some(archs) = detect_universal_macho(binary) {
+        let arch = arch.ok_or_else(|| {
+            format!(
+                "{} is a universal Mach-O binary containing {}, please provide the target to store it under with --arch",
+                path,
+                archs.join(", ")
+            )
+        })?;
+        validate_target(arch)?;
+        let contained = archs.iter().any(|a| {
+            ["macos", "ios"]
+                .iter()
+                .any(|os| format!("{}-{}", a, os) == arch)
+        });
+        return if contained {
+            Ok(arch.to_string())
+        } else {
+            Err(format!(
+                "{} is a universal Mach-O binary containing {}, but was provided as {}",
+                path,
+                archs.join(", "),
+                arch
+            )
+            .into())
+        };
+    }
+
+    let detected = detect_target(binary);
+    match (arch, detected) {
+        (Some(arch), detected) => {
+            validate_target(arch)?;
+            match detected {
+                Some(detected) if !detected.matches(arch) => Err(format!(
+                    "{} is a {} binary, but was provided as {}",
+                    path,
+                    detected.target(),
+                    arch
+                )
+                .into()),
+                _ => Ok(arch.to_string()),
+            }
+        }
+        (None, Some(detected)) => Ok(detected.target()),
+        (None, None) => Err(format!(
+            "Unable to detect the architecture of {}, please provide it with --arch",
+            path
+        )
+        .into()),
+    }
+}
+
+/// Ensures a target is a known `<arch>-<os>` pair, suggesting the closest known target for typos
+pub(crate) fn validate_target(target: &str) -> Result<(), Box<dyn Error>> {
+    let mut parts = target.splitn(2, '-');
+    let (arch, os) = match (parts.next(), parts.next()) {
+        (Some(arch), Some(os)) => (arch, os),
+        _ => {
+            return Err(format!(
+                "Invalid target {}, targets must be in the format ARCH-OS (e.g. x86_64-linux)",
+                target
+            )
+            .into())
+        }
+    };
+    if ARCHITECTURES.contains(&arch) && OPERATING_SYSTEMS.contains(&os) {
+        return Ok(());
+    }
+
+    let suggestion = ARCHITECTURES
+        .iter()
+        .flat_map(|arch| {
+            OPERATING_SYSTEMS
+                .iter()
+                .map(move |os| format!("{}-{}", arch, os))
+        })
+        .map(|candidate| (edit_distance(target, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance);
+    match suggestion {
+        Some((_, candidate)) => {
+            Err(format!("Unknown target {}, did you mean {}?", target, candidate).into())
+        }
+        None => Err(format!(
+            "Unknown target {}, architecture must be one of {} and operating system one of {}",
+            target,
+            ARCHITECTURES.join(", "),
+            OPERATING_SYSTEMS.join(", ")
+        )
+        .into()),
+    }
+}
+
+/// Converts a directory name from a cross-compilation build into a target. Names can either be
+/// targets themselves (e.g. `x86_64-linux`) or Rust target triples (e.g. `x86_64-unknown-linux-gnu`)
+pub(crate) fn target_from_triple(name: &str) -> Option<String> {
+    if validate_target(name).is_ok() {
+        return Some(name.to_string());
+    }
+    let mut parts = name.split('-');
+    let arch = match parts.next()? {
+        "x86_64" => "x86_64",
+        "i386" | "i586" | "i686" => "x86",
+        "aarch64" | "arm64" => "aarch64",
+        a if a.starts_with("arm") || a.starts_with("thumb") => "arm",
+        a if a.starts_with("mips64") => "mips64",
+        a if a.starts_with("mips") => "mips",
+        a if a.starts_with("powerpc64") => "powerpc64",
+        "powerpc" => "powerpc",
+        a if a.starts_with("riscv64") => "riscv64",
+        "s390x" => "s390x",
+        "sparc64" | "sparcv9" => "sparc64",
+        _ => return None,
+    };
+    let rest: Vec<&str> = parts.collect();
+    let has = |os: &str| rest.iter().any(|part| part.starts_with(os));
+    // Android triples also contain "linux", so they are checked first
+    let os = if has("android") {
+        "android"
+    } else if has("linux") {
+        "linux"
+    } else if has("darwin") || has("macos") {
+        "macos"
+    } else if has("ios") {
+        "ios"
+    } else if has("windows") {
+        "windows"
+    } else if has("freebsd") {
+        "freebsd"
+    } else if has("netbsd") {
+        "netbsd"
+    } else if has("openbsd") {
+        "openbsd"
+    } else if has("dragonfly") {
+        "dragonfly"
+    } else if has("solaris") {
+        "solaris"
+    } else {
+        return None;
+    };
+    Some(format!("{}-{}", arch, os))
+}
+
+/// Reads the target of a native binary from its ELF, Mach-O or PE headers
+pub(crate) fn detect_target(binary: &[u8]) -> Option<DetectedTarget> {
+    match binary.get(0..4)? {
+        [0x7f, b'E', b'L', b'F'] => detect_elf(binary),
+        [0xcf, 0xfa, 0xed, 0xfe] | [0xce, 0xfa, 0xed, 0xfe] => detect_macho(binary),
+        [b'M', b'Z', _, _] => detect_pe(binary),
+        _ => None,
+    }
+}
+
+fn detect_elf(binary: &[u8]) -> Option<DetectedTarget> {
+    let is_64 = *binary.get(4)? == 2;
+    let machine = match binary.get(5)? {
+        1 => u16::from_le_bytes([*binary.get(18)?, *binary.get(19)?]),
+        2 => u16::from_be_bytes([*binary.get(18)?, *binary.get(19)?]),
+        _ => return None,
+    };
+    let arch = match (machine, is_64) {
+        (3, _) => "x86",
+        (62, _) => "x86_64",
+        (40, _) => "arm",
+        (183, _) => "aarch64",
+        (8, false) => "mips",
+        (8, true) => "mips64",
+        (20, _) => "powerpc",
+        (21, _) => "powerpc64",
+        (243, true) => "riscv64",
+        (22, _) => "s390x",
+        (43, _) => "sparc64",
+        _ => return None,
+    };
+    // Linux binaries are usually marked as System V rather than GNU/Linux
+    let oses: &[&str] = match binary.get(7)? {
+        0 | 3 => &["linux"],
+        2 => &["netbsd"],
+        6 => &["solaris"],
+        9 => &["freebsd", "dragonfly"],
+        12 => &["openbsd"],
+        _ => return None,
+    };
+    Some(DetectedTarget { arch, oses })
+}
+
+fn detect_macho(binary: &[u8]) -> Option<DetectedTarget> {
+    let cputype = u32::from_le_bytes([
+        *binary.get(4)?,
+        *binary.get(5)?,
+        *binary.get(6)?,
+        *binary.get(7)?,
+    ]);
+    Some(DetectedTarget {
+        arch: macho_arch(cputype)?,
+        oses: &["macos", "ios"],
+    })
+}
+
+/// Reads the architectures contained in a universal (fat) Mach-O binary, returning `None` for any
+/// other binary
+fn detect_universal_macho(binary: &[u8]) -> Option<Vec<&'static str>> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        Some(u32::from_be_bytes([
+            *binary.get(offset)?,
+            *binary.get(offset + 1)?,
+            *binary.get(offset + 2)?,
+            *binary.get(offset + 3)?,
+        ]))
+    };
+    // 32 and 64 bit universal headers differ only in the size of their architecture entries
+    let entry_size = match read_u32(0)? {
+        0xcafe_babe => 20,
+        0xcafe_babf => 32,
+        _ => return None,
+    };
+    let count = read_u32(4)?;
+    if count == 0 || count > MAX_UNIVERSAL_ARCHITECTURES {
+        return None;
+    }
+    let archs = (0..count as usize)
+        .map(|i| {
+            let cputype = read_u32(8 + i * entry_size)?;
+            Some(macho_arch(cputype).unwrap_or("unknown"))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(archs)
+}
+
+fn macho_arch(cputype: u32) -> Option<&'static str> {
+    match cputype {
+        0x0000_0007 => Some("x86"),
+        0x0100_0007 => Some("x86_64"),
+        0x0000_000c => Some("arm"),
+        0x0100_000c => Some("aarch64"),
+        0x0000_0012 => Some("powerpc"),
+        0x0100_0012 => Some("powerpc64"),
+        _ => None,
+    }
+}
+
+fn detect_pe(binary: &[u8]) -> Option<DetectedTarget> {
+    let offset = u32::from_le_bytes([
+        *binary.get(0x3c)?,
+        *binary.get(0x3d)?,
+        *binary.get(0x3e)?,
+        *binary.get(0x3f)?,
+    ]) as usize;
+    if binary.get(offset..offset + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let machine = u16::from_le_bytes([*binary.get(offset + 4)?, *binary.get(offset + 5)?]);
+    let arch = match machine {
+        0x014c => "x86",
+        0x8664 => "x86_64",
+        0x01c4 => "arm",
+        0xaa64 => "aarch64",
+        _ => return None,
+    };
+    Some(DetectedTarget {
+        arch,
+        oses: &["windows"],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn elf(class: u8, data: u8, osabi: u8, machine: u16) -> Vec<u8> {
+        let mut header = vec![0x7f, b'E', b'L', b'F', class, data, 1, osabi];
+        header.resize(18, 0);
+        match data {
+            2 => header.extend_from_slice(&machine.to_be_bytes()),
+            _ => header.extend_from_slice(&machine.to_le_bytes()),
+        }
+        header.resize(64, 0);
+        header
+    }
+
+    #[test]
+    fn test_detect_target() {
+        assert_eq!(
+            detect_target(&elf(2, 1, 0, 62)).unwrap().target(),
+            "x86_64-linux"
+        );
+        assert_eq!(
+            detect_target(&elf(2, 1, 3, 183)).unwrap().target(),
+            "aarch64-linux"
+        );
+        assert_eq!(
+            detect_target(&elf(2, 2, 0, 8)).unwrap().target(),
+            "mips64-linux"
+        );
+        assert_eq!(
+            detect_target(&elf(2, 1, 9, 62)).unwrap().target(),
+            "x86_64-freebsd"
+        );
+
+        let mut macho = vec![0xcf, 0xfa, 0xed, 0xfe];
+        macho.extend_from_slice(&0x0100_000c_u32.to_le_bytes());
+        assert_eq!(detect_target(&macho).unwrap().target(), "aarch64-macos");
+
+        let mut pe = vec![0u8; 0x84];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3c..0x40].copy_from_slice(&0x80_u32.to_le_bytes());
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        pe.extend_from_slice(&0x8664_u16.to_le_bytes());
+        assert_eq!(detect_target(&pe).unwrap().target(), "x86_64-windows");
+
+        assert!(detect_target(b"01100010 01110100 01110111").is_none());
+        assert!(detect_target(&[0x7f, b'E', b'L', b'F']).is_none());
+    }
+
+    #[test]
+    fn test_target_from_triple() {
+        assert_eq!(target_from_triple("x86_64-linux").unwrap(), "x86_64-linux");
+        assert_eq!(
+            target_from_triple("x86_64-unknown-linux-gnu").unwrap(),
+            "x86_64-linux"
+        );
+        assert_eq!(
+            target_from_triple("aarch64-linux-android").unwrap(),
+            "aarch64-android"
+        );
+        assert_eq!(
+            target_from_triple("aarch64-apple-darwin").unwrap(),
+            "aarch64-macos"
+        );
+        assert_eq!(
+            target_from_triple("armv7-unknown-linux-gnueabihf").unwrap(),
+            "arm-linux"
+        );
+        assert_eq!(
+            target_from_triple("x86_64-pc-windows-msvc").unwrap(),
+            "x86_64-windows"
+        );
+        assert!(target_from_triple("release").is_none());
+        assert!(target_from_triple("wasm32-unknown-unknown").is_none());
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let linux = elf(2, 1, 0, 62);
+        assert_eq!(
+            resolve_target(None, &linux, "libtest.so").unwrap(),
+            "x86_64-linux"
+        );
+        assert_eq!(
+            resolve_target(Some("x86_64-android"), &linux, "libtest.so")
+                .unwrap_err()
+                .to_string(),
+            "libtest.so is a x86_64-linux binary, but was provided as x86_64-android"
+        );
+        let freebsd = elf(2, 1, 9, 62);
+        assert_eq!(
+            resolve_target(Some("x86_64-dragonfly"), &freebsd, "libtest.so").unwrap(),
+            "x86_64-dragonfly"
+        );
+        assert_eq!(
+            resolve_target(Some("aarch64-linux"), &linux, "libtest.so")
+                .unwrap_err()
+                .to_string(),
+            "libtest.so is a x86_64-linux binary, but was provided as aarch64-linux"
+        );
+        assert_eq!(
+            resolve_target(Some("x86_64-linx"), &linux, "libtest.so")
+                .unwrap_err()
+                .to_string(),
+            "Unknown target x86_64-linx, did you mean x86_64-linux?"
+        );
+
+        // Binaries in unknown formats are trusted to match a known target
+        assert_eq!(
+            resolve_target(Some("mips64-android"), b"binary", "libtest.so").unwrap(),
+            "mips64-android"
+        );
+        assert!(resolve_target(None, b"binary", "libtest.so").is_err());
+        assert!(resolve_target(Some("x86_64"), b"binary", "libtest.so").is_err());
+    }
+
+    #[test]
+    fn test_resolve_universal_macho() {
+        let mut universal = vec![0xca, 0xfe, 0xba, 0xbe];
+        universal.extend_from_slice(&2_u32.to_be_bytes());
+        for cputype in &[0x0100_0007_u32, 0x0100_000c] {
+            let mut entry = cputype.to_be_bytes().to_vec();
+            entry.resize(20, 0);
+            universal.extend_from_slice(&entry);
+        }
+
+        assert_eq!(
+            resolve_target(None, &universal, "libtest.dylib")
+                .unwrap_err()
+                .to_string(),
+            "libtest.dylib is a universal Mach-O binary containing x86_64, aarch64, please provide the target to store it under with --arch"
+        );
+        assert_eq!(
+            resolve_target(Some("aarch64-macos"), &universal, "libtest.dylib").unwrap(),
+            "aarch64-macos"
+        );
+        assert_eq!(
+            resolve_target(Some("x86_64-linux"), &universal, "libtest.dylib")
+                .unwrap_err()
+                .to_string(),
+            "libtest.dylib is a universal Mach-O binary containing x86_64, aarch64, but was provided as x86_64-linux"
+        );
+
+        // Java class files share the magic number, but are followed by their version
+        let mut class = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52];
+        class.resize(64, 0);
+        assert!(detect_universal_macho(&class).is_none());
+    }
+}
@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::path::PathBuf;
+use wascap::caps::*;
+
+/// Environment variable pointing at a capability catalog file, defaults to $HOME/.wash/capabilities.toml
+pub(crate) const WASH_CAPABILITIES: &str = "WASH_CAPABILITIES";
+
+/// Maximum edit distance for an unknown capability to be considered a typo of a known one
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+/// Namespace of the first-party contracts, the only contract IDs checked for typos
+const WASMCLOUD_NAMESPACE: &str = "wasmcloud:";
+
+/// A capability contract known to wash, along with the aliases that can be used to refer to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Capability {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) aliases: Vec<String>,
+    #[serde(skip_deserializing)]
+    pub(crate) builtin: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogFile {
+    #[serde(default, rename = "capability")]
+    capabilities: Vec<Capability>,
+}
+
+/// Well-known capability contracts, extended with any contracts declared in the user's catalog file
+#[derive(Debug, Clone)]
+pub(crate) struct CapabilityCatalog {
+    capabilities: Vec<Capability>,
+}
+
+impl CapabilityCatalog {
+    /// Catalog of the first-party wasmcloud capability contracts
+    pub(crate) fn builtin() -> Self {
+        let builtin = |id: &str, name: &str, aliases: &[&str]| Capability {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            builtin: true,
+        };
+        CapabilityCatalog {
+            capabilities: vec![
+                builtin(
+                    BLOB,
+                    "Blob Store",
+                    &["blob", "blobstore", "blob-store", "blob_store"],
+                ),
+                builtin(
+                    EVENTSTREAMS,
+                    "Event Streams",
+                    &["events", "eventstream", "eventstreams", "event-streams"],
+                ),
+                builtin(EXTRAS, "Extras", &["extras"]),
+                builtin(
+                    HTTP_CLIENT,
+                    "HTTP Client",
+                    &["httpclient", "http-client", "http_client"],
+                ),
+                builtin(
+                    HTTP_SERVER,
+                    "HTTP Server",
+                    &["httpserver", "http-server", "http_server"],
+                ),
+                builtin(
+                    KEY_VALUE,
+                    "K/V Store",
+                    &["kv", "keyvalue", "key-value", "key_value"],
+                ),
+                builtin(LOGGING, "Logging", &["log", "logging"]),
+                builtin(MESSAGING, "Messaging", &["msg", "messaging"]),
+            ],
+        }
+    }
+
+    /// Loads the built-in catalog along with the catalog file at $WASH_CAPABILITIES
+    /// ($HOME/.wash/capabilities.toml), if one exists
+    pub(crate) fn load() -> Result<Self, Box<dyn Error>> {
+        let path = match env::var(WASH_CAPABILITIES) {
+            Ok(path) => Some(PathBuf::from(path)),
+            Err(_) => env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".wash").join("capabilities.toml")),
+        };
+        match path {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(&path)?;
+                let file: CatalogFile = serdeconv::from_toml_str(&contents).map_err(|e| {
+                    format!(
+                        "Unable to parse capability catalog {}: {}",
+                        path.display(),
+                        e
+                    )
+                })?;
+                Ok(Self::builtin().extend(file.capabilities))
+            }
+            _ => Ok(Self::builtin()),
+        }
+    }
+
+    /// Adds user-defined capabilities. Entries with the ID of a known capability rename it and add aliases.
+    /// IDs and aliases are lowercased, as they are matched case-insensitively
+    fn extend(mut self, capabilities: Vec<Capability>) -> Self {
+        for mut cap in capabilities {
+            cap.id = cap.id.to_lowercase();
+            cap.aliases = cap.aliases.iter().map(|a| a.to_lowercase()).collect();
+            match self.capabilities.iter_mut().find(|c| c.id == cap.id) {
+                Some(existing) => {
+                    existing.name = cap.name;
+                    existing.aliases.extend(cap.aliases);
+                }
+                None => self.capabilities.push(cap),
+            }
+        }
+        self
+    }
+
+    pub(crate) fn capabilities(&self) -> &[Capability] {
+        &self.capabilities
+    }
+
+    /// Human friendly name of a capability contract, or the contract ID itself if it is unknown
+    pub(crate) fn name(&self, id: &str) -> String {
+        self.capabilities
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Resolves a contract ID or alias to a contract ID. Contract IDs that are not in the
+    /// catalog are accepted as custom capabilities, unless they are in the `wasmcloud` namespace
+    /// and look like a typo of a known one
+    pub(crate) fn resolve(&self, input: &str) -> Result<String, Box<dyn Error>> {
+        let lowercase = input.to_lowercase();
+        if let Some(cap) = self
+            .capabilities
+            .iter()
+            .find(|c| c.id == lowercase || c.aliases.contains(&lowercase))
+        {
+            return Ok(cap.id.clone());
+        }
+
+        let contract_id = is_contract_id(input);
+        if contract_id && !lowercase.starts_with(WASMCLOUD_NAMESPACE) {
+            return Ok(input.to_string());
+        }
+
+        // Contract IDs are only compared with other contract IDs
+        let suggestion = self
+            .capabilities
+            .iter()
+            .flat_map(|c| {
+                std::iter::once(&c.id).chain(c.aliases.iter().filter(move |_| !contract_id))
+            })
+            .map(|candidate| (edit_distance(&lowercase, candidate), candidate))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(distance, _)| *distance);
+
+        match suggestion {
+            Some((_, candidate)) => Err(format!(
+                "Unknown capability \"{}\", did you mean \"{}\"?",
+                input, candidate
+            )
+            .into()),
+            None if contract_id => Ok(input.to_string()),
+            None => Err(format!(
+                "Unknown capability \"{}\". Custom capabilities must be contract IDs in the form \"namespace:capability\", run `wash claims caps list` to see known capabilities",
+                input
+            )
+            .into()),
+        }
+    }
+}
+
+fn is_contract_id(input: &str) -> bool {
+    let mut parts = input.split(':');
+    matches!(
+        (parts.next(), parts.next(), parts.next()),
+        (Some(ns), Some(name), None) if !ns.is_empty() && !name.is_empty()
+    )
+}
+
+/// Levenshtein distance between two strings
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_aliases() {
+        let catalog = CapabilityCatalog::builtin();
+        assert_eq!(catalog.resolve("kv").unwrap(), KEY_VALUE);
+        assert_eq!(catalog.resolve("http-server").unwrap(), HTTP_SERVER);
+        assert_eq!(
+            catalog.resolve("wasmcloud:httpserver").unwrap(),
+            HTTP_SERVER
+        );
+        assert_eq!(catalog.resolve("Logging").unwrap(), LOGGING);
+        assert_eq!(catalog.resolve("acme:payments").unwrap(), "acme:payments");
+    }
+
+    #[test]
+    fn test_resolve_unknown() {
+        let catalog = CapabilityCatalog::builtin();
+        assert_eq!(
+            catalog
+                .resolve("wasmcloud:keyvaleu")
+                .unwrap_err()
+                .to_string(),
+            "Unknown capability \"wasmcloud:keyvaleu\", did you mean \"wasmcloud:keyvalue\"?"
+        );
+        assert_eq!(
+            catalog.resolve("htttp-server").unwrap_err().to_string(),
+            "Unknown capability \"htttp-server\", did you mean \"http-server\"?"
+        );
+        assert!(catalog.resolve("payments").is_err());
+        assert!(catalog.resolve("acme:").is_err());
+
+        // Custom contract IDs close to a known ID or alias are accepted
+        assert_eq!(catalog.resolve("a:kv").unwrap(), "a:kv");
+        assert_eq!(catalog.resolve("acme:keyvalue").unwrap(), "acme:keyvalue");
+    }
+
+    #[test]
+    fn test_user_catalog() {
+        let file: CatalogFile = serdeconv::from_toml_str(
+            r#"
+            [[capability]]
+            id = "acme:payments"
+            name = "Payments"
+            aliases = ["payments", "Pay"]
+
+            [[capability]]
+            id = "wasmcloud:keyvalue"
+            name = "Key Value"
+            aliases = ["redis"]
+            "#,
+        )
+        .unwrap();
+        let catalog = CapabilityCatalog::builtin().extend(file.capabilities);
+        assert_eq!(catalog.resolve("pay").unwrap(), "acme:payments");
+        assert_eq!(catalog.resolve("Pay").unwrap(), "acme:payments");
+        assert_eq!(catalog.resolve("redis").unwrap(), KEY_VALUE);
+        assert_eq!(catalog.resolve("kv").unwrap(), KEY_VALUE);
+        assert_eq!(catalog.name(KEY_VALUE), "Key Value");
+        assert_eq!(catalog.name("acme:payments"), "Payments");
+        assert!(
+            !catalog
+                .capabilities()
+                .iter()
+                .find(|c| c.id == "acme:payments")
+                .unwrap()
+                .builtin
+        );
+    }
+}
@@ -12,13 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::caps::CapabilityCatalog;
+use crate::diff::Diff;
 use crate::keys::extract_keypair;
-use crate::util::{format_output, Output, OutputKind};
+use crate::reg::PullOptions;
+use crate::signer::{encode_claims, extract_signer, Signer};
+use crate::util::{extract_arg_value, format_output, FailedOutput, Output, OutputKind};
 use nkeys::{KeyPair, KeyPairType};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Read;
 use std::io::Write;
@@ -34,7 +38,7 @@ use wascap::caps::*;
 use wascap::jwt::{
     Account, Actor, CapabilityProvider, Claims, Operator, Token, TokenValidation, WascapEntity,
 };
-use wascap::wasm::{days_from_now_to_jwt_time, sign_buffer_with_claims};
+use wascap::wasm::days_from_now_to_jwt_time;
 
 #[derive(Debug, StructOpt, Clone)]
 #[structopt(
@@ -63,6 +67,37 @@ pub(crate) enum ClaimsCliCommand {
     /// Generate a signed JWT by supplying basic token information, a signing seed key, and metadata
     #[structopt(name = "token")]
     Token(TokenCommand),
+    /// Verify the operator, account and actor chain of trust of a signed WebAssembly module
+    #[structopt(name = "verify")]
+    Verify(VerifyCommand),
+    /// Update the claims of a signed WebAssembly module, keeping all claims that are not changed
+    #[structopt(name = "update")]
+    Update(UpdateCommand),
+    /// Remove the embedded claims from a signed WebAssembly module, producing the unsigned module
+    #[structopt(name = "strip")]
+    Strip(StripCommand),
+    /// Write the JWT embedded in a signed WebAssembly module to a file
+    #[structopt(name = "extract")]
+    Extract(ExtractCommand),
+    /// Show the differences between the claims of two signed WebAssembly modules
+    #[structopt(name = "diff")]
+    Diff(DiffCommand),
+    /// Work with the catalog of known capability contracts
+    #[structopt(name = "caps")]
+    Caps(CapsCliCommand),
+}
+
+#[derive(Debug, Clone, StructOpt)]
+pub(crate) enum CapsCliCommand {
+    /// List known capability contracts and their aliases
+    #[structopt(name = "list")]
+    List(CapsListCommand),
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct CapsListCommand {
+    #[structopt(flatten)]
+    pub(crate) output: Output,
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -78,31 +113,32 @@ pub(crate) struct InspectCommand {
     #[structopt(short = "d", long = "digest")]
     digest: Option<String>,
 
-    /// Allow latest artifact tags (if OCI URL is provided for <module>)
-    #[structopt(long = "allow-latest")]
-    allow_latest: bool,
+    #[structopt(flatten)]
+    opts: PullOptions,
 
-    /// OCI username, if omitted anonymous authentication will be used
-    #[structopt(
-        short = "u",
-        long = "user",
-        env = "WASH_REG_USER",
-        hide_env_values = true
-    )]
-    user: Option<String>,
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
 
-    /// OCI password, if omitted anonymous authentication will be used
-    #[structopt(
-        short = "p",
-        long = "password",
-        env = "WASH_REG_PASSWORD",
-        hide_env_values = true
-    )]
-    password: Option<String>,
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct VerifyCommand {
+    /// Path to signed actor module or OCI URL of signed actor module
+    pub(crate) module: String,
+
+    /// Operator JWT, or path to a file containing the JWT, used as the root of trust
+    #[structopt(long = "operator")]
+    operator: String,
+
+    /// Account JWT, or path to a file containing the JWT, that was issued by the operator
+    #[structopt(long = "account")]
+    account: String,
+
+    /// Digest to verify artifact against (if OCI URL is provided for <module>)
+    #[structopt(short = "d", long = "digest")]
+    digest: Option<String>,
 
-    /// Allow insecure (HTTP) registry connections
-    #[structopt(long = "insecure")]
-    insecure: bool,
+    #[structopt(flatten)]
+    opts: PullOptions,
 
     #[structopt(flatten)]
     pub(crate) output: Output,
@@ -110,17 +146,209 @@ pub(crate) struct InspectCommand {
 
 #[derive(StructOpt, Debug, Clone)]
 pub(crate) struct SignCommand {
-    /// File to read
+    /// File to read. A directory or glob pattern (e.g. "build/*.wasm") signs every matching module
     pub(crate) source: String,
 
-    /// Destination for signed module. If this flag is not provided, the signed module will be placed in the same directory as the source with a "_s" suffix
+    /// Destination for signed module. If this flag is not provided, the signed module will be placed in the same directory as the source with a "_s" suffix. When signing multiple modules, this is the directory signed modules are written to
     #[structopt(short = "d", long = "destination")]
     destination: Option<String>,
 
+    /// Path to a TOML, YAML or JSON file describing the claims to sign the module with. Flags provided on the command line take precedence over values in the file
+    #[structopt(long = "claims-file")]
+    claims_file: Option<String>,
+
     #[structopt(flatten)]
     metadata: ActorMetadata,
 }
 
+/// Signing configuration for a module, as read from a `--claims-file`. Relative key paths
+/// are resolved against the directory containing the file.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ClaimsFile {
+    name: Option<String>,
+    caps: Vec<String>,
+    tags: Vec<String>,
+    provider: bool,
+    rev: Option<i32>,
+    ver: Option<String>,
+    call_alias: Option<String>,
+    expires_in_days: Option<u64>,
+    not_before_days: Option<u64>,
+    issuer: Option<String>,
+    subject: Option<String>,
+    directory: Option<String>,
+    disable_keygen: bool,
+}
+
+impl ClaimsFile {
+    fn load(path: &str) -> Result<ClaimsFile, Box<dyn ::std::error::Error>> {
+        let path = PathBuf::from(path);
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read claims file {}: {}", path.display(), e))?;
+        let mut claims_file: ClaimsFile = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => serdeconv::from_toml_str(&contents)?,
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)?,
+            Some("json") => serde_json::from_str(&contents)?,
+            _ => {
+                return Err(format!(
+                    "Unsupported claims file {}, expected a .toml, .yaml, .yml or .json file",
+                    path.display()
+                )
+                .into())
+            }
+        };
+
+        let base = path.parent().map(PathBuf::from).unwrap_or_default();
+        claims_file.issuer = claims_file.issuer.map(|k| resolve_key_path(&base, k));
+        claims_file.subject = claims_file.subject.map(|k| resolve_key_path(&base, k));
+        claims_file.directory = claims_file.directory.map(|d| resolve_key_path(&base, d));
+        Ok(claims_file)
+    }
+}
+
+/// Resolves a key location relative to `base` if it exists there, leaving seeds and
+/// absolute paths untouched
+fn resolve_key_path(base: &std::path::Path, key: String) -> String {
+    let relative = base.join(&key);
+    if PathBuf::from(&key).is_relative() && relative.exists() {
+        relative.to_string_lossy().to_string()
+    } else {
+        key
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct StripCommand {
+    /// Signed module to remove claims from
+    pub(crate) module: String,
+
+    /// Destination for the unsigned module. If this flag is not provided, the unsigned module will be placed in the same directory as the source with an "_unsigned" suffix
+    #[structopt(short = "d", long = "destination")]
+    destination: Option<String>,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct DiffCommand {
+    /// Path to signed actor module or OCI URL of signed actor module to compare from
+    pub(crate) left: String,
+
+    /// Path to signed actor module or OCI URL of signed actor module to compare to
+    pub(crate) right: String,
+
+    /// Digest to verify the left artifact against (if OCI URL is provided for <left>)
+    #[structopt(long = "left-digest")]
+    left_digest: Option<String>,
+
+    /// Digest to verify the right artifact against (if OCI URL is provided for <right>)
+    #[structopt(long = "right-digest")]
+    right_digest: Option<String>,
+
+    #[structopt(flatten)]
+    opts: PullOptions,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct ExtractCommand {
+    /// Signed module to extract the JWT from
+    pub(crate) module: String,
+
+    /// File to write the JWT to. If this flag is not provided, the JWT will be placed in the same directory as the source with a ".jwt" extension
+    #[structopt(short = "d", long = "destination")]
+    destination: Option<String>,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct UpdateCommand {
+    /// Signed module to update
+    pub(crate) source: String,
+
+    /// Destination for the updated module. If this flag is not provided, the source module will be updated in place
+    #[structopt(short = "d", long = "destination")]
+    destination: Option<String>,
+
+    /// A new human-readable, descriptive name for the token
+    #[structopt(short = "n", long = "name")]
+    name: Option<String>,
+
+    /// Add capabilities by contract ID or alias
+    #[structopt(short = "c", long = "cap", name = "capabilities")]
+    add_caps: Vec<String>,
+
+    /// Remove capabilities by contract ID or alias
+    #[structopt(long = "remove-cap")]
+    remove_caps: Vec<String>,
+
+    /// Add tags to be embedded in the token
+    #[structopt(short = "t", long = "tag")]
+    add_tags: Vec<String>,
+
+    /// Remove tags from the token
+    #[structopt(long = "remove-tag")]
+    remove_tags: Vec<String>,
+
+    /// Revision number
+    #[structopt(short = "r", long = "rev")]
+    rev: Option<i32>,
+
+    /// Increment the existing revision number by one
+    #[structopt(long = "bump-rev", conflicts_with = "rev")]
+    bump_rev: bool,
+
+    /// Human-readable version string
+    #[structopt(short = "v", long = "ver")]
+    ver: Option<String>,
+
+    /// Developer or human friendly unique alias used for invoking an actor, consisting of lowercase alphanumeric characters, underscores '_' and slashes '/'
+    #[structopt(short = "a", long = "call-alias")]
+    call_alias: Option<String>,
+
+    /// Indicates the token expires in the given amount of days from now. If this option is left off, the existing expiration is kept
+    #[structopt(short = "x", long = "expires")]
+    expires_in_days: Option<u64>,
+
+    /// Period in days from now that must elapse before this token is valid. If this option is left off, the existing value is kept
+    #[structopt(short = "b", long = "nbf")]
+    not_before_days: Option<u64>,
+
+    /// Path to issuer seed key (account). If this flag is not provided, the will be sourced from $WASH_KEYS ($HOME/.wash/keys) or generated for you if it cannot be found.
+    #[structopt(
+        short = "i",
+        long = "issuer",
+        env = "WASH_ISSUER_KEY",
+        hide_env_values = true
+    )]
+    issuer: Option<String>,
+
+    /// Allow re-signing the module with an issuer other than the account that originally signed it
+    #[structopt(long = "rotate-issuer")]
+    rotate_issuer: bool,
+
+    /// Location of key files for signing. Defaults to $WASH_KEYS ($HOME/.wash/keys)
+    #[structopt(long = "directory", env = "WASH_KEYS", hide_env_values = true)]
+    directory: Option<String>,
+
+    /// Disables autogeneration of keys if seed(s) are not provided
+    #[structopt(long = "disable-keygen")]
+    disable_keygen: bool,
+
+    /// Command used to sign with an issuer provided as a public key, keeping its seed outside of wash
+    #[structopt(long = "signer", env = "WASH_SIGNER")]
+    signer: Option<String>,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
 #[derive(Debug, Clone, StructOpt)]
 pub(crate) enum TokenCommand {
     /// Generate a signed JWT for an actor module
@@ -155,6 +383,10 @@ struct GenerateCommon {
     #[structopt(long = "disable-keygen")]
     disable_keygen: bool,
 
+    /// Command used to sign with an issuer provided as a public key, keeping its seed outside of wash. The command receives {"public_key", "payload"} as JSON on stdin and must print {"signature"} as JSON
+    #[structopt(long = "signer", env = "WASH_SIGNER")]
+    signer: Option<String>,
+
     #[structopt(flatten)]
     pub(crate) output: Output,
 }
@@ -286,10 +518,10 @@ pub(crate) struct ActorMetadata {
     /// Enable access to an append-only event stream provider
     #[structopt(short = "e", long = "events")]
     eventstream: bool,
-    /// A human-readable, descriptive name for the token
+    /// A human-readable, descriptive name for the token. When signing multiple modules, each module is named after its file instead
     #[structopt(short = "n", long = "name")]
-    name: String,
-    /// Add custom capabilities
+    name: Option<String>,
+    /// Add capabilities by contract ID or alias, see `wash claims caps list` for known capabilities
     #[structopt(short = "c", long = "cap", name = "capabilities")]
     custom_caps: Vec<String>,
     /// A list of arbitrary tags to be embedded in the token
@@ -330,6 +562,40 @@ pub(crate) struct ActorMetadata {
     common: GenerateCommon,
 }
 
+impl ActorMetadata {
+    /// Fills in any values not supplied on the command line from a claims file
+    fn merge_claims_file(self, file: ClaimsFile) -> ActorMetadata {
+        let mut custom_caps = file.caps;
+        custom_caps.extend(self.custom_caps);
+        let mut tags = file.tags;
+        for tag in self.tags {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        ActorMetadata {
+            name: self.name.or(file.name),
+            custom_caps,
+            tags,
+            provider: self.provider || file.provider,
+            rev: self.rev.or(file.rev),
+            ver: self.ver.or(file.ver),
+            call_alias: self.call_alias.or(file.call_alias),
+            issuer: self.issuer.or(file.issuer),
+            subject: self.subject.or(file.subject),
+            common: GenerateCommon {
+                directory: self.common.directory.or(file.directory),
+                expires_in_days: self.common.expires_in_days.or(file.expires_in_days),
+                not_before_days: self.common.not_before_days.or(file.not_before_days),
+                disable_keygen: self.common.disable_keygen || file.disable_keygen,
+                signer: self.common.signer,
+                output: self.common.output,
+            },
+            ..self
+        }
+    }
+}
+
 pub(crate) async fn handle_command(
     command: ClaimsCliCommand,
 ) -> Result<String, Box<dyn ::std::error::Error>> {
@@ -337,6 +603,12 @@ pub(crate) async fn handle_command(
         ClaimsCliCommand::Inspect(inspectcmd) => render_caps(inspectcmd).await,
         ClaimsCliCommand::Sign(signcmd) => sign_file(signcmd),
         ClaimsCliCommand::Token(gencmd) => generate_token(gencmd),
+        ClaimsCliCommand::Verify(verifycmd) => verify_module(verifycmd).await,
+        ClaimsCliCommand::Update(updatecmd) => update_file(updatecmd),
+        ClaimsCliCommand::Strip(stripcmd) => strip_file(stripcmd),
+        ClaimsCliCommand::Extract(extractcmd) => extract_jwt(extractcmd),
+        ClaimsCliCommand::Diff(diffcmd) => diff_modules(diffcmd).await,
+        ClaimsCliCommand::Caps(CapsCliCommand::List(listcmd)) => list_caps(listcmd),
     }
 }
 
@@ -370,53 +642,31 @@ fn get_keypair_vec(
 }
 
 fn generate_actor(actor: ActorMetadata) -> Result<String, Box<dyn ::std::error::Error>> {
-    let issuer = extract_keypair(
+    let name = actor
+        .name
+        .clone()
+        .ok_or("A name must be provided with --name")?;
+    let issuer = extract_signer(
         actor.issuer.clone(),
-        Some(actor.name.clone()),
+        Some(name.clone()),
         actor.common.directory.clone(),
         KeyPairType::Account,
         actor.common.disable_keygen,
+        actor.common.signer.clone(),
     )?;
-    let subject = extract_keypair(
+    let subject = extract_signer(
         actor.subject.clone(),
-        Some(actor.name.clone()),
+        Some(name.clone()),
         actor.common.directory.clone(),
         KeyPairType::Module,
         actor.common.disable_keygen,
+        actor.common.signer.clone(),
     )?;
 
-    let mut caps_list = vec![];
-    if actor.keyvalue {
-        caps_list.push(wascap::caps::KEY_VALUE.to_string());
-    }
-    if actor.msg_broker {
-        caps_list.push(wascap::caps::MESSAGING.to_string());
-    }
-    if actor.http_client {
-        caps_list.push(wascap::caps::HTTP_CLIENT.to_string());
-    }
-    if actor.http_server {
-        caps_list.push(wascap::caps::HTTP_SERVER.to_string());
-    }
-    if actor.blob_store {
-        caps_list.push(wascap::caps::BLOB.to_string());
-    }
-    if actor.logging {
-        caps_list.push(wascap::caps::LOGGING.to_string());
-    }
-    if actor.eventstream {
-        caps_list.push(wascap::caps::EVENTSTREAMS.to_string());
-    }
-    if actor.extras {
-        caps_list.push(wascap::caps::EXTRAS.to_string());
-    }
-    caps_list.extend(actor.custom_caps.iter().cloned());
+    let caps_list = signing_caps(&actor)?;
 
-    if actor.provider && caps_list.len() > 1 {
-        return Err("Capability providers cannot provide multiple capabilities at once.".into());
-    }
     let claims: Claims<Actor> = Claims::<Actor>::with_dates(
-        actor.name.clone(),
+        name,
         issuer.public_key(),
         subject.public_key(),
         Some(caps_list),
@@ -429,7 +679,7 @@ fn generate_actor(actor: ActorMetadata) -> Result<String, Box<dyn ::std::error::
         sanitize_alias(actor.call_alias)?,
     );
 
-    let jwt = claims.encode(&issuer)?;
+    let jwt = encode_claims(&claims, issuer.as_ref())?;
     let out = format_output(
         jwt.clone(),
         json!({ "token": jwt }),
@@ -440,12 +690,13 @@ fn generate_actor(actor: ActorMetadata) -> Result<String, Box<dyn ::std::error::
 }
 
 fn generate_operator(operator: OperatorMetadata) -> Result<String, Box<dyn ::std::error::Error>> {
-    let self_sign_key = extract_keypair(
+    let self_sign_key = extract_signer(
         operator.issuer.clone(),
         Some(operator.name.clone()),
         operator.common.directory.clone(),
         KeyPairType::Operator,
         operator.common.disable_keygen,
+        operator.common.signer.clone(),
     )?;
 
     let additional_keys = match operator.additional_signing_keys.clone() {
@@ -471,7 +722,7 @@ fn generate_operator(operator: OperatorMetadata) -> Result<String, Box<dyn ::std
         },
     );
 
-    let jwt = claims.encode(&self_sign_key)?;
+    let jwt = encode_claims(&claims, self_sign_key.as_ref())?;
     let out = format_output(
         jwt.clone(),
         json!({ "token": jwt }),
@@ -481,19 +732,21 @@ fn generate_operator(operator: OperatorMetadata) -> Result<String, Box<dyn ::std
 }
 
 fn generate_account(account: AccountMetadata) -> Result<String, Box<dyn ::std::error::Error>> {
-    let issuer = extract_keypair(
+    let issuer = extract_signer(
         account.issuer.clone(),
         Some(account.name.clone()),
         account.common.directory.clone(),
         KeyPairType::Operator,
         account.common.disable_keygen,
+        account.common.signer.clone(),
     )?;
-    let subject = extract_keypair(
+    let subject = extract_signer(
         account.subject.clone(),
         Some(account.name.clone()),
         account.common.directory.clone(),
         KeyPairType::Account,
         account.common.disable_keygen,
+        account.common.signer.clone(),
     )?;
     let additional_keys = match account.additional_signing_keys.clone() {
         Some(keys) => get_keypair_vec(
@@ -517,7 +770,7 @@ fn generate_account(account: AccountMetadata) -> Result<String, Box<dyn ::std::e
             vec![]
         },
     );
-    let jwt = claims.encode(&issuer)?;
+    let jwt = encode_claims(&claims, issuer.as_ref())?;
     let out = format_output(
         jwt.clone(),
         json!({ "token": jwt }),
@@ -527,19 +780,21 @@ fn generate_account(account: AccountMetadata) -> Result<String, Box<dyn ::std::e
 }
 
 fn generate_provider(provider: ProviderMetadata) -> Result<String, Box<dyn ::std::error::Error>> {
-    let issuer = extract_keypair(
+    let issuer = extract_signer(
         provider.issuer.clone(),
         Some(provider.name.clone()),
         provider.common.directory.clone(),
         KeyPairType::Account,
         provider.common.disable_keygen,
+        provider.common.signer.clone(),
     )?;
-    let subject = extract_keypair(
+    let subject = extract_signer(
         provider.subject.clone(),
         Some(provider.name.clone()),
         provider.common.directory.clone(),
         KeyPairType::Service,
         provider.common.disable_keygen,
+        provider.common.signer.clone(),
     )?;
 
     let claims: Claims<CapabilityProvider> = Claims::<CapabilityProvider>::with_dates(
@@ -554,7 +809,7 @@ fn generate_provider(provider: ProviderMetadata) -> Result<String, Box<dyn ::std
         days_from_now_to_jwt_time(provider.common.not_before_days),
         days_from_now_to_jwt_time(provider.common.expires_in_days),
     );
-    let jwt = claims.encode(&issuer)?;
+    let jwt = encode_claims(&claims, issuer.as_ref())?;
     let out = format_output(
         jwt.clone(),
         json!({ "token": jwt }),
@@ -564,93 +819,54 @@ fn generate_provider(provider: ProviderMetadata) -> Result<String, Box<dyn ::std
 }
 
 fn sign_file(cmd: SignCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let metadata = match cmd.claims_file {
+        Some(ref path) => cmd.metadata.merge_claims_file(ClaimsFile::load(path)?),
+        None => cmd.metadata,
+    };
+    if let Some(modules) = batch_modules(&cmd.source)? {
+        return sign_batch(modules, cmd.destination, metadata);
+    }
+    let name = metadata
+        .name
+        .clone()
+        .ok_or("A name must be provided with --name or in the claims file")?;
+
     let mut sfile = File::open(&cmd.source).unwrap();
     let mut buf = Vec::new();
     sfile.read_to_end(&mut buf).unwrap();
 
-    let issuer = extract_keypair(
-        cmd.metadata.issuer.clone(),
+    let issuer = extract_signer(
+        metadata.issuer.clone(),
         Some(cmd.source.clone()),
-        cmd.metadata.common.directory.clone(),
+        metadata.common.directory.clone(),
         KeyPairType::Account,
-        cmd.metadata.common.disable_keygen,
+        metadata.common.disable_keygen,
+        metadata.common.signer.clone(),
     )?;
-    let subject = extract_keypair(
-        cmd.metadata.subject.clone(),
+    let subject = extract_signer(
+        metadata.subject.clone(),
         Some(cmd.source.clone()),
-        cmd.metadata.common.directory.clone(),
+        metadata.common.directory.clone(),
         KeyPairType::Module,
-        cmd.metadata.common.disable_keygen,
+        metadata.common.disable_keygen,
+        metadata.common.signer.clone(),
     )?;
 
-    let mut caps_list = vec![];
-    if cmd.metadata.keyvalue {
-        caps_list.push(wascap::caps::KEY_VALUE.to_string());
-    }
-    if cmd.metadata.msg_broker {
-        caps_list.push(wascap::caps::MESSAGING.to_string());
-    }
-    if cmd.metadata.http_client {
-        caps_list.push(wascap::caps::HTTP_CLIENT.to_string());
-    }
-    if cmd.metadata.http_server {
-        caps_list.push(wascap::caps::HTTP_SERVER.to_string());
-    }
-    if cmd.metadata.blob_store {
-        caps_list.push(wascap::caps::BLOB.to_string());
-    }
-    if cmd.metadata.logging {
-        caps_list.push(wascap::caps::LOGGING.to_string());
-    }
-    if cmd.metadata.extras {
-        caps_list.push(wascap::caps::EXTRAS.to_string());
-    }
-    if cmd.metadata.eventstream {
-        caps_list.push(wascap::caps::EVENTSTREAMS.to_string());
-    }
-    caps_list.extend(cmd.metadata.custom_caps.iter().cloned());
-
-    if cmd.metadata.provider && caps_list.len() > 1 {
-        return Err("Capability providers cannot provide multiple capabilities at once.".into());
-    }
+    let caps_list = signing_caps(&metadata)?;
 
-    let signed = sign_buffer_with_claims(
-        cmd.metadata.name.clone(),
+    let signed = sign_buffer(
         &buf,
-        subject,
-        issuer,
-        cmd.metadata.common.expires_in_days,
-        cmd.metadata.common.not_before_days,
+        name,
+        subject.public_key(),
+        issuer.as_ref(),
+        &metadata,
         caps_list.clone(),
-        cmd.metadata.tags.clone(),
-        cmd.metadata.provider,
-        cmd.metadata.rev,
-        cmd.metadata.ver.clone(),
-        sanitize_alias(cmd.metadata.call_alias)?,
+        sanitize_alias(metadata.call_alias.clone())?,
     )?;
 
     let destination = match cmd.destination.clone() {
         Some(d) => d,
-        None => {
-            let path = PathBuf::from(cmd.source.clone())
-                .parent()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .to_string();
-            let module_name = PathBuf::from(cmd.source.clone())
-                .file_stem()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .to_string();
-            // If path is empty, user supplied module in current directory
-            if path.is_empty() {
-                format!("./{}_s.wasm", module_name)
-            } else {
-                format!("{}/{}_s.wasm", path, module_name)
-            }
-        }
+        None => signed_destination(&cmd.source),
     };
 
     let mut outfile = File::create(&destination).unwrap();
@@ -662,7 +878,7 @@ fn sign_file(cmd: SignCommand) -> Result<String, Box<dyn ::std::error::Error>> {
                 caps_list.join(",")
             ),
             json!({"result": "success", "destination": destination, "capabilities": caps_list}),
-            &cmd.metadata.common.output.kind,
+            &metadata.common.output.kind,
         )),
         Err(e) => Err(Box::new(e)),
     }?;
@@ -670,33 +886,618 @@ fn sign_file(cmd: SignCommand) -> Result<String, Box<dyn ::std::error::Error>> {
     Ok(output)
 }
 
-async fn get_caps(
-    cmd: &InspectCommand,
-) -> Result<Option<Token<Actor>>, Box<dyn ::std::error::Error>> {
-    let module_bytes = match File::open(&cmd.module) {
+/// Embeds claims described by the actor metadata in a module, signed by the issuer
+fn sign_buffer(
+    buf: &[u8],
+    name: String,
+    subject: String,
+    issuer: &dyn Signer,
+    metadata: &ActorMetadata,
+    caps_list: Vec<String>,
+    call_alias: Option<String>,
+) -> Result<Vec<u8>, Box<dyn ::std::error::Error>> {
+    let claims = Claims::<Actor>::with_dates(
+        name,
+        issuer.public_key(),
+        subject,
+        Some(caps_list),
+        Some(metadata.tags.clone()),
+        days_from_now_to_jwt_time(metadata.common.not_before_days),
+        days_from_now_to_jwt_time(metadata.common.expires_in_days),
+        metadata.provider,
+        metadata.rev,
+        metadata.ver.clone(),
+        call_alias,
+    );
+    crate::signer::embed_claims(buf, &claims, issuer)
+}
+
+/// Collects the capabilities enabled by flags and `--cap`, resolving aliases through the
+/// capability catalog and removing duplicates
+fn signing_caps(metadata: &ActorMetadata) -> Result<Vec<String>, Box<dyn ::std::error::Error>> {
+    let catalog = CapabilityCatalog::load()?;
+    let flags = [
+        (metadata.keyvalue, KEY_VALUE),
+        (metadata.msg_broker, MESSAGING),
+        (metadata.http_client, HTTP_CLIENT),
+        (metadata.http_server, HTTP_SERVER),
+        (metadata.blob_store, BLOB),
+        (metadata.logging, LOGGING),
+        (metadata.extras, EXTRAS),
+        (metadata.eventstream, EVENTSTREAMS),
+    ];
+    let mut caps_list: Vec<String> = flags
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, id)| id.to_string())
+        .collect();
+    for cap in metadata.custom_caps.iter() {
+        caps_list.push(catalog.resolve(cap)?);
+    }
+    let mut seen = HashSet::new();
+    caps_list.retain(|cap| seen.insert(cap.clone()));
+
+    if metadata.provider && caps_list.len() > 1 {
+        return Err("Capability providers cannot provide multiple capabilities at once.".into());
+    }
+    Ok(caps_list)
+}
+
+/// Default location of a signed module, next to the source with a "_s" suffix
+fn signed_destination(source: &str) -> String {
+    let path = PathBuf::from(source)
+        .parent()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let module_name = PathBuf::from(source)
+        .file_stem()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    // If path is empty, user supplied module in current directory
+    if path.is_empty() {
+        format!("./{}_s.wasm", module_name)
+    } else {
+        format!("{}/{}_s.wasm", path, module_name)
+    }
+}
+
+/// Resolves the modules to sign when the source is a directory or a glob pattern. Returns
+/// `None` when the source refers to a single module
+fn batch_modules(source: &str) -> Result<Option<Vec<PathBuf>>, Box<dyn ::std::error::Error>> {
+    let path = PathBuf::from(source);
+    let is_module =
+        |p: &PathBuf| p.is_file() && p.extension().and_then(|ext| ext.to_str()) == Some("wasm");
+    let mut modules: Vec<PathBuf> = if path.is_dir() {
+        std::fs::read_dir(&path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(is_module)
+            .collect()
+    } else if !path.exists() && source.contains(&['*', '?', '['][..]) {
+        glob::glob(source)?
+            .filter_map(Result::ok)
+            .filter(is_module)
+            .collect()
+    } else {
+        return Ok(None);
+    };
+    if modules.is_empty() {
+        return Err(format!("No modules found matching {}", source).into());
+    }
+    modules.sort();
+    Ok(Some(modules))
+}
+
+enum SignOutcome {
+    Signed(String),
+    Skipped(String),
+    Failed(String),
+}
+
+/// Signs every module with the same issuer and claims, locating or generating a subject
+/// key for each module individually
+fn sign_batch(
+    modules: Vec<PathBuf>,
+    destination: Option<String>,
+    metadata: ActorMetadata,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    if metadata.name.is_some() && modules.len() > 1 {
+        return Err("A name cannot be shared between multiple modules, omit --name to name each module after its file".into());
+    }
+    if metadata.subject.is_some() {
+        return Err("A subject key cannot be shared between multiple modules, omit --subject to use a key per module".into());
+    }
+    if metadata.call_alias.is_some() {
+        return Err(
+            "A call alias must be unique and cannot be used when signing multiple modules".into(),
+        );
+    }
+    let caps_list = signing_caps(&metadata)?;
+    let issuer = extract_signer(
+        metadata.issuer.clone(),
+        modules.first().map(|m| m.to_string_lossy().to_string()),
+        metadata.common.directory.clone(),
+        KeyPairType::Account,
+        metadata.common.disable_keygen,
+        metadata.common.signer.clone(),
+    )?;
+    if let Some(ref dir) = destination {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let sign_module = |module: &PathBuf| -> Result<String, Box<dyn ::std::error::Error>> {
+        let source = module.to_string_lossy().to_string();
+        let buf = std::fs::read(module)?;
+        let subject = extract_signer(
+            None,
+            Some(source.clone()),
+            metadata.common.directory.clone(),
+            KeyPairType::Module,
+            metadata.common.disable_keygen,
+            metadata.common.signer.clone(),
+        )?;
+        let name = metadata.name.clone().unwrap_or_else(|| {
+            module
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        });
+        let signed = sign_buffer(
+            &buf,
+            name,
+            subject.public_key(),
+            issuer.as_ref(),
+            &metadata,
+            caps_list.clone(),
+            None,
+        )?;
+        let dest = match destination {
+            Some(ref dir) => PathBuf::from(dir)
+                .join(format!(
+                    "{}_s.wasm",
+                    module.file_stem().unwrap().to_string_lossy()
+                ))
+                .to_string_lossy()
+                .to_string(),
+            None => signed_destination(&source),
+        };
+        std::fs::write(&dest, signed)?;
+        Ok(dest)
+    };
+
+    let results: Vec<(String, SignOutcome)> = modules
+        .iter()
+        .map(|module| {
+            let outcome = if module
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default()
+                .ends_with("_s")
+            {
+                SignOutcome::Skipped("Module is the output of a previous signing".to_string())
+            } else {
+                match sign_module(module) {
+                    Ok(dest) => SignOutcome::Signed(dest),
+                    Err(e) => SignOutcome::Failed(e.to_string()),
+                }
+            };
+            (module.to_string_lossy().to_string(), outcome)
+        })
+        .collect();
+
+    let failed = results
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, SignOutcome::Failed(_)))
+        .count();
+    let output = render_batch(&results, &caps_list, &metadata.common.output.kind);
+    if failed > 0 {
+        Err(output.into())
+    } else {
+        Ok(output)
+    }
+}
+
+fn render_batch(
+    results: &[(String, SignOutcome)],
+    caps_list: &[String],
+    output_kind: &OutputKind,
+) -> String {
+    match *output_kind {
+        OutputKind::Json => {
+            let json: Vec<serde_json::Value> = results
+                .iter()
+                .map(|(module, outcome)| match outcome {
+                    SignOutcome::Signed(dest) => json!({"module": module, "result": "signed", "destination": dest, "capabilities": caps_list}),
+                    SignOutcome::Skipped(reason) => json!({"module": module, "result": "skipped", "reason": reason}),
+                    SignOutcome::Failed(error) => json!({"module": module, "result": "failed", "error": error}),
+                })
+                .collect();
+            format!("{}", serde_json::Value::Array(json))
+        }
+        OutputKind::Text { max_width } => {
+            let mut table = Table::new();
+            crate::util::configure_table_style(&mut table, 3, max_width);
+            table.add_row(Row::new(vec![
+                TableCell::new_with_alignment("Module", 1, Alignment::Left),
+                TableCell::new_with_alignment("Result", 1, Alignment::Left),
+                TableCell::new_with_alignment("Details", 1, Alignment::Left),
+            ]));
+            for (module, outcome) in results {
+                let (result, details) = match outcome {
+                    SignOutcome::Signed(dest) => ("Signed", dest),
+                    SignOutcome::Skipped(reason) => ("Skipped", reason),
+                    SignOutcome::Failed(error) => ("Failed", error),
+                };
+                table.add_row(Row::new(vec![
+                    TableCell::new_with_alignment(module, 1, Alignment::Left),
+                    TableCell::new_with_alignment(result, 1, Alignment::Left),
+                    TableCell::new_with_alignment(details, 1, Alignment::Left),
+                ]));
+            }
+            table.render()
+        }
+    }
+}
+
+fn list_caps(cmd: CapsListCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let catalog = CapabilityCatalog::load()?;
+    let source = |builtin: bool| if builtin { "built-in" } else { "user" };
+    match cmd.output.kind {
+        OutputKind::Json => {
+            let caps: Vec<serde_json::Value> = catalog
+                .capabilities()
+                .iter()
+                .map(|c| json!({"id": c.id, "name": c.name, "aliases": c.aliases, "source": source(c.builtin)}))
+                .collect();
+            Ok(format!("{}", json!({ "capabilities": caps })))
+        }
+        OutputKind::Text { max_width } => {
+            let mut table = Table::new();
+            crate::util::configure_table_style(&mut table, 4, max_width);
+            table.add_row(Row::new(vec![
+                TableCell::new_with_alignment("Contract ID", 1, Alignment::Left),
+                TableCell::new_with_alignment("Name", 1, Alignment::Left),
+                TableCell::new_with_alignment("Aliases", 1, Alignment::Left),
+                TableCell::new_with_alignment("Source", 1, Alignment::Left),
+            ]));
+            for c in catalog.capabilities() {
+                table.add_row(Row::new(vec![
+                    TableCell::new_with_alignment(&c.id, 1, Alignment::Left),
+                    TableCell::new_with_alignment(&c.name, 1, Alignment::Left),
+                    TableCell::new_with_alignment(c.aliases.join(", "), 1, Alignment::Left),
+                    TableCell::new_with_alignment(source(c.builtin), 1, Alignment::Left),
+                ]));
+            }
+            Ok(table.render())
+        }
+    }
+}
+
+/// Splits a signed module into the module without its `jwt` custom section and the
+/// embedded JWT. The module hash is not validated so tokens can be recovered from any module.
+fn split_claims(
+    buf: &[u8],
+    module_path: &str,
+) -> Result<(Vec<u8>, String), Box<dyn ::std::error::Error>> {
+    let mut module: parity_wasm::elements::Module = parity_wasm::deserialize_buffer(buf)
+        .map_err(|e| format!("{} is not a valid WebAssembly module: {}", module_path, e))?;
+    let jwt = match module.custom_sections().find(|sect| sect.name() == "jwt") {
+        Some(sect) => String::from_utf8(sect.payload().to_vec())?,
+        None => return Err(format!("No claims found in {}", module_path).into()),
+    };
+    module.clear_custom_section("jwt");
+    Ok((parity_wasm::serialize(module)?, jwt))
+}
+
+/// Places a file derived from `source` next to it, replacing the "_s" suffix of signed modules
+fn sibling_destination(source: &str, suffix: &str) -> String {
+    let source = PathBuf::from(source);
+    let stem = source.file_stem().unwrap().to_string_lossy();
+    let stem = stem.strip_suffix("_s").unwrap_or(&stem);
+    source
+        .with_file_name(format!("{}{}", stem, suffix))
+        .to_string_lossy()
+        .to_string()
+}
+
+fn strip_file(cmd: StripCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let buf = std::fs::read(&cmd.module)?;
+    let (unsigned, _) = split_claims(&buf, &cmd.module)?;
+
+    let destination = match cmd.destination {
+        Some(d) => d,
+        None => sibling_destination(&cmd.module, "_unsigned.wasm"),
+    };
+    let mut outfile = File::create(&destination)?;
+    outfile.write_all(&unsigned)?;
+
+    Ok(format_output(
+        format!(
+            "Successfully removed claims from {}, unsigned module written to {}",
+            cmd.module, destination
+        ),
+        json!({"result": "success", "destination": destination}),
+        &cmd.output.kind,
+    ))
+}
+
+fn extract_jwt(cmd: ExtractCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let buf = std::fs::read(&cmd.module)?;
+    let (_, jwt) = split_claims(&buf, &cmd.module)?;
+    let claims = Claims::<Actor>::decode(&jwt)?;
+
+    let destination = match cmd.destination {
+        Some(d) => d,
+        None => sibling_destination(&cmd.module, ".jwt"),
+    };
+    let mut outfile = File::create(&destination)?;
+    outfile.write_all(jwt.as_bytes())?;
+
+    Ok(format_output(
+        format!(
+            "Successfully extracted claims for module {} to {}",
+            claims.subject, destination
+        ),
+        json!({"result": "success", "destination": destination, "module": claims.subject}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Applies the changes requested by an update command to an existing set of actor claims
+fn update_claims(
+    claims: Claims<Actor>,
+    cmd: &UpdateCommand,
+    issuer: String,
+) -> Result<Claims<Actor>, Box<dyn ::std::error::Error>> {
+    let md = claims.metadata.unwrap_or_default();
+    let catalog = CapabilityCatalog::load()?;
+
+    let mut caps = md.caps.unwrap_or_default();
+    for cap in cmd.add_caps.iter() {
+        let cap = catalog.resolve(cap)?;
+        if !caps.contains(&cap) {
+            caps.push(cap);
+        }
+    }
+    let remove_caps = cmd
+        .remove_caps
+        .iter()
+        .map(|cap| catalog.resolve(cap))
+        .collect::<Result<Vec<_>, _>>()?;
+    caps.retain(|cap| !remove_caps.contains(cap));
+    if md.provider && caps.len() > 1 {
+        return Err("Capability providers cannot provide multiple capabilities at once.".into());
+    }
+
+    let mut tags = md.tags.unwrap_or_default();
+    for tag in cmd.add_tags.iter() {
+        if !tags.contains(tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags.retain(|tag| !cmd.remove_tags.contains(tag));
+
+    let rev = if cmd.bump_rev {
+        Some(md.rev.unwrap_or(0) + 1)
+    } else {
+        cmd.rev.or(md.rev)
+    };
+    let call_alias = match cmd.call_alias.clone() {
+        Some(alias) => sanitize_alias(Some(alias))?,
+        None => md.call_alias,
+    };
+
+    Ok(Claims::<Actor>::with_dates(
+        cmd.name.clone().or(md.name).unwrap_or_default(),
+        issuer,
+        claims.subject,
+        Some(caps),
+        Some(tags),
+        days_from_now_to_jwt_time(cmd.not_before_days).or(claims.not_before),
+        days_from_now_to_jwt_time(cmd.expires_in_days).or(claims.expires),
+        md.provider,
+        rev,
+        cmd.ver.clone().or(md.ver),
+        call_alias,
+    ))
+}
+
+fn update_file(cmd: UpdateCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let mut sfile = File::open(&cmd.source)?;
+    let mut buf = Vec::new();
+    sfile.read_to_end(&mut buf)?;
+
+    let token = wascap::wasm::extract_claims(&buf)?.ok_or_else(|| {
+        format!(
+            "No claims found in {}, use `wash claims sign` to sign it",
+            cmd.source
+        )
+    })?;
+
+    let issuer = extract_signer(
+        cmd.issuer.clone(),
+        Some(cmd.source.clone()),
+        cmd.directory.clone(),
+        KeyPairType::Account,
+        cmd.disable_keygen,
+        cmd.signer.clone(),
+    )?;
+    if issuer.public_key() != token.claims.issuer && !cmd.rotate_issuer {
+        return Err(format!(
+            "Module was signed by {}, but the provided issuer is {}. Use --rotate-issuer to re-sign the module with a different account",
+            token.claims.issuer,
+            issuer.public_key()
+        )
+        .into());
+    }
+
+    let claims = update_claims(token.claims, &cmd, issuer.public_key())?;
+    let caps_list = claims
+        .metadata
+        .as_ref()
+        .and_then(|md| md.caps.clone())
+        .unwrap_or_default();
+    let signed = crate::signer::embed_claims(&buf, &claims, issuer.as_ref())?;
+
+    let destination = cmd
+        .destination
+        .clone()
+        .unwrap_or_else(|| cmd.source.clone());
+    let mut outfile = File::create(&destination)?;
+    outfile.write_all(&signed)?;
+
+    Ok(format_output(
+        format!(
+            "Successfully updated {} with capabilities: {}",
+            destination,
+            caps_list.join(",")
+        ),
+        json!({"result": "success", "destination": destination, "capabilities": caps_list, "issuer": claims.issuer}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Reads a module from disk, or pulls it from an OCI registry if no such file exists
+async fn get_module_bytes(
+    module: &str,
+    digest: Option<String>,
+    opts: &PullOptions,
+) -> Result<Vec<u8>, Box<dyn ::std::error::Error>> {
+    match File::open(module) {
         Ok(mut f) => {
             let mut buf = Vec::new();
-            f.read_to_end(&mut buf).unwrap();
-            buf
+            f.read_to_end(&mut buf)?;
+            Ok(buf)
         }
-        Err(_) => {
-            crate::reg::pull_artifact(
-                cmd.module.to_string(),
-                cmd.digest.clone(),
-                cmd.allow_latest,
-                cmd.user.clone(),
-                cmd.password.clone(),
-                cmd.insecure,
-            )
-            .await?
+        Err(_) => crate::reg::pull_artifact(module.to_string(), digest, opts).await,
+    }
+}
+
+/// Claims of any entity that can be inspected, along with the raw JWT they were decoded from
+enum InspectedClaims {
+    Actor(Token<Actor>),
+    Provider(Token<CapabilityProvider>),
+    Account(Token<Account>),
+    Operator(Token<Operator>),
+}
+
+impl InspectedClaims {
+    fn jwt(&self) -> &str {
+        match self {
+            InspectedClaims::Actor(token) => &token.jwt,
+            InspectedClaims::Provider(token) => &token.jwt,
+            InspectedClaims::Account(token) => &token.jwt,
+            InspectedClaims::Operator(token) => &token.jwt,
         }
-    };
+    }
 
-    // Extract will return an error if it encounters an invalid hash in the claims
-    let claims = wascap::wasm::extract_claims(&module_bytes);
-    match claims {
-        Ok(token) => Ok(token),
-        Err(e) => Err(Box::new(e)),
+    fn render(self, output: &Output) -> Result<String, Box<dyn ::std::error::Error>> {
+        Ok(match self {
+            InspectedClaims::Actor(token) => {
+                let validation = wascap::jwt::validate_token::<Actor>(&token.jwt)?;
+                render_actor_claims(token.claims, validation, output)
+            }
+            InspectedClaims::Provider(token) => {
+                let validation = wascap::jwt::validate_token::<CapabilityProvider>(&token.jwt)?;
+                render_provider_claims(token.claims, validation, output)?
+            }
+            InspectedClaims::Account(token) => {
+                let validation = wascap::jwt::validate_token::<Account>(&token.jwt)?;
+                let signers = token
+                    .claims
+                    .metadata
+                    .as_ref()
+                    .and_then(|md| md.valid_signers.clone());
+                render_signer_claims(token.claims, signers, validation, output)
+            }
+            InspectedClaims::Operator(token) => {
+                let validation = wascap::jwt::validate_token::<Operator>(&token.jwt)?;
+                let signers = token
+                    .claims
+                    .metadata
+                    .as_ref()
+                    .and_then(|md| md.valid_signers.clone());
+                render_signer_claims(token.claims, signers, validation, output)
+            }
+        })
+    }
+}
+
+/// Returns true if the input has the `header.claims.signature` shape of a JWT
+fn is_jwt(input: &str) -> bool {
+    let segments: Vec<&str> = input.split('.').collect();
+    segments.len() == 3
+        && segments.iter().all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// Decodes a raw JWT, using the key prefix of the subject to determine which entity it describes
+fn decode_jwt(jwt: &str) -> Result<InspectedClaims, Box<dyn ::std::error::Error>> {
+    // Account metadata has no required fields, so any wascap token can be decoded as one
+    let subject = Claims::<Account>::decode(jwt)?.subject;
+    let jwt = jwt.to_string();
+    match subject.chars().next() {
+        Some('M') => Ok(InspectedClaims::Actor(Token {
+            claims: Claims::decode(&jwt)?,
+            jwt,
+        })),
+        Some('V') => Ok(InspectedClaims::Provider(Token {
+            claims: Claims::decode(&jwt)?,
+            jwt,
+        })),
+        Some('A') => Ok(InspectedClaims::Account(Token {
+            claims: Claims::decode(&jwt)?,
+            jwt,
+        })),
+        Some('O') => Ok(InspectedClaims::Operator(Token {
+            claims: Claims::decode(&jwt)?,
+            jwt,
+        })),
+        _ => Err(format!("Unsupported token subject {}", subject).into()),
+    }
+}
+
+/// Returns true if the bytes are a (possibly compressed) TAR archive
+fn is_archive(bytes: &[u8]) -> bool {
+    const TAR_MAGIC_OFFSET: usize = 257;
+    crate::par::is_compressed(bytes).unwrap_or(false)
+        || bytes.get(TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + 5) == Some(b"ustar")
+}
+
+async fn get_caps(
+    cmd: &InspectCommand,
+) -> Result<Option<InspectedClaims>, Box<dyn ::std::error::Error>> {
+    // A raw JWT may be supplied directly instead of a file or OCI reference
+    if !std::path::Path::new(&cmd.module).exists() && is_jwt(&cmd.module) {
+        return decode_jwt(&cmd.module).map(Some);
+    }
+
+    let module_bytes = get_module_bytes(&cmd.module, cmd.digest.clone(), &cmd.opts).await?;
+
+    if module_bytes.starts_with(b"\0asm") {
+        // Extract will return an error if it encounters an invalid hash in the claims
+        Ok(wascap::wasm::extract_claims(&module_bytes)?.map(InspectedClaims::Actor))
+    } else if is_archive(&module_bytes) {
+        // Loading the archive validates the hashes of each library against the claims
+        let (_, token) = crate::par::parse_archive(&module_bytes)?;
+        Ok(Some(InspectedClaims::Provider(token)))
+    } else {
+        match std::str::from_utf8(&module_bytes) {
+            Ok(contents) if is_jwt(contents.trim()) => decode_jwt(contents.trim()).map(Some),
+            _ => Err(format!(
+                "{} is not an actor module, provider archive or JWT",
+                cmd.module
+            )
+            .into()),
+        }
     }
 }
 
@@ -704,12 +1505,11 @@ async fn render_caps(cmd: InspectCommand) -> Result<String, Box<dyn ::std::error
     let caps = get_caps(&cmd).await?;
 
     let out = match caps {
-        Some(token) => {
+        Some(claims) => {
             if cmd.jwt_only {
-                token.jwt
+                claims.jwt().to_string()
             } else {
-                let validation = wascap::jwt::validate_token::<Actor>(&token.jwt)?;
-                render_actor_claims(token.claims, validation, &cmd.output)
+                claims.render(&cmd.output)?
             }
         }
         None => format!("No capabilities discovered in : {}", &cmd.module),
@@ -717,6 +1517,270 @@ async fn render_caps(cmd: InspectCommand) -> Result<String, Box<dyn ::std::error
     Ok(out)
 }
 
+/// A broken link in the operator -> account -> actor chain of trust
+#[derive(Debug, Clone, PartialEq)]
+struct ChainFailure {
+    /// The token that failed verification, one of `operator`, `account` or `actor`
+    token: &'static str,
+    /// Machine-readable reason for the failure, e.g. `untrusted_issuer`
+    reason: &'static str,
+    message: String,
+}
+
+impl ChainFailure {
+    fn new(token: &'static str, reason: &'static str, message: String) -> Self {
+        ChainFailure {
+            token,
+            reason,
+            message,
+        }
+    }
+
+    fn render(&self, output: &OutputKind) -> String {
+        format_output(
+            format!(
+                "Verification of the {} token failed ({}): {}",
+                self.token, self.reason, self.message
+            ),
+            json!({"valid": false, "token": self.token, "reason": self.reason, "message": self.message}),
+            output,
+        )
+    }
+}
+
+/// The verified tokens of an operator -> account -> actor chain of trust
+#[derive(Debug)]
+struct TrustChain {
+    operator: Claims<Operator>,
+    account: Claims<Account>,
+    actor: Claims<Actor>,
+}
+
+/// Checks the signature and validity period of a single token
+fn verify_token<T>(token: &'static str, jwt: &str) -> Result<Claims<T>, ChainFailure>
+where
+    T: serde::Serialize + DeserializeOwned + WascapEntity,
+{
+    let claims = Claims::<T>::decode(jwt)
+        .map_err(|e| ChainFailure::new(token, "invalid_token", format!("{}", e)))?;
+    let validation = wascap::jwt::validate_token::<T>(jwt)
+        .map_err(|e| ChainFailure::new(token, "invalid_token", format!("{}", e)))?;
+
+    if !validation.signature_valid {
+        Err(ChainFailure::new(
+            token,
+            "invalid_signature",
+            format!("Token was not signed by its issuer {}", claims.issuer),
+        ))
+    } else if validation.expired {
+        Err(ChainFailure::new(
+            token,
+            "expired",
+            format!("Token expired {}", validation.expires_human),
+        ))
+    } else if validation.cannot_use_yet {
+        Err(ChainFailure::new(
+            token,
+            "not_yet_valid",
+            format!("Token cannot be used until {}", validation.not_before_human),
+        ))
+    } else {
+        Ok(claims)
+    }
+}
+
+/// Returns the subject of a token along with any additional keys it permits to sign on its behalf
+fn valid_signers(subject: &str, additional_signing_keys: Option<Vec<String>>) -> Vec<String> {
+    let mut signers = vec![subject.to_string()];
+    signers.extend(additional_signing_keys.unwrap_or_default());
+    signers
+}
+
+/// Verifies that the operator is self-signed, the account was issued by the operator (or one of
+/// its additional signing keys) and the actor was issued by the account (or one of its additional signing keys)
+fn verify_chain(
+    operator_jwt: &str,
+    account_jwt: &str,
+    actor_jwt: &str,
+) -> Result<TrustChain, ChainFailure> {
+    let operator = verify_token::<Operator>("operator", operator_jwt)?;
+    let account = verify_token::<Account>("account", account_jwt)?;
+    let actor = verify_token::<Actor>("actor", actor_jwt)?;
+
+    if operator.issuer != operator.subject || !operator.issuer.starts_with('O') {
+        return Err(ChainFailure::new(
+            "operator",
+            "not_self_signed",
+            format!(
+                "Operator {} was not issued by its own operator key",
+                operator.subject
+            ),
+        ));
+    }
+
+    // Tokens of every entity decode as any other, so each key must be of the expected type
+    if !account.subject.starts_with('A') || !account.issuer.starts_with('O') {
+        return Err(ChainFailure::new(
+            "account",
+            "invalid_account",
+            format!(
+                "{} is not an account issued by an operator key (issuer {})",
+                account.subject, account.issuer
+            ),
+        ));
+    }
+    if !actor.subject.starts_with('M') || !actor.issuer.starts_with('A') {
+        return Err(ChainFailure::new(
+            "actor",
+            "invalid_module",
+            format!(
+                "{} is not a module issued by an account key (issuer {})",
+                actor.subject, actor.issuer
+            ),
+        ));
+    }
+
+    let operator_signers = valid_signers(
+        &operator.subject,
+        operator
+            .metadata
+            .as_ref()
+            .and_then(|md| md.valid_signers.clone()),
+    );
+    if !operator_signers.contains(&account.issuer) {
+        return Err(ChainFailure::new(
+            "account",
+            "untrusted_issuer",
+            format!(
+                "Account {} was issued by {}, which is not a valid signer for operator {}",
+                account.subject, account.issuer, operator.subject
+            ),
+        ));
+    }
+
+    let account_signers = valid_signers(
+        &account.subject,
+        account
+            .metadata
+            .as_ref()
+            .and_then(|md| md.valid_signers.clone()),
+    );
+    if !account_signers.contains(&actor.issuer) {
+        return Err(ChainFailure::new(
+            "actor",
+            "untrusted_issuer",
+            format!(
+                "Module {} was issued by {}, which is not a valid signer for account {}",
+                actor.subject, actor.issuer, account.subject
+            ),
+        ));
+    }
+
+    Ok(TrustChain {
+        operator,
+        account,
+        actor,
+    })
+}
+
+async fn verify_module(cmd: VerifyCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let module_bytes = get_module_bytes(&cmd.module, cmd.digest.clone(), &cmd.opts).await?;
+    let operator_jwt = extract_arg_value(&cmd.operator)?;
+    let account_jwt = extract_arg_value(&cmd.account)?;
+
+    // Extract will return an error if it encounters an invalid hash in the claims
+    let result = match wascap::wasm::extract_claims(&module_bytes) {
+        Ok(Some(token)) => verify_chain(operator_jwt.trim(), account_jwt.trim(), &token.jwt),
+        Ok(None) => Err(ChainFailure::new(
+            "actor",
+            "no_claims",
+            format!("No claims embedded in {}", cmd.module),
+        )),
+        Err(e) => Err(ChainFailure::new(
+            "actor",
+            "invalid_token",
+            format!("{}", e),
+        )),
+    };
+
+    match result {
+        Ok(chain) => Ok(format_output(
+            format!(
+                "Verified chain of trust: operator {} -> account {} -> module {}",
+                chain.operator.subject, chain.account.subject, chain.actor.subject
+            ),
+            json!({"valid": true,
+                "operator": chain.operator.subject,
+                "account": chain.account.subject,
+                "module": chain.actor.subject}),
+            &cmd.output.kind,
+        )),
+        Err(failure) => Err(FailedOutput(failure.render(&cmd.output.kind)).into()),
+    }
+}
+
+/// Compares the claims embedded in two signed modules
+async fn diff_modules(cmd: DiffCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let mut claims = Vec::new();
+    for &(module, digest) in [
+        (&cmd.left, &cmd.left_digest),
+        (&cmd.right, &cmd.right_digest),
+    ]
+    .iter()
+    {
+        let module_bytes = get_module_bytes(module, digest.clone(), &cmd.opts).await?;
+        let token = wascap::wasm::extract_claims(&module_bytes)?
+            .ok_or_else(|| format!("No claims embedded in {}", module))?;
+        claims.push(token.claims);
+    }
+    let right = claims.pop().unwrap();
+    let left = claims.pop().unwrap();
+
+    Ok(diff_actor_claims(&left, &right).render(&cmd.left, &cmd.right, &cmd.output.kind))
+}
+
+fn diff_actor_claims(left: &Claims<Actor>, right: &Claims<Actor>) -> Diff {
+    let mut diff = Diff::new();
+    diff.field("name", Some(left.name()), Some(right.name()));
+    diff.field("account", Some(&left.issuer), Some(&right.issuer));
+    diff.field("module", Some(&left.subject), Some(&right.subject));
+    diff.field("expires", left.expires, right.expires);
+    diff.field("not_before", left.not_before, right.not_before);
+
+    let (lmd, rmd) = (left.metadata.as_ref(), right.metadata.as_ref());
+    diff.field(
+        "module_hash",
+        lmd.map(|md| &md.module_hash),
+        rmd.map(|md| &md.module_hash),
+    );
+    diff.field(
+        "revision",
+        lmd.and_then(|md| md.rev),
+        rmd.and_then(|md| md.rev),
+    );
+    diff.field(
+        "version",
+        lmd.and_then(|md| md.ver.as_ref()),
+        rmd.and_then(|md| md.ver.as_ref()),
+    );
+    diff.field(
+        "call_alias",
+        lmd.and_then(|md| md.call_alias.as_ref()),
+        rmd.and_then(|md| md.call_alias.as_ref()),
+    );
+    diff.list(
+        "capabilities",
+        &lmd.and_then(|md| md.caps.clone()).unwrap_or_default(),
+        &rmd.and_then(|md| md.caps.clone()).unwrap_or_default(),
+    );
+    diff.list(
+        "tags",
+        &lmd.and_then(|md| md.tags.clone()).unwrap_or_default(),
+        &rmd.and_then(|md| md.tags.clone()).unwrap_or_default(),
+    );
+    diff
+}
+
 /// Renders actor claims into provided output format
 pub(crate) fn render_actor_claims(
     claims: Claims<Actor>,
@@ -744,7 +1808,8 @@ pub(crate) fn render_actor_claims(
     };
 
     let friendly_caps: Vec<String> = if let Some(caps) = &claims.metadata.as_ref().unwrap().caps {
-        caps.iter().map(|c| capability_name(&c)).collect()
+        let catalog = CapabilityCatalog::load().unwrap_or_else(|_| CapabilityCatalog::builtin());
+        caps.iter().map(|c| catalog.name(c)).collect()
     } else {
         vec![]
     };
@@ -802,13 +1867,133 @@ pub(crate) fn render_actor_claims(
             )]));
 
             table.add_row(Row::new(vec![TableCell::new_with_alignment(
-                "Tags",
+                "Tags",
+                2,
+                Alignment::Center,
+            )]));
+
+            table.add_row(Row::new(vec![TableCell::new_with_alignment(
+                tags,
+                2,
+                Alignment::Left,
+            )]));
+
+            table.render()
+        }
+    }
+}
+
+/// Renders capability provider claims into provided output format
+fn render_provider_claims(
+    claims: Claims<CapabilityProvider>,
+    validation: TokenValidation,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let md = claims
+        .metadata
+        .clone()
+        .ok_or("No metadata found in provider claims")?;
+    let friendly_rev = md.rev.unwrap_or(0);
+    let friendly_ver = md.ver.unwrap_or_else(|| "None".to_string());
+    let friendly = format!("{} ({})", friendly_ver, friendly_rev);
+
+    let mut targets: Vec<String> = md.target_hashes.keys().cloned().collect();
+    targets.sort();
+
+    Ok(match output.kind {
+        OutputKind::Json => {
+            let iss_label = token_label(&claims.issuer).to_ascii_lowercase();
+            let sub_label = token_label(&claims.subject).to_ascii_lowercase();
+            format!(
+                "{}",
+                json!({ iss_label: claims.issuer,
+                sub_label: claims.subject,
+                "expires": validation.expires_human,
+                "can_be_used": validation.not_before_human,
+                "capability_contract_id": md.capid,
+                "vendor": md.vendor,
+                "version": friendly_ver,
+                "revision": friendly_rev,
+                "targets": targets,
+                })
+            )
+        }
+        OutputKind::Text { max_width } => {
+            let mut table = render_core(&claims, validation, max_width);
+
+            table.add_row(Row::new(vec![
+                TableCell::new("Capability Contract ID"),
+                TableCell::new_with_alignment(md.capid, 1, Alignment::Right),
+            ]));
+
+            table.add_row(Row::new(vec![
+                TableCell::new("Vendor"),
+                TableCell::new_with_alignment(md.vendor, 1, Alignment::Right),
+            ]));
+
+            table.add_row(Row::new(vec![
+                TableCell::new("Version"),
+                TableCell::new_with_alignment(friendly, 1, Alignment::Right),
+            ]));
+
+            table.add_row(Row::new(vec![TableCell::new_with_alignment(
+                "Supported Architecture Targets",
+                2,
+                Alignment::Center,
+            )]));
+
+            table.add_row(Row::new(vec![TableCell::new_with_alignment(
+                targets.join("\n"),
+                2,
+                Alignment::Left,
+            )]));
+
+            table.render()
+        }
+    })
+}
+
+/// Renders operator or account claims, including their additional signing keys, into provided output format
+fn render_signer_claims<T>(
+    claims: Claims<T>,
+    valid_signers: Option<Vec<String>>,
+    validation: TokenValidation,
+    output: &Output,
+) -> String
+where
+    T: serde::Serialize + DeserializeOwned + WascapEntity,
+{
+    let signers = valid_signers.unwrap_or_default();
+
+    match output.kind {
+        OutputKind::Json => {
+            let iss_label = token_label(&claims.issuer).to_ascii_lowercase();
+            let sub_label = token_label(&claims.subject).to_ascii_lowercase();
+            format!(
+                "{}",
+                json!({ iss_label: claims.issuer,
+                sub_label: claims.subject,
+                "expires": validation.expires_human,
+                "can_be_used": validation.not_before_human,
+                "additional_signing_keys": signers,
+                })
+            )
+        }
+        OutputKind::Text { max_width } => {
+            let mut table = render_core(&claims, validation, max_width);
+
+            table.add_row(Row::new(vec![TableCell::new_with_alignment(
+                "Additional Signing Keys",
                 2,
                 Alignment::Center,
             )]));
 
             table.add_row(Row::new(vec![TableCell::new_with_alignment(
-                tags,
+                if signers.is_empty() {
+                    "None".to_string()
+                } else {
+                    signers.join("\n")
+                },
                 2,
                 Alignment::Left,
             )]));
@@ -818,9 +2003,6 @@ pub(crate) fn render_actor_claims(
     }
 }
 
-// * - we don't need render impls for Operator or Account because those tokens are never embedded into a module,
-// only actors.
-
 fn token_label(pk: &str) -> String {
     match pk.chars().next().unwrap() {
         'A' => "Account".to_string(),
@@ -828,6 +2010,7 @@ fn token_label(pk: &str) -> String {
         'O' => "Operator".to_string(),
         'S' => "Server".to_string(),
         'U' => "User".to_string(),
+        'V' => "Service".to_string(),
         _ => "<Unknown>".to_string(),
     }
 }
@@ -969,6 +2152,7 @@ mod test {
             "opensesame",
             "--allow-latest",
             "--insecure",
+            "--no-cache",
             "--jwt-only",
         ])
         .unwrap();
@@ -978,10 +2162,7 @@ mod test {
                 module,
                 jwt_only,
                 digest,
-                allow_latest,
-                user,
-                password,
-                insecure,
+                opts,
                 output,
             }) => {
                 assert_eq!(module, SUBSCRIBER_OCI);
@@ -990,10 +2171,11 @@ mod test {
                     "sha256:5790f650cff526fcbc1271107a05111a6647002098b74a9a5e2e26e3c0a116b8"
                 );
                 assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
-                assert_eq!(user.unwrap(), "name");
-                assert_eq!(password.unwrap(), "opensesame");
-                assert!(allow_latest);
-                assert!(insecure);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "opensesame");
+                assert!(opts.allow_latest);
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
                 assert!(jwt_only);
             }
             cmd => panic!("claims constructed incorrect command: {:?}", cmd),
@@ -1013,6 +2195,7 @@ mod test {
             "opensesame",
             "--allow-latest",
             "--insecure",
+            "--no-cache",
             "--jwt-only",
         ])
         .unwrap();
@@ -1022,10 +2205,7 @@ mod test {
                 module,
                 jwt_only,
                 digest,
-                allow_latest,
-                user,
-                password,
-                insecure,
+                opts,
                 output,
             }) => {
                 assert_eq!(module, SUBSCRIBER_OCI);
@@ -1034,10 +2214,11 @@ mod test {
                     "sha256:5790f650cff526fcbc1271107a05111a6647002098b74a9a5e2e26e3c0a116b8"
                 );
                 assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
-                assert_eq!(user.unwrap(), "name");
-                assert_eq!(password.unwrap(), "opensesame");
-                assert!(allow_latest);
-                assert!(insecure);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "opensesame");
+                assert!(opts.allow_latest);
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
                 assert!(jwt_only);
             }
             cmd => panic!("claims constructed incorrect command: {:?}", cmd),
@@ -1089,6 +2270,8 @@ mod test {
             "--msg",
             "--prov",
             "--disable-keygen",
+            "--claims-file",
+            "./actor.toml",
         ])
         .unwrap();
 
@@ -1096,10 +2279,12 @@ mod test {
             ClaimsCliCommand::Sign(SignCommand {
                 source,
                 destination,
+                claims_file,
                 metadata,
             }) => {
                 assert_eq!(source, LOCAL_WASM);
                 assert_eq!(destination.unwrap(), "./myactor_s.wasm");
+                assert_eq!(claims_file.unwrap(), "./actor.toml");
                 assert_eq!(metadata.common.directory.unwrap(), "./dir");
                 assert_eq!(metadata.common.expires_in_days.unwrap(), 3);
                 assert_eq!(metadata.common.not_before_days.unwrap(), 1);
@@ -1113,7 +2298,7 @@ mod test {
                 assert!(metadata.extras);
                 assert!(metadata.logging);
                 assert!(metadata.eventstream);
-                assert_eq!(metadata.name, "MyActor");
+                assert_eq!(metadata.name.unwrap(), "MyActor");
                 assert!(!metadata.custom_caps.is_empty());
                 assert_eq!(metadata.custom_caps[0], "test:custom");
                 assert!(!metadata.tags.is_empty());
@@ -1169,9 +2354,11 @@ mod test {
             ClaimsCliCommand::Sign(SignCommand {
                 source,
                 destination,
+                claims_file,
                 metadata,
             }) => {
                 assert_eq!(source, LOCAL_WASM);
+                assert!(claims_file.is_none());
                 assert_eq!(destination.unwrap(), "./myactor_s.wasm");
                 assert_eq!(metadata.common.directory.unwrap(), "./dir");
                 assert_eq!(metadata.common.expires_in_days.unwrap(), 3);
@@ -1186,7 +2373,7 @@ mod test {
                 assert!(metadata.extras);
                 assert!(metadata.logging);
                 assert!(metadata.eventstream);
-                assert_eq!(metadata.name, "MyActor");
+                assert_eq!(metadata.name.unwrap(), "MyActor");
                 assert!(!metadata.custom_caps.is_empty());
                 assert_eq!(metadata.custom_caps[0], "test:custom");
                 assert!(!metadata.tags.is_empty());
@@ -1322,7 +2509,7 @@ mod test {
                 ver,
                 ..
             })) => {
-                assert_eq!(name, "TokenName");
+                assert_eq!(name.unwrap(), "TokenName");
                 assert_eq!(common.directory.unwrap(), DIR);
                 assert_eq!(
                     common.expires_in_days.unwrap(),
@@ -1464,4 +2651,614 @@ mod test {
             cmd => panic!("claims constructed incorrect command: {:?}", cmd),
         }
     }
+
+    #[test]
+    /// Enumerates all options and flags of the `claims verify` command
+    /// to ensure command line arguments do not change between versions
+    fn test_claims_verify_comprehensive() {
+        const OPERATOR_JWT: &str = "./operator.jwt";
+        const ACCOUNT_JWT: &str = "./account.jwt";
+        let cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "verify",
+            SUBSCRIBER_OCI,
+            "--operator",
+            OPERATOR_JWT,
+            "--account",
+            ACCOUNT_JWT,
+            "--digest",
+            "sha256:5790f650cff526fcbc1271107a05111a6647002098b74a9a5e2e26e3c0a116b8",
+            "--user",
+            "name",
+            "--password",
+            "opensesame",
+            "--allow-latest",
+            "--insecure",
+            "--no-cache",
+            "-o",
+            "json",
+        ])
+        .unwrap();
+
+        match cmd.command {
+            ClaimsCliCommand::Verify(VerifyCommand {
+                module,
+                operator,
+                account,
+                digest,
+                opts,
+                output,
+            }) => {
+                assert_eq!(module, SUBSCRIBER_OCI);
+                assert_eq!(operator, OPERATOR_JWT);
+                assert_eq!(account, ACCOUNT_JWT);
+                assert_eq!(
+                    digest.unwrap(),
+                    "sha256:5790f650cff526fcbc1271107a05111a6647002098b74a9a5e2e26e3c0a116b8"
+                );
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "opensesame");
+                assert!(opts.allow_latest);
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+    }
+
+    #[test]
+    fn test_claims_diff_comprehensive() {
+        const LOCAL: &str = "./echo_s.wasm";
+        let cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "diff",
+            LOCAL,
+            SUBSCRIBER_OCI,
+            "--user",
+            "name",
+            "--password",
+            "opensesame",
+            "--allow-latest",
+            "--insecure",
+            "--no-cache",
+            "--right-digest",
+            "sha256:blah",
+            "-o",
+            "json",
+        ])
+        .unwrap();
+
+        match cmd.command {
+            ClaimsCliCommand::Diff(DiffCommand {
+                left,
+                right,
+                left_digest,
+                right_digest,
+                opts,
+                output,
+            }) => {
+                assert_eq!(left, LOCAL);
+                assert_eq!(right, SUBSCRIBER_OCI);
+                assert!(left_digest.is_none());
+                assert_eq!(right_digest.unwrap(), "sha256:blah");
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "opensesame");
+                assert!(opts.allow_latest);
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+    }
+
+    #[test]
+    fn test_claims_verify_chain() {
+        let operator_kp = KeyPair::new_operator();
+        let operator_signer_kp = KeyPair::new_operator();
+        let account_kp = KeyPair::new_account();
+        let account_signer_kp = KeyPair::new_account();
+        let module_kp = KeyPair::new_module();
+
+        let operator = Claims::<Operator>::new(
+            "Operator".to_string(),
+            operator_kp.public_key(),
+            operator_kp.public_key(),
+            vec![operator_signer_kp.public_key()],
+        )
+        .encode(&operator_kp)
+        .unwrap();
+        // Account issued by the operator's additional signing key
+        let account = Claims::<Account>::new(
+            "Account".to_string(),
+            operator_signer_kp.public_key(),
+            account_kp.public_key(),
+            vec![account_signer_kp.public_key()],
+        )
+        .encode(&operator_signer_kp)
+        .unwrap();
+        // Actor issued by the account's additional signing key
+        let actor = Claims::<Actor>::new(
+            "Actor".to_string(),
+            account_signer_kp.public_key(),
+            module_kp.public_key(),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .encode(&account_signer_kp)
+        .unwrap();
+        assert!(verify_chain(&operator, &account, &actor).is_ok());
+
+        let rogue_kp = KeyPair::new_account();
+        let rogue_actor = Claims::<Actor>::new(
+            "Actor".to_string(),
+            rogue_kp.public_key(),
+            module_kp.public_key(),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .encode(&rogue_kp)
+        .unwrap();
+        let failure = verify_chain(&operator, &account, &rogue_actor).unwrap_err();
+        assert_eq!(failure.token, "actor");
+        assert_eq!(failure.reason, "untrusted_issuer");
+
+        let rogue_operator_kp = KeyPair::new_operator();
+        let rogue_account = Claims::<Account>::new(
+            "Account".to_string(),
+            rogue_operator_kp.public_key(),
+            account_kp.public_key(),
+            vec![],
+        )
+        .encode(&rogue_operator_kp)
+        .unwrap();
+        let failure = verify_chain(&operator, &rogue_account, &actor).unwrap_err();
+        assert_eq!(failure.token, "account");
+        assert_eq!(failure.reason, "untrusted_issuer");
+
+        let expired_operator = Claims::<Operator>::with_dates(
+            "Operator".to_string(),
+            operator_kp.public_key(),
+            operator_kp.public_key(),
+            None,
+            Some(1),
+            vec![],
+        )
+        .encode(&operator_kp)
+        .unwrap();
+        let failure = verify_chain(&expired_operator, &account, &actor).unwrap_err();
+        assert_eq!(failure.token, "operator");
+        assert_eq!(failure.reason, "expired");
+
+        // The operator token passed as the account, with a module signed by the operator key
+        let operator_actor = Claims::<Actor>::new(
+            "Actor".to_string(),
+            operator_kp.public_key(),
+            module_kp.public_key(),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .encode(&operator_kp)
+        .unwrap();
+        let failure = verify_chain(&operator, &operator, &operator_actor).unwrap_err();
+        assert_eq!(failure.token, "account");
+        assert_eq!(failure.reason, "invalid_account");
+
+        // A module token must belong to a module key
+        let service_kp = KeyPair::new_service();
+        let service_actor = Claims::<Actor>::new(
+            "Actor".to_string(),
+            account_signer_kp.public_key(),
+            service_kp.public_key(),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        )
+        .encode(&account_signer_kp)
+        .unwrap();
+        let failure = verify_chain(&operator, &account, &service_actor).unwrap_err();
+        assert_eq!(failure.token, "actor");
+        assert_eq!(failure.reason, "invalid_module");
+    }
+
+    #[test]
+    fn test_claims_inspect_decode_jwt() {
+        let operator_kp = KeyPair::new_operator();
+        let account_kp = KeyPair::new_account();
+        let service_kp = KeyPair::new_service();
+        let output = Output {
+            kind: OutputKind::Json,
+        };
+
+        let operator = Claims::<Operator>::new(
+            "Operator".to_string(),
+            operator_kp.public_key(),
+            operator_kp.public_key(),
+            vec![],
+        )
+        .encode(&operator_kp)
+        .unwrap();
+        assert!(is_jwt(&operator));
+        match decode_jwt(&operator).unwrap() {
+            claims @ InspectedClaims::Operator(_) => {
+                let rendered = claims.render(&output).unwrap();
+                assert!(
+                    rendered.contains(&format!("\"operator\":\"{}\"", operator_kp.public_key()))
+                );
+                assert!(rendered.contains("\"additional_signing_keys\":[]"));
+            }
+            _ => panic!("operator token was decoded as another entity"),
+        }
+
+        let account = Claims::<Account>::new(
+            "Account".to_string(),
+            operator_kp.public_key(),
+            account_kp.public_key(),
+            vec![],
+        )
+        .encode(&operator_kp)
+        .unwrap();
+        match decode_jwt(&account).unwrap() {
+            claims @ InspectedClaims::Account(_) => {
+                let rendered = claims.render(&output).unwrap();
+                assert!(rendered.contains(&format!("\"account\":\"{}\"", account_kp.public_key())));
+            }
+            _ => panic!("account token was decoded as another entity"),
+        }
+
+        let provider = Claims::<CapabilityProvider>::new(
+            "Provider".to_string(),
+            account_kp.public_key(),
+            service_kp.public_key(),
+            "wasmcloud:testing".to_string(),
+            "TestRunner".to_string(),
+            Some(1),
+            None,
+            HashMap::new(),
+        )
+        .encode(&account_kp)
+        .unwrap();
+        match decode_jwt(&provider).unwrap() {
+            claims @ InspectedClaims::Provider(_) => {
+                let rendered = claims.render(&output).unwrap();
+                assert!(rendered.contains(&format!("\"service\":\"{}\"", service_kp.public_key())));
+                assert!(rendered.contains("\"capability_contract_id\":\"wasmcloud:testing\""));
+            }
+            _ => panic!("provider token was decoded as another entity"),
+        }
+
+        let mut no_metadata = Claims::<CapabilityProvider>::new(
+            "Provider".to_string(),
+            account_kp.public_key(),
+            service_kp.public_key(),
+            "wasmcloud:testing".to_string(),
+            "TestRunner".to_string(),
+            None,
+            None,
+            HashMap::new(),
+        );
+        no_metadata.metadata = None;
+        let validation = TokenValidation {
+            expired: false,
+            cannot_use_yet: false,
+            expires_human: "never".to_string(),
+            not_before_human: "immediately".to_string(),
+            signature_valid: true,
+        };
+        assert_eq!(
+            render_provider_claims(no_metadata, validation, &output)
+                .unwrap_err()
+                .to_string(),
+            "No metadata found in provider claims"
+        );
+
+        assert!(!is_jwt(SUBSCRIBER_OCI));
+        assert!(!is_jwt("./actor.wasm"));
+    }
+
+    #[test]
+    /// Enumerates all options and flags of the `claims update` command
+    /// to ensure command line arguments do not change between versions
+    fn test_claims_update_comprehensive() {
+        const LOCAL_WASM: &str = "./myactor_s.wasm";
+        const ISSUER_KEY: &str = "SAAOBYD6BLELXSNN4S3TXUM7STGPB3A5HYU3D5T7XA4WHGVQBDBD4LJPOM";
+        let long_cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "update",
+            LOCAL_WASM,
+            "--destination",
+            "./myactor_updated.wasm",
+            "--name",
+            "MyActor",
+            "--cap",
+            "test:custom",
+            "--remove-cap",
+            "test:removed",
+            "--tag",
+            "testtag",
+            "--remove-tag",
+            "oldtag",
+            "--bump-rev",
+            "--ver",
+            "0.0.2",
+            "--call-alias",
+            "myactor",
+            "--expires",
+            "3",
+            "--nbf",
+            "1",
+            "--issuer",
+            ISSUER_KEY,
+            "--rotate-issuer",
+            "--directory",
+            "./dir",
+            "--disable-keygen",
+            "--signer",
+            "./signer.sh",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+
+        match long_cmd.command {
+            ClaimsCliCommand::Update(UpdateCommand {
+                source,
+                destination,
+                name,
+                add_caps,
+                remove_caps,
+                add_tags,
+                remove_tags,
+                rev,
+                bump_rev,
+                ver,
+                call_alias,
+                expires_in_days,
+                not_before_days,
+                issuer,
+                rotate_issuer,
+                directory,
+                disable_keygen,
+                signer,
+                output,
+            }) => {
+                assert_eq!(source, LOCAL_WASM);
+                assert_eq!(signer.unwrap(), "./signer.sh");
+                assert_eq!(destination.unwrap(), "./myactor_updated.wasm");
+                assert_eq!(name.unwrap(), "MyActor");
+                assert_eq!(add_caps, vec!["test:custom"]);
+                assert_eq!(remove_caps, vec!["test:removed"]);
+                assert_eq!(add_tags, vec!["testtag"]);
+                assert_eq!(remove_tags, vec!["oldtag"]);
+                assert!(rev.is_none());
+                assert!(bump_rev);
+                assert_eq!(ver.unwrap(), "0.0.2");
+                assert_eq!(call_alias.unwrap(), "myactor");
+                assert_eq!(expires_in_days.unwrap(), 3);
+                assert_eq!(not_before_days.unwrap(), 1);
+                assert_eq!(issuer.unwrap(), ISSUER_KEY);
+                assert!(rotate_issuer);
+                assert_eq!(directory.unwrap(), "./dir");
+                assert!(disable_keygen);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+
+        let short_cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "update",
+            LOCAL_WASM,
+            "-d",
+            "./myactor_updated.wasm",
+            "-n",
+            "MyActor",
+            "-c",
+            "test:custom",
+            "-t",
+            "testtag",
+            "-r",
+            "4",
+            "-v",
+            "0.0.2",
+            "-a",
+            "myactor",
+            "-x",
+            "3",
+            "-b",
+            "1",
+            "-i",
+            ISSUER_KEY,
+            "-o",
+            "json",
+        ])
+        .unwrap();
+
+        match short_cmd.command {
+            ClaimsCliCommand::Update(UpdateCommand {
+                destination,
+                name,
+                add_caps,
+                add_tags,
+                rev,
+                bump_rev,
+                ver,
+                call_alias,
+                expires_in_days,
+                not_before_days,
+                issuer,
+                rotate_issuer,
+                output,
+                ..
+            }) => {
+                assert_eq!(destination.unwrap(), "./myactor_updated.wasm");
+                assert_eq!(name.unwrap(), "MyActor");
+                assert_eq!(add_caps, vec!["test:custom"]);
+                assert_eq!(add_tags, vec!["testtag"]);
+                assert_eq!(rev.unwrap(), 4);
+                assert!(!bump_rev);
+                assert_eq!(ver.unwrap(), "0.0.2");
+                assert_eq!(call_alias.unwrap(), "myactor");
+                assert_eq!(expires_in_days.unwrap(), 3);
+                assert_eq!(not_before_days.unwrap(), 1);
+                assert_eq!(issuer.unwrap(), ISSUER_KEY);
+                assert!(!rotate_issuer);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+
+        assert!(ClaimsCli::from_iter_safe(&[
+            "claims",
+            "update",
+            LOCAL_WASM,
+            "--rev",
+            "2",
+            "--bump-rev"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_claims_sign_claims_file() {
+        // A name can also come from a claims file, or from the file names of a batch
+        assert!(ClaimsCli::from_iter_safe(&["claims", "sign", "./myactor.wasm"]).is_ok());
+        let token =
+            ClaimsCli::from_iter_safe(&["claims", "token", "actor", "--disable-keygen"]).unwrap();
+        match token.command {
+            ClaimsCliCommand::Token(TokenCommand::Actor(actor)) => {
+                assert!(generate_actor(actor).is_err())
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+
+        let cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "sign",
+            "./myactor.wasm",
+            "--claims-file",
+            "./actor.toml",
+            "--rev",
+            "5",
+            "--tag",
+            "cli",
+            "-k",
+        ])
+        .unwrap();
+        let metadata = match cmd.command {
+            ClaimsCliCommand::Sign(SignCommand { metadata, .. }) => metadata,
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        };
+
+        let file: ClaimsFile = serdeconv::from_toml_str(
+            r#"
+            name = "FileActor"
+            caps = ["wasmcloud:httpserver", "acme:custom"]
+            tags = ["file", "cli"]
+            rev = 2
+            ver = "0.1.0"
+            call_alias = "fileactor"
+            expires_in_days = 30
+            "#,
+        )
+        .unwrap();
+        let merged = metadata.merge_claims_file(file);
+        assert_eq!(merged.name.unwrap(), "FileActor");
+        assert!(merged.keyvalue);
+        assert_eq!(
+            merged.custom_caps,
+            vec!["wasmcloud:httpserver", "acme:custom"]
+        );
+        assert_eq!(merged.tags, vec!["file", "cli"]);
+        assert_eq!(merged.rev.unwrap(), 5);
+        assert_eq!(merged.ver.unwrap(), "0.1.0");
+        assert_eq!(merged.call_alias.unwrap(), "fileactor");
+        assert_eq!(merged.common.expires_in_days.unwrap(), 30);
+        assert!(merged.common.not_before_days.is_none());
+
+        assert!(serdeconv::from_toml_str::<ClaimsFile>("unknown = true").is_err());
+    }
+
+    #[test]
+    fn test_claims_strip_extract_comprehensive() {
+        const LOCAL_WASM: &str = "./myactor_s.wasm";
+        let strip_cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "strip",
+            LOCAL_WASM,
+            "--destination",
+            "./myactor.wasm",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match strip_cmd.command {
+            ClaimsCliCommand::Strip(StripCommand {
+                module,
+                destination,
+                output,
+            }) => {
+                assert_eq!(module, LOCAL_WASM);
+                assert_eq!(destination.unwrap(), "./myactor.wasm");
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+
+        let extract_cmd = ClaimsCli::from_iter_safe(&[
+            "claims",
+            "extract",
+            LOCAL_WASM,
+            "-d",
+            "./token.jwt",
+            "-o",
+            "json",
+        ])
+        .unwrap();
+        match extract_cmd.command {
+            ClaimsCliCommand::Extract(ExtractCommand {
+                module,
+                destination,
+                output,
+            }) => {
+                assert_eq!(module, LOCAL_WASM);
+                assert_eq!(destination.unwrap(), "./token.jwt");
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+
+        assert_eq!(
+            sibling_destination("./build/myactor_s.wasm", "_unsigned.wasm"),
+            "./build/myactor_unsigned.wasm"
+        );
+        assert_eq!(sibling_destination("myactor.wasm", ".jwt"), "myactor.jwt");
+    }
+
+    #[test]
+    fn test_claims_caps_list() {
+        let cmd = ClaimsCli::from_iter_safe(&["claims", "caps", "list", "-o", "json"]).unwrap();
+        match cmd.command {
+            ClaimsCliCommand::Caps(CapsCliCommand::List(CapsListCommand { output })) => {
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("claims constructed incorrect command: {:?}", cmd),
+        }
+    }
 }
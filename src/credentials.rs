@@ -0,0 +1,430 @@
+use log::{debug, warn};
+use oci_distribution::secrets::RegistryAuth;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Path to the wash registry credentials file, defaults to $HOME/.wash/credentials.json
+pub(crate) const WASH_REG_CREDENTIALS: &str = "WASH_REG_CREDENTIALS";
+/// Directory containing the docker config.json, defaults to $HOME/.docker
+const DOCKER_CONFIG: &str = "DOCKER_CONFIG";
+/// Docker stores credentials for Docker Hub under its legacy index URL
+const DOCKER_HUB: &str = "docker.io";
+const DOCKER_HUB_SERVER: &str = "https://index.docker.io/v1/";
+
+/// Registry credentials in the format of a docker config.json. Other fields of the docker
+/// config are kept as they are, so saving the file leaves them intact
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct CredentialsFile {
+    /// Credentials stored directly in the file, keyed by registry
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) auths: BTreeMap<String, AuthEntry>,
+    /// Credential helpers to use for specific registries, e.g. `ecr-login` for `docker-credential-ecr-login`
+    #[serde(
+        default,
+        rename = "credHelpers",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub(crate) cred_helpers: BTreeMap<String, String>,
+    /// Credential helper to use for every registry without an entry in `credHelpers`
+    #[serde(
+        default,
+        rename = "credsStore",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) creds_store: Option<String>,
+    /// Any other fields, such as `HttpHeaders` or `proxies`
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct AuthEntry {
+    /// Base64 encoded `username:password`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) auth: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) password: Option<String>,
+}
+
+/// Response of a docker credential helper's `get` command
+#[derive(Debug, Deserialize)]
+struct HelperCredentials {
+    #[serde(rename = "Username")]
+    username: String,
+    #[serde(rename = "Secret")]
+    secret: String,
+}
+
+impl CredentialsFile {
+    /// Loads a credentials file, returning `None` if it does not exist
+    pub(crate) fn load(path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        let file = serde_json::from_str(&contents).map_err(|e| {
+            format!(
+                "Unable to parse registry credentials {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        Ok(Some(file))
+    }
+
+    /// Writes the credentials file, readable only by the current user
+    pub(crate) fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        crate::keys::write_key_file(
+            &path.to_string_lossy(),
+            &serde_json::to_string_pretty(self)?,
+        )
+    }
+
+    /// Stores a username and password for a registry, replacing any existing entry
+    pub(crate) fn store(&mut self, registry: &str, username: &str, password: &str) {
+        self.remove(registry);
+        self.auths.insert(
+            normalize_registry(registry),
+            AuthEntry {
+                auth: Some(base64::encode(format!("{}:{}", username, password))),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Removes the stored credentials for a registry, returning false if there were none
+    pub(crate) fn remove(&mut self, registry: &str) -> bool {
+        let registry = normalize_registry(registry);
+        let before = self.auths.len();
+        self.auths
+            .retain(|key, _| normalize_registry(key) != registry);
+        self.auths.len() != before
+    }
+
+    /// Finds the username and password for a registry, using a credential helper if one is
+    /// configured for the registry or for every registry
+    pub(crate) fn lookup(
+        &self,
+        registry: &str,
+    ) -> Result<Option<(String, String)>, Box<dyn Error>> {
+        let registry = normalize_registry(registry);
+        let helper = self
+            .cred_helpers
+            .iter()
+            .find(|(key, _)| normalize_registry(key) == registry)
+            .map(|(_, helper)| helper)
+            .or(self.creds_store.as_ref());
+        if let Some(helper) = helper {
+            if let Some(credentials) =
+                run_helper(&format!("docker-credential-{}", helper), &registry)?
+            {
+                return Ok(Some(credentials));
+            }
+        }
+
+        match self
+            .auths
+            .iter()
+            .find(|(key, _)| normalize_registry(key) == registry)
+        {
+            Some((key, entry)) => entry
+                .credentials()
+                .map_err(|e| format!("Invalid credentials for {}: {}", key, e).into()),
+            None => Ok(None),
+        }
+    }
+}
+
+impl AuthEntry {
+    fn credentials(&self) -> Result<Option<(String, String)>, Box<dyn Error>> {
+        if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            return Ok(Some((username.to_string(), password.to_string())));
+        }
+        match &self.auth {
+            Some(auth) => {
+                let decoded = String::from_utf8(base64::decode(auth.trim())?)?;
+                let mut parts = decoded.splitn(2, ':');
+                match (parts.next(), parts.next()) {
+                    (Some(username), Some(password)) => {
+                        Ok(Some((username.to_string(), password.to_string())))
+                    }
+                    _ => Err("auth must be a base64 encoded username:password".into()),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Reduces a registry URL or docker config key to its host, treating every Docker Hub host alike
+pub(crate) fn normalize_registry(registry: &str) -> String {
+    let host = registry
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    match host {
+        "index.docker.io" | "registry-1.docker.io" => DOCKER_HUB.to_string(),
+        host => host.to_string(),
+    }
+}
+
+/// Asks a docker credential helper for the credentials of a registry, returning `None` if the
+/// helper has no credentials stored for it
+fn run_helper(program: &str, registry: &str) -> Result<Option<(String, String)>, Box<dyn Error>> {
+    let server = if registry == DOCKER_HUB {
+        DOCKER_HUB_SERVER
+    } else {
+        registry
+    };
+    let mut child = Command::new(program)
+        .arg("get")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Unable to run credential helper {}: {}", program, e))?;
+    child
+        .stdin
+        .take()
+        .ok_or("Unable to write to credential helper")?
+        .write_all(server.as_bytes())?;
+    let output = child.wait_with_output()?;
+
+    if output.status.success() {
+        let credentials: HelperCredentials =
+            serde_json::from_slice(&output.stdout).map_err(|e| {
+                format!(
+                    "Credential helper {} returned invalid credentials: {}",
+                    program, e
+                )
+            })?;
+        Ok(Some((credentials.username, credentials.secret)))
+    } else {
+        let message = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        if message.contains("credentials not found") {
+            Ok(None)
+        } else {
+            Err(format!(
+                "Credential helper {} failed for {}: {}",
+                program,
+                registry,
+                message.trim()
+            )
+            .into())
+        }
+    }
+}
+
+/// Path to the wash registry credentials file, $WASH_REG_CREDENTIALS ($HOME/.wash/credentials.json)
+pub(crate) fn wash_credentials_path() -> Option<PathBuf> {
+    match env::var(WASH_REG_CREDENTIALS) {
+        Ok(path) => Some(PathBuf::from(path)),
+        Err(_) => env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".wash").join("credentials.json")),
+    }
+}
+
+/// Path to the docker config file, $DOCKER_CONFIG/config.json ($HOME/.docker/config.json)
+fn docker_config_path() -> Option<PathBuf> {
+    match env::var(DOCKER_CONFIG) {
+        Ok(dir) => Some(PathBuf::from(dir).join("config.json")),
+        Err(_) => env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".docker").join("config.json")),
+    }
+}
+
+/// Resolves the authentication for a registry. Explicit credentials are used if provided,
+/// otherwise the wash credentials file is consulted before the docker config, falling back to
+/// anonymous authentication if neither has credentials for the registry. Only a credentials file
+/// configured through WASH_REG_CREDENTIALS fails the resolution when it cannot be used, problems
+/// with the default files are logged and the next source is tried instead
+pub(crate) fn resolve_auth(
+    registry: &str,
+    user: Option<String>,
+    password: Option<String>,
+) -> Result<RegistryAuth, Box<dyn Error>> {
+    if let (Some(user), Some(password)) = (user, password) {
+        return Ok(RegistryAuth::Basic(user, password));
+    }
+
+    let explicit = env::var(WASH_REG_CREDENTIALS).is_ok();
+    let sources = vec![
+        (wash_credentials_path(), explicit),
+        (docker_config_path(), false),
+    ];
+    for (path, explicit) in sources
+        .into_iter()
+        .filter_map(|(path, explicit)| path.map(|path| (path, explicit)))
+    {
+        match CredentialsFile::load(&path).and_then(|file| match file {
+            Some(file) => file.lookup(registry),
+            None => Ok(None),
+        }) {
+            Ok(Some((user, password))) => {
+                debug!("Using credentials for {} from {}", registry, path.display());
+                return Ok(RegistryAuth::Basic(user, password));
+            }
+            Ok(None) => (),
+            Err(e) if explicit => return Err(e),
+            Err(e) => warn!(
+                "Ignoring credentials for {} from {}: {}",
+                registry,
+                path.display(),
+                e
+            ),
+        }
+    }
+    debug!(
+        "No credentials found for {}, using anonymous authentication",
+        registry
+    );
+    Ok(RegistryAuth::Anonymous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_registry() {
+        assert_eq!(normalize_registry("localhost:5000"), "localhost:5000");
+        assert_eq!(
+            normalize_registry("https://wasmcloud.azurecr.io/v2/"),
+            "wasmcloud.azurecr.io"
+        );
+        assert_eq!(
+            normalize_registry("https://index.docker.io/v1/"),
+            "docker.io"
+        );
+        assert_eq!(normalize_registry("registry-1.docker.io"), "docker.io");
+    }
+
+    #[test]
+    fn test_lookup_auths() {
+        let file: CredentialsFile = serde_json::from_str(
+            r#"{
+                "auths": {
+                    "https://index.docker.io/v1/": {"auth": "dXNlcjpzZWNyZXQ6d2l0aDpjb2xvbnM="},
+                    "localhost:5000": {"username": "local", "password": "pass"},
+                    "ghcr.io": {}
+                },
+                "HttpHeaders": {"User-Agent": "Docker-Client"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            file.lookup("docker.io").unwrap().unwrap(),
+            ("user".to_string(), "secret:with:colons".to_string())
+        );
+        assert_eq!(
+            file.lookup("localhost:5000").unwrap().unwrap(),
+            ("local".to_string(), "pass".to_string())
+        );
+        assert!(file.lookup("ghcr.io").unwrap().is_none());
+        assert!(file.lookup("wasmcloud.azurecr.io").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unknown_fields_preserved() {
+        let mut file: CredentialsFile = serde_json::from_str(
+            r#"{
+                "auths": {},
+                "HttpHeaders": {"User-Agent": "Docker-Client"},
+                "experimental": "enabled"
+            }"#,
+        )
+        .unwrap();
+        file.store("localhost:5000", "user", "secret");
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&file).unwrap()).unwrap();
+        assert_eq!(saved["HttpHeaders"]["User-Agent"], "Docker-Client");
+        assert_eq!(saved["experimental"], "enabled");
+        assert_eq!(saved["auths"]["localhost:5000"]["auth"], "dXNlcjpzZWNyZXQ=");
+    }
+
+    #[test]
+    fn test_store_remove() {
+        let mut file = CredentialsFile::default();
+        file.auths.insert(
+            "https://localhost:5000/v2/".to_string(),
+            AuthEntry {
+                username: Some("old".to_string()),
+                password: Some("old".to_string()),
+                ..Default::default()
+            },
+        );
+        file.store("localhost:5000", "user", "secret");
+        assert_eq!(file.auths.len(), 1);
+        assert_eq!(
+            file.lookup("localhost:5000").unwrap().unwrap(),
+            ("user".to_string(), "secret".to_string())
+        );
+        assert_eq!(
+            serde_json::to_string(&file).unwrap(),
+            "{\"auths\":{\"localhost:5000\":{\"auth\":\"dXNlcjpzZWNyZXQ=\"}}}"
+        );
+
+        assert!(file.remove("http://localhost:5000"));
+        assert!(!file.remove("localhost:5000"));
+        assert!(file.lookup("localhost:5000").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_missing_helper() {
+        let file = CredentialsFile {
+            cred_helpers: vec![(
+                "wasmcloud.azurecr.io".to_string(),
+                "wash-test-missing".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let err = file.lookup("wasmcloud.azurecr.io").unwrap_err();
+        assert!(format!("{}", err)
+            .contains("Unable to run credential helper docker-credential-wash-test-missing"));
+        // Registries without a helper don't run one
+        assert!(file.lookup("localhost:5000").unwrap().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_helper() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = env::temp_dir().join("wash_credentials_helper");
+        std::fs::create_dir_all(&dir).unwrap();
+        let helper = dir.join("docker-credential-test");
+        std::fs::write(
+            &helper,
+            "#!/bin/sh\nread server\nif [ \"$server\" = \"https://index.docker.io/v1/\" ]; then\n  echo '{\"ServerURL\":\"'$server'\",\"Username\":\"hub\",\"Secret\":\"token\"}'\nelse\n  echo 'credentials not found in native keychain'\n  exit 1\nfi\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&helper, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let helper = helper.to_str().unwrap();
+        assert_eq!(
+            run_helper(helper, "docker.io").unwrap().unwrap(),
+            ("hub".to_string(), "token".to_string())
+        );
+        assert!(run_helper(helper, "localhost:5000").unwrap().is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+use crate::util::{configure_table_style, format_output, OutputKind};
+use serde_json::json;
+use std::collections::BTreeSet;
+use term_table::{
+    row::Row,
+    table_cell::{Alignment, TableCell},
+    Table,
+};
+
+/// A single difference between two artifacts
+#[derive(Debug, Clone, PartialEq)]
+enum Change {
+    /// A value that differs, or is only present on one side
+    Field {
+        field: String,
+        left: Option<String>,
+        right: Option<String>,
+    },
+    /// A list whose entries were removed from the left or added on the right
+    List {
+        field: String,
+        removed: Vec<String>,
+        added: Vec<String>,
+    },
+}
+
+/// Differences between the claims and contents of two artifacts, in the order they were compared
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Diff {
+    changes: Vec<Change>,
+}
+
+impl Diff {
+    pub(crate) fn new() -> Self {
+        Diff::default()
+    }
+
+    /// Records a field if its value differs between the two artifacts
+    pub(crate) fn field<T: ToString>(&mut self, field: &str, left: Option<T>, right: Option<T>) {
+        let left = left.map(|v| v.to_string());
+        let right = right.map(|v| v.to_string());
+        if left != right {
+            self.changes.push(Change::Field {
+                field: field.to_string(),
+                left,
+                right,
+            });
+        }
+    }
+
+    /// Records the entries of a list that are only present in one of the two artifacts
+    pub(crate) fn list(&mut self, field: &str, left: &[String], right: &[String]) {
+        let left: BTreeSet<&String> = left.iter().collect();
+        let right: BTreeSet<&String> = right.iter().collect();
+        let removed: Vec<String> = left.difference(&right).map(|s| s.to_string()).collect();
+        let added: Vec<String> = right.difference(&left).map(|s| s.to_string()).collect();
+        if !removed.is_empty() || !added.is_empty() {
+            self.changes.push(Change::List {
+                field: field.to_string(),
+                removed,
+                added,
+            });
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Renders the differences as a table of fields, with the left artifact in the first column
+    /// and the right artifact in the second, or as JSON
+    pub(crate) fn render(&self, left: &str, right: &str, output: &OutputKind) -> String {
+        let changes: Vec<serde_json::Value> = self
+            .changes
+            .iter()
+            .map(|change| match change {
+                Change::Field { field, left, right } => {
+                    json!({"field": field, "left": left, "right": right})
+                }
+                Change::List {
+                    field,
+                    removed,
+                    added,
+                } => json!({"field": field, "removed": removed, "added": added}),
+            })
+            .collect();
+        let json =
+            json!({"left": left, "right": right, "identical": self.is_empty(), "changes": changes});
+
+        match output {
+            OutputKind::Text { .. } if self.is_empty() => format_output(
+                format!("No differences between {} and {}", left, right),
+                json,
+                output,
+            ),
+            OutputKind::Text { max_width } => {
+                format_output(self.render_table(left, right, *max_width), json, output)
+            }
+            OutputKind::Json => format_output(String::new(), json, output),
+        }
+    }
+
+    fn render_table(&self, left: &str, right: &str, max_width: usize) -> String {
+        let mut table = Table::new();
+        configure_table_style(&mut table, 3, max_width);
+        table.add_row(Row::new(vec![
+            TableCell::new("Field"),
+            TableCell::new(left),
+            TableCell::new(right),
+        ]));
+
+        for change in self.changes.iter() {
+            let (field, left, right) = match change {
+                Change::Field { field, left, right } => (
+                    field.to_string(),
+                    left.clone().unwrap_or_else(|| "(Not set)".to_string()),
+                    right.clone().unwrap_or_else(|| "(Not set)".to_string()),
+                ),
+                Change::List {
+                    field,
+                    removed,
+                    added,
+                } => (
+                    field.to_string(),
+                    removed
+                        .iter()
+                        .map(|entry| format!("- {}", entry))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    added
+                        .iter()
+                        .map(|entry| format!("+ {}", entry))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+            };
+            table.add_row(Row::new(vec![
+                TableCell::new(field),
+                TableCell::new_with_alignment(left, 1, Alignment::Left),
+                TableCell::new_with_alignment(right, 1, Alignment::Left),
+            ]));
+        }
+
+        table.render()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_records_changes_only() {
+        let mut diff = Diff::new();
+        diff.field("name", Some("Echo"), Some("Echo"));
+        diff.field("revision", Some(1), Some(2));
+        diff.field("call_alias", None, Some("echo"));
+        diff.list(
+            "tags",
+            &["a".to_string(), "b".to_string()],
+            &["b".to_string(), "a".to_string()],
+        );
+        diff.list(
+            "capabilities",
+            &["wasmcloud:httpserver".to_string()],
+            &["wasmcloud:keyvalue".to_string()],
+        );
+
+        assert_eq!(
+            diff.changes,
+            vec![
+                Change::Field {
+                    field: "revision".to_string(),
+                    left: Some("1".to_string()),
+                    right: Some("2".to_string()),
+                },
+                Change::Field {
+                    field: "call_alias".to_string(),
+                    left: None,
+                    right: Some("echo".to_string()),
+                },
+                Change::List {
+                    field: "capabilities".to_string(),
+                    removed: vec!["wasmcloud:httpserver".to_string()],
+                    added: vec!["wasmcloud:keyvalue".to_string()],
+                },
+            ]
+        );
+        assert_eq!(
+            diff.render("a", "b", &OutputKind::Json),
+            "{\"changes\":[\
+             {\"field\":\"revision\",\"left\":\"1\",\"right\":\"2\"},\
+             {\"field\":\"call_alias\",\"left\":null,\"right\":\"echo\"},\
+             {\"added\":[\"wasmcloud:keyvalue\"],\"field\":\"capabilities\",\"removed\":[\"wasmcloud:httpserver\"]}],\
+             \"identical\":false,\"left\":\"a\",\"right\":\"b\"}"
+        );
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let mut diff = Diff::new();
+        diff.field("name", Some("Echo"), Some("Echo"));
+        diff.list("tags", &[], &[]);
+        assert!(diff.is_empty());
+        assert_eq!(
+            diff.render("a", "b", &OutputKind::Text { max_width: 0 }),
+            "No differences between a and b"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ocicache::cache_dir;
 use crate::util::format_output;
 use crate::util::{Output, OutputKind};
 use serde_json::json;
@@ -51,11 +52,8 @@ impl IntoIterator for &DrainSelection {
 
     fn into_iter(self) -> Self::IntoIter {
         let paths = match self {
-            DrainSelection::All(_) => vec![
-                env::temp_dir().join("wasmcloudcache"),
-                env::temp_dir().join("wasmcloud_ocicache"),
-            ],
-            DrainSelection::Oci(_) => vec![env::temp_dir().join("wasmcloud_ocicache")],
+            DrainSelection::All(_) => vec![env::temp_dir().join("wasmcloudcache"), cache_dir()],
+            DrainSelection::Oci(_) => vec![cache_dir()],
             DrainSelection::Lib(_) => vec![env::temp_dir().join("wasmcloudcache")],
         };
         paths.into_iter()
@@ -1,13 +1,51 @@
+use crate::par::{is_compressed, parse_archive, write_archive};
+use crate::signer::{embed_claims, encode_claims, extract_signer, Signer};
 use crate::util::{format_output, print_or_log, Output, OutputKind};
 use nkeys::{KeyPair, KeyPairType};
+use provider_archive::ProviderArchive;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::env;
 use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::Error;
+use std::num::NonZeroU32;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use structopt::StructOpt;
+use term_table::{
+    row::Row,
+    table_cell::{Alignment, TableCell},
+    Table,
+};
+use termion::input::TermRead;
+use wascap::jwt::{Account, CapabilityProvider, Claims, Operator, WascapEntity};
+
+/// Environment variable holding the passphrase used to unlock and encrypt keys
+pub(crate) const WASH_KEYS_PASSPHRASE: &str = "WASH_KEYS_PASSPHRASE";
+const KEYSTORE_VERSION: u8 = 1;
+const KDF_PBKDF2_SHA256: &str = "pbkdf2-sha256";
+const KDF_ITERATIONS: u32 = 100_000;
+const CIPHER_CHACHA20_POLY1305: &str = "chacha20-poly1305";
+const SALT_LEN: usize = 16;
+
+/// Passphrase-encrypted seed, stored as JSON in place of the plaintext seed of a `.nk` file
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSeed {
+    version: u8,
+    public_key: String,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
 
 #[derive(Debug, Clone, StructOpt)]
 pub(crate) struct KeysCli {
@@ -29,6 +67,26 @@ pub(crate) enum KeysCliCommand {
         /// The type of keypair to generate. May be Account, User, Module (Actor), Service (Capability Provider), Server, Operator, Cluster
         #[structopt(case_insensitive = true)]
         keytype: KeyPairType,
+        #[structopt(
+            short = "n",
+            long = "name",
+            help = "Name of the key. If provided, the key is written to <name>_<type>.nk in the key directory instead of being printed"
+        )]
+        name: Option<String>,
+        #[structopt(
+            short = "d",
+            long = "directory",
+            env = "WASH_KEYS",
+            hide_env_values = true,
+            help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`. Only used with --name"
+        )]
+        directory: Option<String>,
+        #[structopt(
+            long = "force",
+            requires = "name",
+            help = "Overwrite an existing key with the same name"
+        )]
+        force: bool,
         #[structopt(flatten)]
         output: Output,
     },
@@ -57,6 +115,157 @@ pub(crate) enum KeysCliCommand {
             help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`"
         )]
         directory: Option<String>,
+        #[structopt(
+            short = "t",
+            long = "type",
+            case_insensitive = true,
+            help = "Only list keys of this type. May be Account, User, Module (Actor), Service (Capability Provider), Server, Operator, Cluster"
+        )]
+        keytype: Option<KeyPairType>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    #[structopt(
+        name = "import",
+        about = "Imports a seed into the key directory, optionally encrypting it"
+    )]
+    ImportCommand {
+        #[structopt(help = "The seed to import, or a path to a file containing it")]
+        seed: String,
+        #[structopt(
+            short = "n",
+            long = "name",
+            help = "Name of the key, stored as <name>_<type>.nk"
+        )]
+        name: String,
+        #[structopt(
+            short = "d",
+            long = "directory",
+            env = "WASH_KEYS",
+            hide_env_values = true,
+            help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`"
+        )]
+        directory: Option<String>,
+        #[structopt(
+            long = "encrypt",
+            help = "Encrypt the seed with a passphrase, read from $WASH_KEYS_PASSPHRASE or prompted for"
+        )]
+        encrypt: bool,
+        #[structopt(long = "force", help = "Overwrite an existing key with the same name")]
+        force: bool,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    #[structopt(
+        name = "export",
+        about = "Exports the plaintext seed of a key, decrypting it if needed"
+    )]
+    ExportCommand {
+        #[structopt(help = "The name of the key to export")]
+        keyname: String,
+        #[structopt(
+            short = "d",
+            long = "directory",
+            env = "WASH_KEYS",
+            hide_env_values = true,
+            help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`"
+        )]
+        directory: Option<String>,
+        #[structopt(
+            long = "destination",
+            help = "File to write the seed to. If omitted, the seed is printed"
+        )]
+        destination: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    #[structopt(name = "encrypt", about = "Encrypts a key in place with a passphrase")]
+    EncryptCommand {
+        #[structopt(help = "The name of the key to encrypt")]
+        keyname: String,
+        #[structopt(
+            short = "d",
+            long = "directory",
+            env = "WASH_KEYS",
+            hide_env_values = true,
+            help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`"
+        )]
+        directory: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    #[structopt(name = "decrypt", about = "Decrypts a key in place")]
+    DecryptCommand {
+        #[structopt(help = "The name of the key to decrypt")]
+        keyname: String,
+        #[structopt(
+            short = "d",
+            long = "directory",
+            env = "WASH_KEYS",
+            hide_env_values = true,
+            help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`"
+        )]
+        directory: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    #[structopt(
+        name = "inspect",
+        about = "Validates a key and prints its type and public key"
+    )]
+    InspectCommand {
+        #[structopt(help = "The seed or public key to inspect, or a path to a file containing it")]
+        key: String,
+        #[structopt(
+            long = "payload",
+            help = "Payload to sign with the seed, or to verify against --signature. Can be a value or path to a file"
+        )]
+        payload: Option<String>,
+        #[structopt(
+            long = "signature",
+            requires = "payload",
+            help = "Base64 (URL safe) signature of the payload to verify"
+        )]
+        signature: Option<String>,
+        #[structopt(flatten)]
+        output: Output,
+    },
+    #[structopt(
+        name = "rotate",
+        about = "Replaces an account or operator key with a new key of the same type"
+    )]
+    RotateCommand {
+        #[structopt(help = "The name of the key to rotate")]
+        keyname: String,
+        #[structopt(
+            short = "d",
+            long = "directory",
+            env = "WASH_KEYS",
+            hide_env_values = true,
+            help = "Absolute path to where keypairs are stored. Defaults to `$HOME/.wash/keys`"
+        )]
+        directory: Option<String>,
+        #[structopt(
+            long = "token",
+            help = "Operator or account JWT issued to the rotated key. The new key is added to its additional signing keys"
+        )]
+        token: Option<String>,
+        #[structopt(
+            long = "token-issuer",
+            help = "Operator key that issued the token, if it was not issued by the rotated key. Can be a seed value or path to a seed file"
+        )]
+        token_issuer: Option<String>,
+        #[structopt(
+            long = "signer",
+            env = "WASH_SIGNER",
+            help = "Command used to sign the token when --token-issuer is a public key"
+        )]
+        signer: Option<String>,
+        #[structopt(
+            long = "resign",
+            help = "Directory of signed actors (_s.wasm) and provider archives (.par) to re-sign with the new key if they were issued by the rotated key"
+        )]
+        resign: Option<String>,
         #[structopt(flatten)]
         output: Output,
     },
@@ -66,13 +275,73 @@ pub(crate) fn handle_command(
     command: KeysCliCommand,
 ) -> Result<String, Box<dyn ::std::error::Error>> {
     match command {
-        KeysCliCommand::GenCommand { keytype, output } => Ok(generate(&keytype, &output.kind)),
+        KeysCliCommand::GenCommand {
+            keytype,
+            name: Some(name),
+            directory,
+            force,
+            output,
+        } => generate_named(&keytype, &name, directory, force, &output),
+        KeysCliCommand::GenCommand {
+            keytype, output, ..
+        } => Ok(generate(&keytype, &output.kind)),
         KeysCliCommand::GetCommand {
             keyname,
             directory,
             output,
         } => get(&keyname, directory, &output),
-        KeysCliCommand::ListCommand { directory, output } => list(directory, &output),
+        KeysCliCommand::ListCommand {
+            directory,
+            keytype,
+            output,
+        } => list(directory, keytype, &output),
+        KeysCliCommand::ImportCommand {
+            seed,
+            name,
+            directory,
+            encrypt,
+            force,
+            output,
+        } => import(&seed, &name, directory, encrypt, force, &output),
+        KeysCliCommand::ExportCommand {
+            keyname,
+            directory,
+            destination,
+            output,
+        } => export(&keyname, directory, destination, &output),
+        KeysCliCommand::EncryptCommand {
+            keyname,
+            directory,
+            output,
+        } => encrypt(&keyname, directory, &output),
+        KeysCliCommand::DecryptCommand {
+            keyname,
+            directory,
+            output,
+        } => decrypt(&keyname, directory, &output),
+        KeysCliCommand::InspectCommand {
+            key,
+            payload,
+            signature,
+            output,
+        } => inspect(&key, payload, signature, &output),
+        KeysCliCommand::RotateCommand {
+            keyname,
+            directory,
+            token,
+            token_issuer,
+            signer,
+            resign,
+            output,
+        } => rotate(
+            &keyname,
+            directory,
+            token,
+            token_issuer,
+            signer,
+            resign,
+            &output,
+        ),
     }
 }
 
@@ -93,6 +362,38 @@ pub(crate) fn generate(kt: &KeyPairType, output: &OutputKind) -> String {
     )
 }
 
+/// Generates a keypair and writes it to `<name>_<type>.nk` in a specified directory, or $WASH_KEYS ($HOME/.wash/keys) if directory is not specified
+pub(crate) fn generate_named(
+    kt: &KeyPairType,
+    name: &str,
+    directory: Option<String>,
+    force: bool,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let dir = determine_directory(directory)?;
+    let path = format!("{}/{}_{}.nk", dir, name, keypair_type_to_string(kt.clone()));
+    if Path::new(&path).exists() && !force {
+        return Err(format!("{} already exists, use --force to overwrite it", path).into());
+    }
+
+    let kp = KeyPair::new(kt.clone());
+    let seed = kp.seed()?;
+    // Keys are encrypted the same way as keys generated on demand by `extract_keypair`
+    match env::var(WASH_KEYS_PASSPHRASE) {
+        Ok(passphrase) => write_key_file(&path, &encrypt_seed(&seed, &passphrase)?)?,
+        Err(_) => write_key_file(&path, &seed)?,
+    }
+
+    Ok(format_output(
+        format!("Public Key: {}\nWritten to {}", kp.public_key(), path),
+        json!({
+            "public_key": kp.public_key(),
+            "path": path,
+        }),
+        &output.kind,
+    ))
+}
+
 /// Retrieves a keypair by name in a specified directory, or $WASH_KEYS ($HOME/.wash/keys) if directory is not specified
 pub(crate) fn get(
     keyname: &str,
@@ -100,27 +401,472 @@ pub(crate) fn get(
     output: &Output,
 ) -> Result<String, Box<dyn ::std::error::Error>> {
     let dir = determine_directory(directory)?;
-    let mut f = File::open(format!("{}/{}", dir, keyname))
-        .map_err(|e| format!("{}.\nPlease ensure {}/{} exists.", e, dir, keyname))?;
+    let path = format!("{}/{}", dir, keyname);
+    File::open(&path).map_err(|e| format!("{}.\nPlease ensure {}/{} exists.", e, dir, keyname))?;
+
+    let s = read_seed_file(&path)?;
+    Ok(format_output(s.clone(), json!({ "seed": s }), &output.kind))
+}
+
+/// Imports a seed as `<name>_<type>.nk` in a specified directory, or $WASH_KEYS ($HOME/.wash/keys) if directory is not specified
+pub(crate) fn import(
+    seed: &str,
+    name: &str,
+    directory: Option<String>,
+    encrypt: bool,
+    force: bool,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let seed = if Path::new(seed).is_file() {
+        read_seed_file(seed)?
+    } else {
+        seed.to_string()
+    };
+    let kp = KeyPair::from_seed(&seed).map_err(|e| format!("Invalid seed: {}", e))?;
 
-    let mut s = String::new();
-    let res = match f.read_to_string(&mut s) {
-        Ok(_) => Ok(s),
-        Err(e) => Err(e),
+    let dir = determine_directory(directory)?;
+    let path = format!(
+        "{}/{}_{}.nk",
+        dir,
+        name,
+        keypair_type_to_string(key_type(&kp.public_key())?)
+    );
+    if Path::new(&path).exists() && !force {
+        return Err(format!("{} already exists, use --force to overwrite it", path).into());
+    }
+    let contents = if encrypt {
+        encrypt_seed(
+            &seed,
+            &passphrase(&format!("New passphrase for {}: ", path), true)?,
+        )?
+    } else {
+        seed
     };
-    match res {
-        Err(e) => Err(e.into()),
-        Ok(s) => Ok(format_output(
-            s.trim().to_string(),
-            json!({ "seed": s.trim() }),
+    write_key_file(&path, &contents)?;
+
+    Ok(format_output(
+        format!("Imported {} to {}", kp.public_key(), path),
+        json!({"result": "success", "public_key": kp.public_key(), "path": path, "encrypted": encrypt}),
+        &output.kind,
+    ))
+}
+
+/// Outputs the plaintext seed of a key, or writes it to a destination file
+pub(crate) fn export(
+    keyname: &str,
+    directory: Option<String>,
+    destination: Option<String>,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let path = key_path(keyname, directory)?;
+    let seed = read_seed_file(&path)?;
+    match destination {
+        Some(destination) => {
+            write_key_file(&destination, &seed)?;
+            Ok(format_output(
+                format!("Exported {} to {}", path, destination),
+                json!({"result": "success", "destination": destination}),
+                &output.kind,
+            ))
+        }
+        None => Ok(format_output(
+            seed.clone(),
+            json!({ "seed": seed }),
             &output.kind,
         )),
     }
 }
 
+/// Encrypts a plaintext key in place
+pub(crate) fn encrypt(
+    keyname: &str,
+    directory: Option<String>,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let path = key_path(keyname, directory)?;
+    let contents = fs::read_to_string(&path)?;
+    if is_encrypted(&contents) {
+        return Err(format!("{} is already encrypted", path).into());
+    }
+    let seed = contents.trim();
+    KeyPair::from_seed(seed)
+        .map_err(|e| format!("{} does not contain a valid seed: {}", path, e))?;
+    write_key_file(
+        &path,
+        &encrypt_seed(
+            seed,
+            &passphrase(&format!("New passphrase for {}: ", path), true)?,
+        )?,
+    )?;
+
+    Ok(format_output(
+        format!("Encrypted {}", path),
+        json!({"result": "success", "path": path}),
+        &output.kind,
+    ))
+}
+
+/// Decrypts an encrypted key in place
+pub(crate) fn decrypt(
+    keyname: &str,
+    directory: Option<String>,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let path = key_path(keyname, directory)?;
+    if !is_encrypted(&fs::read_to_string(&path)?) {
+        return Err(format!("{} is not encrypted", path).into());
+    }
+    let seed = read_seed_file(&path)?;
+    write_key_file(&path, &seed)?;
+
+    Ok(format_output(
+        format!("Decrypted {}", path),
+        json!({"result": "success", "path": path}),
+        &output.kind,
+    ))
+}
+
+/// Validates a seed or public key, optionally signing a payload with a seed or verifying
+/// the signature of a payload to prove possession of the seed
+pub(crate) fn inspect(
+    key: &str,
+    payload: Option<String>,
+    signature: Option<String>,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let key = if Path::new(key).is_file() {
+        read_seed_file(key)?
+    } else {
+        key.trim().to_string()
+    };
+    if !valid_checksum(&key) {
+        return Err("Invalid key: checksum mismatch".into());
+    }
+    let kp = if key.starts_with('S') {
+        KeyPair::from_seed(&key).map_err(|e| format!("Invalid seed: {}", e))?
+    } else {
+        KeyPair::from_public_key(&key).map_err(|e| format!("Invalid key: {}", e))?
+    };
+    let public_key = kp.public_key();
+    let keytype = keypair_type_to_string(key_type(&public_key)?);
+
+    let mut text = format!("Public Key: {}\nType: {}", public_key, keytype);
+    let mut json = json!({
+        "public_key": public_key,
+        "type": keytype,
+    });
+    if let Some(payload) = payload {
+        let payload = if Path::new(&payload).is_file() {
+            fs::read(&payload)?
+        } else {
+            payload.into_bytes()
+        };
+        let signature = match signature {
+            Some(signature) => base64::decode_config(signature.trim(), base64::URL_SAFE_NO_PAD)
+                .map_err(|e| format!("Invalid signature: {}", e))?,
+            None => kp.sign(&payload).map_err(|e| {
+                format!(
+                    "Unable to sign payload with {}, a seed is required: {}",
+                    public_key, e
+                )
+            })?,
+        };
+        kp.verify(&payload, &signature)
+            .map_err(|_| format!("Signature is not valid for {}", public_key))?;
+        let signature = base64::encode_config(&signature, base64::URL_SAFE_NO_PAD);
+        text.push_str(&format!("\nSignature: {}\nVerified: true", signature));
+        json["signature"] = json!(signature);
+        json["verified"] = json!(true);
+    }
+
+    Ok(format_output(text, json, &output.kind))
+}
+
+/// Checks the CRC16 checksum at the end of an encoded key. `nkeys` does not reject seeds with a
+/// mismatched checksum, so this is checked separately before a key is reported as valid
+fn valid_checksum(key: &str) -> bool {
+    let raw = match data_encoding::BASE32_NOPAD.decode(key.as_bytes()) {
+        Ok(raw) if raw.len() > 2 => raw,
+        _ => return false,
+    };
+    let (data, checksum) = raw.split_at(raw.len() - 2);
+    let crc = data.iter().fold(0u16, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    });
+    crc.to_le_bytes() == checksum
+}
+
+/// Artifact issued by a rotated key, with its claims already updated for the new key
+enum Reissue {
+    Actor(String, Vec<u8>),
+    Provider(
+        String,
+        Box<(ProviderArchive, Claims<CapabilityProvider>)>,
+        bool,
+    ),
+}
+
+/// Rotates an account or operator key. The new key replaces the old one under the same name, the old key
+/// is kept alongside it, and the token and artifacts issued by the old key are updated to use the new key.
+/// Everything is prepared before the first file is written, so a failure leaves the old key in place.
+pub(crate) fn rotate(
+    keyname: &str,
+    directory: Option<String>,
+    token: Option<String>,
+    token_issuer: Option<String>,
+    signer: Option<String>,
+    resign: Option<String>,
+    output: &Output,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let path = key_path(keyname, directory.clone())?;
+    let was_encrypted = is_encrypted(&fs::read_to_string(&path)?);
+    let old = KeyPair::from_seed(&read_seed_file(&path)?)?;
+    let keytype = key_type(&old.public_key())?;
+    if !matches!(keytype, KeyPairType::Account | KeyPairType::Operator) {
+        return Err(format!(
+            "Only account and operator keys can be rotated, {} is a {} key",
+            path,
+            keypair_type_to_string(keytype)
+        )
+        .into());
+    }
+    let new = KeyPair::new(keytype.clone());
+
+    let updated_token = match token.as_ref() {
+        Some(token_path) => {
+            let jwt = fs::read_to_string(token_path)?;
+            let jwt = jwt.trim();
+            let issuer =
+                |public_key: &str| -> Result<Box<dyn Signer>, Box<dyn ::std::error::Error>> {
+                    if public_key == old.public_key() {
+                        return Ok(Box::new(KeyPair::from_seed(&old.seed()?)?));
+                    }
+                    let issuer = extract_signer(
+                        token_issuer.clone(),
+                        None,
+                        directory.clone(),
+                        KeyPairType::Operator,
+                        true,
+                        signer.clone(),
+                    )
+                    .map_err(|e| {
+                        format!(
+                            "The key that issued {} is required with --token-issuer: {}",
+                            token_path, e
+                        )
+                    })?;
+                    if issuer.public_key() != public_key {
+                        return Err(format!(
+                            "{} was issued by {}, not {}",
+                            token_path,
+                            public_key,
+                            issuer.public_key()
+                        )
+                        .into());
+                    }
+                    Ok(issuer)
+                };
+            let updated = match keytype {
+                KeyPairType::Operator => add_valid_signer::<Operator>(
+                    jwt,
+                    &old.public_key(),
+                    &new.public_key(),
+                    issuer,
+                    |md| &mut md.valid_signers,
+                ),
+                _ => add_valid_signer::<Account>(
+                    jwt,
+                    &old.public_key(),
+                    &new.public_key(),
+                    issuer,
+                    |md| &mut md.valid_signers,
+                ),
+            }
+            .map_err(|e| format!("Unable to update {}: {}", token_path, e))?;
+            Some((token_path.clone(), updated))
+        }
+        None => None,
+    };
+
+    let reissues = match resign.as_ref() {
+        Some(dir) => find_reissues(dir, &old.public_key(), &new)?,
+        None => vec![],
+    };
+
+    // Prompt for the new passphrase before anything is written, so an aborted prompt leaves no backup behind
+    let seed = new.seed()?;
+    let contents = if was_encrypted {
+        encrypt_seed(
+            &seed,
+            &passphrase(&format!("New passphrase for {}: ", path), true)?,
+        )?
+    } else {
+        seed
+    };
+
+    // Persist the new key first so re-signed artifacts always have their key
+    let backup = format!("{}.{}.old", path, old.public_key());
+    write_key_file(&backup, &fs::read_to_string(&path)?)?;
+    write_key_file(&path, &contents)?;
+    if let Some((token_path, jwt)) = updated_token.as_ref() {
+        fs::write(token_path, jwt)?;
+    }
+    let mut resigned = vec![];
+    for reissue in reissues {
+        match reissue {
+            Reissue::Actor(module, buf) => {
+                fs::write(&module, buf)?;
+                resigned.push(module);
+            }
+            Reissue::Provider(archive, reissue, compress) => {
+                let (par, claims) = *reissue;
+                write_archive(&par, claims, &archive, &new, compress)?;
+                resigned.push(archive);
+            }
+        }
+    }
+
+    let mut text = format!(
+        "Rotated {}\nPrevious public key: {}\nNew public key: {}\nPrevious key saved to {}",
+        path,
+        old.public_key(),
+        new.public_key(),
+        backup
+    );
+    if let Some(token_path) = token.as_ref() {
+        text.push_str(&format!(
+            "\nAdded {} to the signing keys of {}",
+            new.public_key(),
+            token_path
+        ));
+    }
+    if resign.is_some() {
+        text.push_str(&format!("\nRe-signed {} artifact(s)", resigned.len()));
+        for artifact in resigned.iter() {
+            text.push_str(&format!("\n  {}", artifact));
+        }
+    }
+    Ok(format_output(
+        text,
+        json!({
+            "result": "success",
+            "path": path,
+            "backup": backup,
+            "public_key": new.public_key(),
+            "previous_public_key": old.public_key(),
+            "token": token,
+            "resigned": resigned,
+        }),
+        &output.kind,
+    ))
+}
+
+/// Adds the successor of a rotated key to the signing keys of an operator or account token,
+/// re-signing it with its original issuer
+fn add_valid_signer<T>(
+    jwt: &str,
+    old_key: &str,
+    new_key: &str,
+    issuer: impl Fn(&str) -> Result<Box<dyn Signer>, Box<dyn ::std::error::Error>>,
+    valid_signers: fn(&mut T) -> &mut Option<Vec<String>>,
+) -> Result<String, Box<dyn ::std::error::Error>>
+where
+    T: Serialize + DeserializeOwned + WascapEntity + Default,
+{
+    let mut claims = Claims::<T>::decode(jwt)?;
+    let mut metadata = claims.metadata.take().unwrap_or_default();
+    let signers = valid_signers(&mut metadata).get_or_insert_with(Vec::new);
+    if claims.subject != old_key && !signers.iter().any(|k| k == old_key) {
+        return Err(format!("token does not belong to {}", old_key).into());
+    }
+    if !signers.iter().any(|k| k == new_key) {
+        signers.push(new_key.to_string());
+    }
+    claims.metadata = Some(metadata);
+    claims.issued_at = now();
+    encode_claims(&claims, issuer(&claims.issuer)?.as_ref())
+}
+
+/// Finds the signed actors and provider archives in a directory that were issued by the old key,
+/// and re-signs them in memory with the new key
+fn find_reissues(
+    dir: &str,
+    old_key: &str,
+    new_key: &KeyPair,
+) -> Result<Vec<Reissue>, Box<dyn ::std::error::Error>> {
+    if !Path::new(dir).is_dir() {
+        return Err(format!("{} is not a directory", dir).into());
+    }
+    let mut artifacts = vec![];
+    for pattern in &["**/*_s.wasm", "**/*.par", "**/*.par.gz"] {
+        let pattern = Path::new(dir).join(pattern);
+        artifacts.extend(glob::glob(&pattern.to_string_lossy())?.filter_map(Result::ok));
+    }
+    artifacts.sort();
+
+    let mut reissues = vec![];
+    for artifact in artifacts {
+        let artifact = artifact.display().to_string();
+        let buf = fs::read(&artifact)?;
+        if artifact.ends_with(".wasm") {
+            let token = wascap::wasm::extract_claims(&buf)
+                .map_err(|e| format!("Unable to read claims of {}: {}", artifact, e))?;
+            if let Some(token) = token.filter(|t| t.claims.issuer == old_key) {
+                let mut claims = token.claims;
+                claims.issuer = new_key.public_key();
+                claims.issued_at = now();
+                let signed = embed_claims(&buf, &claims, new_key)?;
+                reissues.push(Reissue::Actor(artifact, signed));
+            }
+        } else {
+            let (par, token) = parse_archive(&buf)
+                .map_err(|e| format!("Unable to load provider archive {}: {}", artifact, e))?;
+            if token.claims.issuer == old_key {
+                let mut claims = token.claims;
+                claims.issuer = new_key.public_key();
+                claims.issued_at = now();
+                let compress = is_compressed(&buf)?;
+                reissues.push(Reissue::Provider(
+                    artifact,
+                    Box::new((par, claims)),
+                    compress,
+                ));
+            }
+        }
+    }
+    Ok(reissues)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn key_path(
+    keyname: &str,
+    directory: Option<String>,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let dir = determine_directory(directory)?;
+    let path = format!("{}/{}", dir, keyname);
+    if Path::new(&path).is_file() {
+        Ok(path)
+    } else {
+        Err(format!("Please ensure {} exists.", path).into())
+    }
+}
+
 /// Lists all keypairs (file extension .nk) in a specified directory or $WASH_KEYS($HOME/.wash/keys) if directory is not specified
 pub(crate) fn list(
     directory: Option<String>,
+    keytype: Option<KeyPairType>,
     output: &Output,
 ) -> Result<String, Box<dyn ::std::error::Error>> {
     let dir = determine_directory(directory)?;
@@ -130,17 +876,99 @@ pub(crate) fn list(
         .map_err(|e| format!("Error: {}, please ensure directory {} exists", e, dir))?;
 
     for path in paths {
-        let f = String::from(path.unwrap().file_name().to_str().unwrap());
+        let path = path?.path();
+        let f = String::from(path.file_name().unwrap().to_str().unwrap());
         if f.ends_with(".nk") {
-            keys.push(f);
+            keys.push((f, path.display().to_string(), key_info(&path)));
         }
     }
+    keys.sort_by(|a, b| a.0.cmp(&b.0));
+    // Keys that cannot be read have no type, so they are only listed without a filter
+    if let Some(kt) = keytype {
+        let kt = keypair_type_to_string(kt);
+        keys.retain(|(_, _, info)| matches!(info, Ok(info) if keypair_type_to_string(info.keytype.clone()) == kt));
+    }
 
-    Ok(format_output(
-        format!("====== Keys found in {} ======\n{}", dir, keys.join("\n")),
-        json!({ "keys": keys }),
-        &output.kind,
-    ))
+    match output.kind {
+        OutputKind::Json => {
+            let keys: Vec<serde_json::Value> = keys
+                .iter()
+                .map(|(name, path, info)| match info {
+                    Ok(info) => json!({
+                        "name": name,
+                        "path": path,
+                        "type": keypair_type_to_string(info.keytype.clone()),
+                        "public_key": info.public_key,
+                        "encrypted": info.encrypted,
+                    }),
+                    Err(e) => json!({ "name": name, "path": path, "error": e }),
+                })
+                .collect();
+            Ok(format!("{}", json!({ "keys": keys })))
+        }
+        OutputKind::Text { max_width } => {
+            let mut table = Table::new();
+            crate::util::configure_table_style(&mut table, 4, max_width);
+            table.add_row(Row::new(vec![
+                TableCell::new_with_alignment("Name", 1, Alignment::Left),
+                TableCell::new_with_alignment("Type", 1, Alignment::Left),
+                TableCell::new_with_alignment("Public Key", 1, Alignment::Left),
+                TableCell::new_with_alignment("Path", 1, Alignment::Left),
+            ]));
+            for (name, path, info) in keys.iter() {
+                let (keytype, public_key) = match info {
+                    Ok(info) if info.encrypted => (
+                        format!(
+                            "{} (encrypted)",
+                            keypair_type_to_string(info.keytype.clone())
+                        ),
+                        info.public_key.clone(),
+                    ),
+                    Ok(info) => (
+                        keypair_type_to_string(info.keytype.clone()),
+                        info.public_key.clone(),
+                    ),
+                    Err(e) => ("invalid".to_string(), e.clone()),
+                };
+                table.add_row(Row::new(vec![
+                    TableCell::new_with_alignment(name, 1, Alignment::Left),
+                    TableCell::new_with_alignment(keytype, 1, Alignment::Left),
+                    TableCell::new_with_alignment(public_key, 1, Alignment::Left),
+                    TableCell::new_with_alignment(path, 1, Alignment::Left),
+                ]));
+            }
+            Ok(format!(
+                "====== Keys found in {} ======\n{}",
+                dir,
+                table.render()
+            ))
+        }
+    }
+}
+
+struct KeyInfo {
+    keytype: KeyPairType,
+    public_key: String,
+    encrypted: bool,
+}
+
+/// Reads the type and public key of a key file. Encrypted keys store their public key
+/// in the clear, so no passphrase is needed to inspect them
+fn key_info(path: &Path) -> Result<KeyInfo, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (public_key, encrypted) = if is_encrypted(&contents) {
+        let encrypted: EncryptedSeed =
+            serde_json::from_str(&contents).map_err(|e| format!("Invalid encrypted key: {}", e))?;
+        (encrypted.public_key, true)
+    } else {
+        let kp = KeyPair::from_seed(contents.trim()).map_err(|e| format!("Invalid seed: {}", e))?;
+        (kp.public_key(), false)
+    };
+    Ok(KeyInfo {
+        keytype: key_type(&public_key).map_err(|e| e.to_string())?,
+        public_key,
+        encrypted,
+    })
 }
 
 fn determine_directory(directory: Option<String>) -> Result<String, Error> {
@@ -168,11 +996,7 @@ pub(crate) fn extract_keypair(
     let seed = if let Some(input_str) = input {
         match File::open(input_str.clone()) {
             // User provided file path to seed as argument
-            Ok(mut f) => {
-                let mut s = String::new();
-                f.read_to_string(&mut s)?;
-                s
-            }
+            Ok(_) => read_seed_file(&input_str)?,
             // User provided seed as an argument
             Err(_e) => input_str,
         }
@@ -197,11 +1021,7 @@ pub(crate) fn extract_keypair(
         );
         match File::open(path.clone()) {
             // Default key found
-            Ok(mut f) => {
-                let mut s = String::new();
-                f.read_to_string(&mut s)?;
-                s
-            }
+            Ok(_) => read_seed_file(&path)?,
             // No default key, generating for user
             Err(_e) if !disable_keygen => {
                 print_or_log(crate::util::format_output(
@@ -217,9 +1037,11 @@ If you'd like to use alternative keys, you can supply them as a flag.\n",
 
                 let kp = KeyPair::new(keygen_type);
                 let seed = kp.seed()?;
-                fs::create_dir_all(Path::new(&path).parent().unwrap())?;
-                let mut f = File::create(path)?;
-                f.write_all(seed.as_bytes())?;
+                // Generated keys are encrypted when a passphrase is available without prompting
+                match env::var(WASH_KEYS_PASSPHRASE) {
+                    Ok(passphrase) => write_key_file(&path, &encrypt_seed(&seed, &passphrase)?)?,
+                    Err(_) => write_key_file(&path, &seed)?,
+                }
                 seed
             }
             _ => {
@@ -237,6 +1059,155 @@ If you'd like to use alternative keys, you can supply them as a flag.\n",
     KeyPair::from_seed(&seed).map_err(|e| format!("{}", e).into())
 }
 
+/// Reads the seed stored in a key file, unlocking it with a passphrase if it is encrypted
+pub(crate) fn read_seed_file(path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)?;
+    if is_encrypted(&contents) {
+        decrypt_seed(
+            &contents,
+            &passphrase(&format!("Passphrase for {}: ", path), false)?,
+        )
+        .map_err(|e| format!("Unable to unlock {}: {}", path, e).into())
+    } else {
+        Ok(contents.trim().to_string())
+    }
+}
+
+/// Writes a key file readable only by the current user, creating parent directories as needed
+pub(crate) fn write_key_file(path: &str, contents: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to newly created files
+        if Path::new(path).exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+fn is_encrypted(contents: &str) -> bool {
+    contents.trim_start().starts_with('{')
+}
+
+/// Reads the keystore passphrase from $WASH_KEYS_PASSPHRASE, or prompts for it on the terminal
+fn passphrase(prompt: &str, confirm: bool) -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(passphrase) = env::var(WASH_KEYS_PASSPHRASE) {
+        return Ok(passphrase);
+    }
+    let mut tty = termion::get_tty().map_err(|_| {
+        format!(
+            "A passphrase is required, set ${} when not running in a terminal",
+            WASH_KEYS_PASSPHRASE
+        )
+    })?;
+    let mut read = |prompt: &str| -> Result<String, Box<dyn std::error::Error>> {
+        tty.write_all(prompt.as_bytes())?;
+        tty.flush()?;
+        let passphrase = tty.try_clone()?.read_passwd(&mut tty)?.unwrap_or_default();
+        tty.write_all(b"\n")?;
+        Ok(passphrase)
+    };
+    let passphrase = read(prompt)?;
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".into());
+    }
+    if confirm && read("Confirm passphrase: ")? != passphrase {
+        return Err("Passphrases do not match".into());
+    }
+    Ok(passphrase)
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    iterations: u32,
+) -> Result<LessSafeKey, Box<dyn std::error::Error>> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(iterations).ok_or("Invalid number of key derivation iterations")?,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key).map_err(|_| "Invalid encryption key")?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn encrypt_seed(seed: &str, passphrase: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let public_key = KeyPair::from_seed(seed)?.public_key();
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt).map_err(|_| "Unable to generate salt")?;
+    rng.fill(&mut nonce)
+        .map_err(|_| "Unable to generate nonce")?;
+
+    let mut ciphertext = seed.as_bytes().to_vec();
+    derive_key(passphrase, &salt, KDF_ITERATIONS)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(public_key.as_bytes()),
+            &mut ciphertext,
+        )
+        .map_err(|_| "Unable to encrypt seed")?;
+
+    Ok(serde_json::to_string_pretty(&EncryptedSeed {
+        version: KEYSTORE_VERSION,
+        public_key,
+        kdf: KDF_PBKDF2_SHA256.to_string(),
+        iterations: KDF_ITERATIONS,
+        salt: base64::encode(salt),
+        cipher: CIPHER_CHACHA20_POLY1305.to_string(),
+        nonce: base64::encode(nonce),
+        ciphertext: base64::encode(ciphertext),
+    })?)
+}
+
+fn decrypt_seed(contents: &str, passphrase: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let encrypted: EncryptedSeed = serde_json::from_str(contents)?;
+    if encrypted.version != KEYSTORE_VERSION
+        || encrypted.kdf != KDF_PBKDF2_SHA256
+        || encrypted.cipher != CIPHER_CHACHA20_POLY1305
+    {
+        return Err("Unsupported encrypted key format".into());
+    }
+    let nonce = Nonce::try_assume_unique_for_key(&base64::decode(&encrypted.nonce)?)
+        .map_err(|_| "Invalid nonce")?;
+    let mut buf = base64::decode(&encrypted.ciphertext)?;
+    let seed = derive_key(
+        passphrase,
+        &base64::decode(&encrypted.salt)?,
+        encrypted.iterations,
+    )?
+    .open_in_place(nonce, Aad::from(encrypted.public_key.as_bytes()), &mut buf)
+    .map_err(|_| "incorrect passphrase")?;
+    Ok(String::from_utf8(seed.to_vec())?)
+}
+
+/// Determines the type of a key from the prefix of its public key
+fn key_type(public_key: &str) -> Result<KeyPairType, Box<dyn std::error::Error>> {
+    use KeyPairType::*;
+    match public_key.chars().next() {
+        Some('A') => Ok(Account),
+        Some('C') => Ok(Cluster),
+        Some('V') => Ok(Service),
+        Some('M') => Ok(Module),
+        Some('N') => Ok(Server),
+        Some('O') => Ok(Operator),
+        Some('U') => Ok(User),
+        _ => Err(format!("Unknown key type for {}", public_key).into()),
+    }
+}
+
 fn keypair_type_to_string(keypair_type: KeyPairType) -> String {
     use KeyPairType::*;
     match keypair_type {
@@ -252,7 +1223,9 @@ fn keypair_type_to_string(keypair_type: KeyPairType) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{generate, KeysCli, KeysCliCommand, OutputKind};
+    use super::{
+        decrypt_seed, encrypt_seed, generate, valid_checksum, KeysCli, KeysCliCommand, OutputKind,
+    };
     use nkeys::KeyPairType;
     use serde::Deserialize;
     use structopt::StructOpt;
@@ -364,7 +1337,13 @@ mod tests {
         key_gen_types.iter().for_each(|cmd| {
             let gen_cmd = KeysCli::from_iter(&["keys", "gen", cmd]);
             match gen_cmd.command {
-                KeysCliCommand::GenCommand { keytype, output } => {
+                KeysCliCommand::GenCommand {
+                    keytype,
+                    name,
+                    output,
+                    ..
+                } => {
+                    assert_eq!(name, None);
                     use KeyPairType::*;
                     match keytype {
                         Account => assert_eq!(*cmd, "account"),
@@ -384,7 +1363,13 @@ mod tests {
         key_gen_types.iter().for_each(|cmd| {
             let gen_cmd = KeysCli::from_iter(&["keys", "gen", cmd, "-o", "json"]);
             match gen_cmd.command {
-                KeysCliCommand::GenCommand { keytype, output } => {
+                KeysCliCommand::GenCommand {
+                    keytype,
+                    name,
+                    output,
+                    ..
+                } => {
+                    assert_eq!(name, None);
                     use KeyPairType::*;
                     match keytype {
                         Account => assert_eq!(*cmd, "account"),
@@ -400,6 +1385,35 @@ mod tests {
                 _ => panic!("`keys gen` constructed incorrect command"),
             };
         });
+
+        let gen_named = KeysCli::from_iter(&[
+            "keys",
+            "gen",
+            "module",
+            "--name",
+            "echo",
+            "-d",
+            "./tests/fixtures",
+            "--force",
+            "-o",
+            "json",
+        ]);
+        match gen_named.command {
+            KeysCliCommand::GenCommand {
+                keytype,
+                name,
+                directory,
+                force,
+                output,
+            } => {
+                assert!(matches!(keytype, KeyPairType::Module));
+                assert_eq!(name, Some("echo".to_string()));
+                assert_eq!(directory, Some("./tests/fixtures".to_string()));
+                assert!(force);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            _ => panic!("`keys gen` constructed incorrect command"),
+        };
     }
 
     #[test]
@@ -453,11 +1467,250 @@ mod tests {
 
         let list_all_flags = KeysCli::from_iter(&["keys", "list", "-d", KEYPATH, "-o", "json"]);
         match list_all_flags.command {
-            KeysCliCommand::ListCommand { directory, output } => {
+            KeysCliCommand::ListCommand {
+                directory,
+                keytype,
+                output,
+            } => {
                 assert_eq!(directory, Some(KEYPATH.to_string()));
+                assert!(keytype.is_none());
                 assert_eq!(output.kind, OutputKind::Json);
             }
             other_cmd => panic!("keys get generated other command {:?}", other_cmd),
         }
+
+        let list_filtered = KeysCli::from_iter(&["keys", "list", "--type", "Account"]);
+        match list_filtered.command {
+            KeysCliCommand::ListCommand { keytype, .. } => {
+                assert!(matches!(keytype, Some(KeyPairType::Account)))
+            }
+            other_cmd => panic!("keys list generated other command {:?}", other_cmd),
+        }
+    }
+
+    #[test]
+    /// Enumerates multiple options of the `import` command to ensure API doesn't
+    /// change between versions. This test will fail if `wash keys import`
+    /// changes syntax, ordering of required elements, or flags.
+    fn test_import_comprehensive() {
+        const SEED: &str = "SMAGCRMDVSCKDLSJBAESKJUTWNJKCRRWJK5FQXQZ2POTYWA3JSS63HILFU";
+        const KEYPATH: &str = "./tests/fixtures";
+
+        let import_all_flags = KeysCli::from_iter(&[
+            "keys",
+            "import",
+            SEED,
+            "--name",
+            "imported",
+            "-d",
+            KEYPATH,
+            "--encrypt",
+            "--force",
+            "-o",
+            "json",
+        ]);
+        match import_all_flags.command {
+            KeysCliCommand::ImportCommand {
+                seed,
+                name,
+                directory,
+                encrypt,
+                force,
+                output,
+            } => {
+                assert_eq!(seed, SEED);
+                assert_eq!(name, "imported");
+                assert_eq!(directory, Some(KEYPATH.to_string()));
+                assert!(encrypt);
+                assert!(force);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            other_cmd => panic!("keys import generated other command {:?}", other_cmd),
+        }
+
+        let export_all_flags = KeysCli::from_iter(&[
+            "keys",
+            "export",
+            "imported_module.nk",
+            "-d",
+            KEYPATH,
+            "--destination",
+            "./seed.nk",
+            "-o",
+            "json",
+        ]);
+        match export_all_flags.command {
+            KeysCliCommand::ExportCommand {
+                keyname,
+                directory,
+                destination,
+                output,
+            } => {
+                assert_eq!(keyname, "imported_module.nk");
+                assert_eq!(directory, Some(KEYPATH.to_string()));
+                assert_eq!(destination, Some("./seed.nk".to_string()));
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            other_cmd => panic!("keys export generated other command {:?}", other_cmd),
+        }
+
+        let encrypt_cmd = KeysCli::from_iter(&["keys", "encrypt", "imported_module.nk"]);
+        match encrypt_cmd.command {
+            KeysCliCommand::EncryptCommand { keyname, .. } => {
+                assert_eq!(keyname, "imported_module.nk")
+            }
+            other_cmd => panic!("keys encrypt generated other command {:?}", other_cmd),
+        }
+        let decrypt_cmd = KeysCli::from_iter(&["keys", "decrypt", "imported_module.nk"]);
+        match decrypt_cmd.command {
+            KeysCliCommand::DecryptCommand { keyname, .. } => {
+                assert_eq!(keyname, "imported_module.nk")
+            }
+            other_cmd => panic!("keys decrypt generated other command {:?}", other_cmd),
+        }
+    }
+
+    #[test]
+    /// Enumerates multiple options of the `inspect` command to ensure API doesn't
+    /// change between versions. This test will fail if `wash keys inspect`
+    /// changes syntax, ordering of required elements, or flags.
+    fn test_inspect_comprehensive() {
+        const SEED: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+
+        let inspect_basic = KeysCli::from_iter(&["keys", "inspect", SEED]);
+        match inspect_basic.command {
+            KeysCliCommand::InspectCommand {
+                key,
+                payload,
+                signature,
+                output,
+            } => {
+                assert_eq!(key, SEED);
+                assert!(payload.is_none());
+                assert!(signature.is_none());
+                assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
+            }
+            other_cmd => panic!("keys inspect generated other command {:?}", other_cmd),
+        }
+
+        let inspect_all_flags = KeysCli::from_iter(&[
+            "keys",
+            "inspect",
+            SEED,
+            "--payload",
+            "hello",
+            "--signature",
+            "c2lnbmF0dXJl",
+            "-o",
+            "json",
+        ]);
+        match inspect_all_flags.command {
+            KeysCliCommand::InspectCommand {
+                key,
+                payload,
+                signature,
+                output,
+            } => {
+                assert_eq!(key, SEED);
+                assert_eq!(payload, Some("hello".to_string()));
+                assert_eq!(signature, Some("c2lnbmF0dXJl".to_string()));
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            other_cmd => panic!("keys inspect generated other command {:?}", other_cmd),
+        }
+
+        assert!(
+            KeysCli::from_iter_safe(&["keys", "inspect", SEED, "--signature", "c2ln"]).is_err()
+        );
+    }
+
+    #[test]
+    /// Enumerates multiple options of the `rotate` command to ensure API doesn't
+    /// change between versions. This test will fail if `wash keys rotate`
+    /// changes syntax, ordering of required elements, or flags.
+    fn test_rotate_comprehensive() {
+        const KEYPATH: &str = "./tests/fixtures";
+
+        let rotate_basic = KeysCli::from_iter(&["keys", "rotate", "issuer_account.nk"]);
+        match rotate_basic.command {
+            KeysCliCommand::RotateCommand {
+                keyname,
+                token,
+                resign,
+                ..
+            } => {
+                assert_eq!(keyname, "issuer_account.nk");
+                assert!(token.is_none());
+                assert!(resign.is_none());
+            }
+            other_cmd => panic!("keys rotate generated other command {:?}", other_cmd),
+        }
+
+        let rotate_all_flags = KeysCli::from_iter(&[
+            "keys",
+            "rotate",
+            "issuer_account.nk",
+            "-d",
+            KEYPATH,
+            "--token",
+            "./account.jwt",
+            "--token-issuer",
+            "./operator.nk",
+            "--signer",
+            "./signer.sh",
+            "--resign",
+            "./build",
+            "-o",
+            "json",
+        ]);
+        match rotate_all_flags.command {
+            KeysCliCommand::RotateCommand {
+                keyname,
+                directory,
+                token,
+                token_issuer,
+                signer,
+                resign,
+                output,
+            } => {
+                assert_eq!(keyname, "issuer_account.nk");
+                assert_eq!(directory, Some(KEYPATH.to_string()));
+                assert_eq!(token, Some("./account.jwt".to_string()));
+                assert_eq!(token_issuer, Some("./operator.nk".to_string()));
+                assert_eq!(signer, Some("./signer.sh".to_string()));
+                assert_eq!(resign, Some("./build".to_string()));
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            other_cmd => panic!("keys rotate generated other command {:?}", other_cmd),
+        }
+    }
+
+    #[test]
+    fn test_valid_checksum() {
+        assert!(valid_checksum(
+            "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE"
+        ));
+        assert!(valid_checksum(
+            "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS"
+        ));
+        assert!(!valid_checksum(
+            "SAADC2FGJRMZCTYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE"
+        ));
+        assert!(!valid_checksum("not a key"));
+    }
+
+    #[test]
+    fn test_encrypted_seed_roundtrip() {
+        const SEED: &str = "SMAGCRMDVSCKDLSJBAESKJUTWNJKCRRWJK5FQXQZ2POTYWA3JSS63HILFU";
+
+        let encrypted = encrypt_seed(SEED, "correct horse").unwrap();
+        assert!(!encrypted.contains(SEED));
+        assert_eq!(decrypt_seed(&encrypted, "correct horse").unwrap(), SEED);
+        assert_eq!(
+            decrypt_seed(&encrypted, "battery staple")
+                .unwrap_err()
+                .to_string(),
+            "incorrect passphrase"
+        );
     }
 }
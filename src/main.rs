@@ -1,18 +1,24 @@
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
+mod arch;
 mod drain;
 use drain::DrainCli;
+mod caps;
 mod claims;
 use claims::ClaimsCli;
+mod credentials;
 mod ctl;
 use ctl::CtlCli;
+mod diff;
 mod keys;
 use keys::KeysCli;
+mod ocicache;
 mod par;
 use par::ParCli;
 mod reg;
 use reg::RegCli;
+mod signer;
 mod up;
 use up::UpCli;
 mod util;
@@ -85,7 +91,10 @@ async fn main() {
             0
         }
         Err(e) => {
-            eprintln!("Error: {}", e);
+            match e.downcast_ref::<util::FailedOutput>() {
+                Some(failed) => println!("{}", failed),
+                None => eprintln!("Error: {}", e),
+            }
             1
         }
     })
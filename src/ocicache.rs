@@ -0,0 +1,231 @@
+use crate::reg::sha256_digest;
+use log::debug;
+use oci_distribution::Reference;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds a tag stays resolved to the same digest before the registry is asked again
+pub(crate) const WASH_OCI_CACHE_TTL: &str = "WASH_OCI_CACHE_TTL";
+const DEFAULT_TAG_TTL: u64 = 300;
+
+/// The OCI cache directory of the wasmcloud host, which wash caches its pulls alongside so that
+/// `wash drain oci` clears both
+pub(crate) fn cache_dir() -> PathBuf {
+    env::temp_dir().join("wasmcloud_ocicache")
+}
+
+/// The digest a tag was resolved to, and when
+#[derive(Debug, Serialize, Deserialize)]
+struct TagEntry {
+    digest: String,
+    resolved_at: u64,
+}
+
+/// The digest of the contents of the artifact a manifest describes
+#[derive(Debug, Serialize, Deserialize)]
+struct ManifestEntry {
+    content: String,
+}
+
+/// Content-addressed cache of pulled artifacts. Artifacts are stored under `blobs` by the digest
+/// of their contents, which is checked whenever they are read. Manifest digests map to those
+/// contents through entries under `manifests`, and tags are resolved to manifest digests through
+/// entries under `tags` that expire after a TTL
+pub(crate) struct OciCache {
+    root: PathBuf,
+    ttl: Duration,
+}
+
+impl OciCache {
+    /// Opens the cache of the current user, or returns `None` if it is not private to them
+    pub(crate) fn new() -> Option<Self> {
+        let ttl = env::var(WASH_OCI_CACHE_TTL)
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(DEFAULT_TAG_TTL);
+        OciCache::at(cache_dir().join("wash"), Duration::from_secs(ttl))
+    }
+
+    fn at(root: PathBuf, ttl: Duration) -> Option<Self> {
+        match create_private_dir(&root) {
+            Ok(_) => Some(OciCache { root, ttl }),
+            Err(e) => {
+                debug!("Not using OCI cache at {}: {}", root.display(), e);
+                None
+            }
+        }
+    }
+
+    fn blob_path(&self, content: &str) -> PathBuf {
+        self.root.join("blobs").join(content.replace(':', "_"))
+    }
+
+    fn manifest_path(&self, digest: &str) -> PathBuf {
+        self.root
+            .join("manifests")
+            .join(format!("{}.json", digest.replace(':', "_")))
+    }
+
+    /// Tag entries are keyed by a digest of the whole reference, as no substitution of the
+    /// characters a reference contains keeps every reference distinct
+    fn tag_path(&self, image: &Reference) -> PathBuf {
+        let name = sha256_digest(image.whole().as_bytes()).replace(':', "_");
+        self.root.join("tags").join(format!("{}.json", name))
+    }
+
+    /// Returns the cached artifact of a manifest digest, as long as its contents still match
+    /// the digest they were stored under
+    pub(crate) fn artifact(&self, digest: &str) -> Option<Vec<u8>> {
+        let contents = fs::read_to_string(self.manifest_path(digest)).ok()?;
+        let entry: ManifestEntry = serde_json::from_str(&contents).ok()?;
+        let artifact = fs::read(self.blob_path(&entry.content)).ok()?;
+        if sha256_digest(&artifact) == entry.content {
+            Some(artifact)
+        } else {
+            debug!("Cached contents of {} do not match their digest", digest);
+            None
+        }
+    }
+
+    /// Returns the digest a tag was last resolved to, unless the resolution has expired
+    pub(crate) fn tag_digest(&self, image: &Reference) -> Option<String> {
+        let contents = fs::read_to_string(self.tag_path(image)).ok()?;
+        let entry: TagEntry = serde_json::from_str(&contents).ok()?;
+        if now().saturating_sub(entry.resolved_at) < self.ttl.as_secs() {
+            Some(entry.digest)
+        } else {
+            None
+        }
+    }
+
+    /// Records the digest a tag currently resolves to
+    pub(crate) fn resolve_tag(&self, image: &Reference, digest: &str) {
+        let entry = TagEntry {
+            digest: digest.to_string(),
+            resolved_at: now(),
+        };
+        if let Err(e) = write_entry(&self.tag_path(image), &entry) {
+            debug!("Unable to cache digest of {}: {}", image.whole(), e);
+        }
+    }
+
+    /// Caches a pulled artifact by the digest of its contents, maps its manifest digest to it and,
+    /// when pulled by tag, resolves the tag to that manifest digest
+    pub(crate) fn store(&self, image: &Reference, digest: &str, artifact: &[u8]) {
+        let entry = ManifestEntry {
+            content: sha256_digest(artifact),
+        };
+        let stored = write_file(&self.blob_path(&entry.content), artifact)
+            .and_then(|_| write_entry(&self.manifest_path(digest), &entry));
+        match stored {
+            Ok(_) if image.digest().is_none() => self.resolve_tag(image, digest),
+            Ok(_) => (),
+            Err(e) => debug!("Unable to cache {}: {}", image.whole(), e),
+        }
+    }
+}
+
+/// Creates a directory only the current user can access. Existing directories are restricted
+/// the same way, which fails for directories owned by another user
+fn create_private_dir(path: &Path) -> Result<(), Box<dyn ::std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700);
+        // The mode only applies to newly created directories
+        if path.exists() {
+            if fs::symlink_metadata(path)?.file_type().is_symlink() {
+                return Err(format!("{} is a symbolic link", path.display()).into());
+            }
+            fs::set_permissions(path, fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    if !path.exists() {
+        builder.create(path)?;
+    }
+    Ok(())
+}
+
+fn write_entry<T: Serialize>(path: &Path, entry: &T) -> Result<(), Box<dyn ::std::error::Error>> {
+    write_file(path, &serde_json::to_vec(entry)?)
+}
+
+/// Writes to a temporary file that is renamed into place, so that a partially written file is
+/// never read from the cache
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), Box<dyn ::std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:5790f650cff526fcbc1271107a05111a6647002098b74a9a5e2e26e3c0a116b8";
+
+    #[test]
+    fn test_cache_store_and_resolve() {
+        let root = env::temp_dir().join("wash_ocicache_test_store");
+        let _ = fs::remove_dir_all(&root);
+        let cache = OciCache::at(root.clone(), Duration::from_secs(60)).unwrap();
+        let image: Reference = "wasmcloud.azurecr.io/echo:0.2.0".parse().unwrap();
+
+        assert!(cache.tag_digest(&image).is_none());
+        assert!(cache.artifact(DIGEST).is_none());
+
+        cache.store(&image, DIGEST, b"artifact");
+        assert_eq!(cache.tag_digest(&image).unwrap(), DIGEST);
+        assert_eq!(cache.artifact(DIGEST).unwrap(), b"artifact");
+        assert!(root
+            .join("tags")
+            .join(format!(
+                "{}.json",
+                sha256_digest(b"wasmcloud.azurecr.io/echo:0.2.0").replace(':', "_")
+            ))
+            .exists());
+
+        // References that only differ in their separators are resolved independently
+        let nested: Reference = "wasmcloud.azurecr.io/echo/0:2.0".parse().unwrap();
+        assert!(cache.tag_digest(&nested).is_none());
+        let blob = root
+            .join("blobs")
+            .join(sha256_digest(b"artifact").replace(':', "_"));
+        assert!(blob.exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&root).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        // Tags resolve to nothing once their TTL has passed, but artifacts never expire
+        let expired = OciCache::at(root.clone(), Duration::from_secs(0)).unwrap();
+        assert!(expired.tag_digest(&image).is_none());
+        assert_eq!(expired.artifact(DIGEST).unwrap(), b"artifact");
+
+        // Contents that no longer match their digest are never returned
+        fs::write(&blob, b"tampered").unwrap();
+        assert!(cache.artifact(DIGEST).is_none());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
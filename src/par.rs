@@ -1,16 +1,26 @@
 extern crate provider_archive;
-use crate::keys::extract_keypair;
-use crate::util::{convert_error, format_output, Output, OutputKind, Result};
+use crate::arch::{resolve_target, target_from_triple};
+use crate::diff::Diff;
+use crate::reg::PullOptions;
+use crate::signer::{encode_claims, extract_signer, hash_bytes, PublicKey, Signer};
+use crate::util::{convert_error, format_output, FailedOutput, Output, OutputKind, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use nkeys::KeyPairType;
 use provider_archive::*;
 use serde_json::json;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
+use wascap::jwt::{CapabilityProvider, Claims, Token};
 
 const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const CLAIMS_JWT_FILE: &str = "claims.jwt";
 
 #[derive(Debug, StructOpt, Clone)]
 #[structopt(
@@ -38,6 +48,21 @@ pub(crate) enum ParCliCommand {
     /// Insert a provider into a provider archive file
     #[structopt(name = "insert")]
     Insert(InsertCommand),
+    /// Extract provider binaries from a provider archive file
+    #[structopt(name = "extract")]
+    Extract(ExtractCommand),
+    /// Remove a provider from a provider archive file
+    #[structopt(name = "remove")]
+    Remove(RemoveCommand),
+    /// Verify the signature, validity and binary hashes of a provider archive
+    #[structopt(name = "verify")]
+    Verify(VerifyCommand),
+    /// Update the metadata of a provider archive file and re-sign it
+    #[structopt(name = "update")]
+    Update(UpdateCommand),
+    /// Show the differences between the claims and binaries of two provider archives
+    #[structopt(name = "diff")]
+    Diff(DiffCommand),
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -89,13 +114,13 @@ pub(crate) struct CreateCommand {
     #[structopt(short = "n", long = "name")]
     name: String,
 
-    /// Architecture of provider binary in format ARCH-OS (e.g. x86_64-linux)
+    /// Architecture of provider binary in format ARCH-OS (e.g. x86_64-linux). Detected from the binary if omitted
     #[structopt(short = "a", long = "arch")]
-    arch: String,
+    arch: Option<String>,
 
-    /// Path to provider binary for populating the archive
-    #[structopt(short = "b", long = "binary")]
-    binary: String,
+    /// Provider binaries for populating the archive. Each can be a path, an ARCH-OS=path pair, or a directory with a subdirectory per target (e.g. x86_64-linux or x86_64-unknown-linux-gnu)
+    #[structopt(short = "b", long = "binary", required = true, number_of_values = 1)]
+    binaries: Vec<String>,
 
     /// File output destination path
     #[structopt(long = "destination")]
@@ -109,6 +134,10 @@ pub(crate) struct CreateCommand {
     #[structopt(long = "disable-keygen")]
     disable_keygen: bool,
 
+    /// Command used to sign with an issuer provided as a public key, keeping its seed outside of wash
+    #[structopt(long = "signer", env = "WASH_SIGNER")]
+    signer: Option<String>,
+
     #[structopt(flatten)]
     pub(crate) output: Output,
 }
@@ -123,31 +152,210 @@ pub(crate) struct InspectCommand {
     #[structopt(short = "d", long = "digest")]
     digest: Option<String>,
 
-    /// Allow latest artifact tags (if OCI URL is provided for <archive>)
-    #[structopt(long = "allow-latest")]
-    allow_latest: bool,
+    #[structopt(flatten)]
+    opts: PullOptions,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct ExtractCommand {
+    /// Path to provider archive or OCI URL of provider archive
+    #[structopt(name = "archive")]
+    archive: String,
+
+    /// Architecture of the binary to extract in format ARCH-OS (e.g. x86_64-linux)
+    #[structopt(
+        short = "a",
+        long = "arch",
+        required_unless = "all",
+        conflicts_with = "all"
+    )]
+    arch: Option<String>,
+
+    /// Extract the binaries of every architecture in the archive
+    #[structopt(long = "all")]
+    all: bool,
+
+    /// File to write the binary to, or directory to write every binary to with --all. Defaults to <ARCH>.bin in the current directory
+    #[structopt(long = "destination")]
+    destination: Option<String>,
+
+    /// Digest to verify artifact against (if OCI URL is provided for <archive>)
+    #[structopt(short = "d", long = "digest")]
+    digest: Option<String>,
+
+    #[structopt(flatten)]
+    opts: PullOptions,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct VerifyCommand {
+    /// Path to provider archive or OCI URL of provider archive
+    #[structopt(name = "archive")]
+    archive: String,
+
+    /// Public key of an account trusted to issue provider archives, can be repeated. If omitted, any issuer is accepted
+    #[structopt(long = "trusted-issuer", number_of_values = 1)]
+    trusted_issuers: Vec<String>,
+
+    /// Digest to verify artifact against (if OCI URL is provided for <archive>)
+    #[structopt(short = "d", long = "digest")]
+    digest: Option<String>,
+
+    #[structopt(flatten)]
+    opts: PullOptions,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct DiffCommand {
+    /// Path to provider archive or OCI URL of provider archive to compare from
+    #[structopt(name = "left")]
+    left: String,
+
+    /// Path to provider archive or OCI URL of provider archive to compare to
+    #[structopt(name = "right")]
+    right: String,
+
+    /// Digest to verify the left artifact against (if OCI URL is provided for <left>)
+    #[structopt(long = "left-digest")]
+    left_digest: Option<String>,
+
+    /// Digest to verify the right artifact against (if OCI URL is provided for <right>)
+    #[structopt(long = "right-digest")]
+    right_digest: Option<String>,
+
+    #[structopt(flatten)]
+    opts: PullOptions,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct RemoveCommand {
+    /// Path to provider archive
+    #[structopt(name = "archive")]
+    archive: String,
+
+    /// Architecture of binary to remove in format ARCH-OS (e.g. x86_64-linux)
+    #[structopt(short = "a", long = "arch")]
+    arch: String,
+
+    /// Location of key files for signing. Defaults to $WASH_KEYS ($HOME/.wash/keys)
+    #[structopt(
+        short = "d",
+        long = "directory",
+        env = "WASH_KEYS",
+        hide_env_values = true
+    )]
+    directory: Option<String>,
+
+    /// Path to issuer seed key (account). If this flag is not provided, the will be sourced from $WASH_KEYS ($HOME/.wash/keys) or generated for you if it cannot be found.
+    #[structopt(
+        short = "i",
+        long = "issuer",
+        env = "WASH_ISSUER_KEY",
+        hide_env_values = true
+    )]
+    issuer: Option<String>,
+
+    /// Allow re-signing the archive with an issuer other than the account that originally signed it
+    #[structopt(long = "rotate-issuer")]
+    rotate_issuer: bool,
+
+    /// Path to subject seed key (service). If this flag is not provided, the subject of the existing archive is kept.
+    #[structopt(
+        short = "s",
+        long = "subject",
+        env = "WASH_SUBJECT_KEY",
+        hide_env_values = true
+    )]
+    subject: Option<String>,
+
+    /// Disables autogeneration of signing keys
+    #[structopt(long = "disable-keygen")]
+    disable_keygen: bool,
+
+    /// Command used to sign with an issuer provided as a public key, keeping its seed outside of wash
+    #[structopt(long = "signer", env = "WASH_SIGNER")]
+    signer: Option<String>,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct UpdateCommand {
+    /// Path to provider archive
+    #[structopt(name = "archive")]
+    archive: String,
+
+    /// New capability contract ID (e.g. wasmcloud:messaging or wasmcloud:keyvalue)
+    #[structopt(short = "c", long = "capid")]
+    capid: Option<String>,
+
+    /// New name of the capability provider
+    #[structopt(short = "n", long = "name")]
+    name: Option<String>,
+
+    /// New vendor string of the provider
+    #[structopt(short = "v", long = "vendor")]
+    vendor: Option<String>,
+
+    /// New monotonically increasing revision number
+    #[structopt(short = "r", long = "revision")]
+    revision: Option<i32>,
+
+    /// New human friendly version string
+    #[structopt(long = "version")]
+    version: Option<String>,
+
+    /// Location of key files for signing. Defaults to $WASH_KEYS ($HOME/.wash/keys)
+    #[structopt(
+        short = "d",
+        long = "directory",
+        env = "WASH_KEYS",
+        hide_env_values = true
+    )]
+    directory: Option<String>,
 
-    /// OCI username, if omitted anonymous authentication will be used
+    /// Path to issuer seed key (account). If this flag is not provided, the will be sourced from $WASH_KEYS ($HOME/.wash/keys) or generated for you if it cannot be found.
     #[structopt(
-        short = "u",
-        long = "user",
-        env = "WASH_REG_USER",
+        short = "i",
+        long = "issuer",
+        env = "WASH_ISSUER_KEY",
         hide_env_values = true
     )]
-    user: Option<String>,
+    issuer: Option<String>,
+
+    /// Allow re-signing the archive with an issuer other than the account that originally signed it
+    #[structopt(long = "rotate-issuer")]
+    rotate_issuer: bool,
 
-    /// OCI password, if omitted anonymous authentication will be used
+    /// Path to subject seed key (service). If this flag is not provided, the subject of the existing archive is kept.
     #[structopt(
-        short = "p",
-        long = "password",
-        env = "WASH_REG_PASSWORD",
+        short = "s",
+        long = "subject",
+        env = "WASH_SUBJECT_KEY",
         hide_env_values = true
     )]
-    password: Option<String>,
+    subject: Option<String>,
+
+    /// Disables autogeneration of signing keys
+    #[structopt(long = "disable-keygen")]
+    disable_keygen: bool,
 
-    /// Allow insecure (HTTP) registry connections
-    #[structopt(long = "insecure")]
-    insecure: bool,
+    /// Command used to sign with an issuer provided as a public key, keeping its seed outside of wash
+    #[structopt(long = "signer", env = "WASH_SIGNER")]
+    signer: Option<String>,
 
     #[structopt(flatten)]
     pub(crate) output: Output,
@@ -159,9 +367,9 @@ pub(crate) struct InsertCommand {
     #[structopt(name = "archive")]
     archive: String,
 
-    /// Architecture of binary in format ARCH-OS (e.g. x86_64-linux)
+    /// Architecture of binary in format ARCH-OS (e.g. x86_64-linux). Detected from the binary if omitted
     #[structopt(short = "a", long = "arch")]
-    arch: String,
+    arch: Option<String>,
 
     /// Path to provider binary to insert into archive
     #[structopt(short = "b", long = "binary")]
@@ -198,6 +406,10 @@ pub(crate) struct InsertCommand {
     #[structopt(long = "disable-keygen")]
     disable_keygen: bool,
 
+    /// Command used to sign with an issuer provided as a public key, keeping its seed outside of wash
+    #[structopt(long = "signer", env = "WASH_SIGNER")]
+    signer: Option<String>,
+
     #[structopt(flatten)]
     pub(crate) output: Output,
 }
@@ -207,6 +419,11 @@ pub(crate) async fn handle_command(command: ParCliCommand) -> Result<String> {
         ParCliCommand::Create(cmd) => handle_create(cmd),
         ParCliCommand::Inspect(cmd) => handle_inspect(cmd).await,
         ParCliCommand::Insert(cmd) => handle_insert(cmd),
+        ParCliCommand::Extract(cmd) => handle_extract(cmd).await,
+        ParCliCommand::Remove(cmd) => handle_remove(cmd),
+        ParCliCommand::Verify(cmd) => handle_verify(cmd).await,
+        ParCliCommand::Update(cmd) => handle_update(cmd),
+        ParCliCommand::Diff(cmd) => handle_diff(cmd).await,
     }
 }
 
@@ -217,36 +434,41 @@ pub(crate) fn handle_create(cmd: CreateCommand) -> Result<String> {
         &cmd.name,
         &cmd.vendor,
         cmd.revision,
-        cmd.version,
+        cmd.version.clone(),
     );
 
-    let mut f = File::open(cmd.binary.clone())?;
-    let mut lib = Vec::new();
-    f.read_to_end(&mut lib)?;
+    let binaries = collect_binaries(&cmd.binaries, cmd.arch.as_deref())?;
+    // Keys and the default output name are derived from the first binary
+    let module = binaries[0].1.to_string_lossy().to_string();
 
-    let issuer = extract_keypair(
+    let issuer = extract_signer(
         cmd.issuer,
-        Some(cmd.binary.clone()),
+        Some(module.clone()),
         cmd.directory.clone(),
         KeyPairType::Account,
         cmd.disable_keygen,
+        cmd.signer.clone(),
     )?;
-    let subject = extract_keypair(
+    let subject = extract_signer(
         cmd.subject,
-        Some(cmd.binary.clone()),
+        Some(module.clone()),
         cmd.directory,
         KeyPairType::Service,
         cmd.disable_keygen,
+        cmd.signer,
     )?;
 
-    par.add_library(&cmd.arch, &lib).map_err(convert_error)?;
+    for (target, _, lib) in binaries.iter() {
+        par.add_library(target, lib).map_err(convert_error)?;
+    }
+    let targets: Vec<&String> = binaries.iter().map(|(target, _, _)| target).collect();
 
     let extension = if cmd.compress { ".par.gz" } else { ".par" };
     let outfile = match cmd.destination {
         Some(path) => path,
         None => format!(
             "{}{}",
-            PathBuf::from(cmd.binary.clone())
+            PathBuf::from(module)
                 .file_stem()
                 .unwrap()
                 .to_str()
@@ -256,48 +478,153 @@ pub(crate) fn handle_create(cmd: CreateCommand) -> Result<String> {
         ),
     };
 
-    Ok(
-        if par
-            .write(&outfile, &issuer, &subject, cmd.compress)
-            .is_err()
-        {
-            format!(
-                "Error writing PAR. Please ensure directory {:?} exists",
-                PathBuf::from(outfile).parent().unwrap(),
+    let claims = Claims::<CapabilityProvider>::new(
+        cmd.name,
+        issuer.public_key(),
+        subject.public_key(),
+        cmd.capid,
+        cmd.vendor,
+        cmd.revision,
+        cmd.version,
+        HashMap::new(),
+    );
+
+    write_archive(&par, claims, &outfile, issuer.as_ref(), cmd.compress).map_err(|e| {
+        // Failing to write the file itself usually means the destination directory is missing
+        match e.downcast_ref::<std::io::Error>() {
+            Some(io_err) => format!(
+                "Error writing PAR: {}. Please ensure directory {:?} exists",
+                io_err,
+                PathBuf::from(&outfile)
+                    .parent()
+                    .unwrap_or_else(|| Path::new(".")),
             )
+            .into(),
+            None => e,
+        }
+    })?;
+
+    Ok(format_output(
+        format!("Successfully created archive {}", outfile),
+        json!({"result": "success", "file": outfile, "targets": targets}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Resolves the binaries given to `par create` into their targets, paths and contents, sorted by target
+fn collect_binaries(
+    inputs: &[String],
+    arch: Option<&str>,
+) -> Result<Vec<(String, PathBuf, Vec<u8>)>> {
+    let mut pending: Vec<(Option<String>, PathBuf)> = Vec::new();
+    for input in inputs {
+        let path = PathBuf::from(input);
+        if path.is_dir() {
+            pending.extend(
+                scan_target_dir(&path)?
+                    .into_iter()
+                    .map(|(target, path)| (Some(target), path)),
+            );
+        } else if path.exists() {
+            pending.push((None, path));
+        } else if let Some(idx) = input.find('=') {
+            pending.push((
+                Some(input[..idx].to_string()),
+                PathBuf::from(&input[idx + 1..]),
+            ));
         } else {
-            format_output(
-                format!("Successfully created archive {}", outfile),
-                json!({"result": "success", "file": outfile}),
-                &cmd.output.kind,
+            return Err(format!("Provider binary {} does not exist", input).into());
+        }
+    }
+
+    if arch.is_some() && (pending.len() != 1 || pending[0].0.is_some()) {
+        return Err("--arch can only be used with a single binary path".into());
+    }
+
+    let mut binaries: Vec<(String, PathBuf, Vec<u8>)> = Vec::new();
+    for (target, path) in pending {
+        let mut lib = Vec::new();
+        File::open(&path)
+            .map_err(|e| format!("Unable to read provider binary {}: {}", path.display(), e))?
+            .read_to_end(&mut lib)?;
+        let target = resolve_target(target.as_deref().or(arch), &lib, &path.to_string_lossy())?;
+        if binaries.iter().any(|(t, _, _)| *t == target) {
+            return Err(format!("Multiple binaries provided for target {}", target).into());
+        }
+        binaries.push((target, path, lib));
+    }
+    binaries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(binaries)
+}
+
+/// Finds the provider library for each target subdirectory of a build output directory. Libraries
+/// are looked for directly in the subdirectory and in its `release` directory, matching the layout
+/// of `cargo build --target`
+fn scan_target_dir(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut found = Vec::new();
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    entries.sort();
+    for subdir in entries {
+        let name = subdir.file_name().unwrap().to_string_lossy().to_string();
+        let target = match target_from_triple(&name) {
+            Some(target) => target,
+            None => continue,
+        };
+        let mut libs: Vec<PathBuf> = [subdir.clone(), subdir.join("release")]
+            .iter()
+            .filter_map(|d| std::fs::read_dir(d).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file()
+                    && matches!(
+                        path.extension().and_then(|e| e.to_str()),
+                        Some("so") | Some("dylib") | Some("dll")
+                    )
+            })
+            .collect();
+        libs.sort();
+        match libs.len() {
+            0 => {
+                return Err(format!(
+                    "No provider library found for {} in {}",
+                    target,
+                    subdir.display()
+                )
+                .into())
+            }
+            1 => found.push((target, libs.remove(0))),
+            _ => {
+                return Err(format!(
+                "Found multiple libraries for {} in {}, please provide it with --binary {}=<path>",
+                target,
+                subdir.display(),
+                target
             )
-        },
-    )
+                .into())
+            }
+        }
+    }
+    if found.is_empty() {
+        return Err(format!(
+            "No binaries found in {}, subdirectories must be named by target (e.g. x86_64-linux or x86_64-unknown-linux-gnu)",
+            dir.display()
+        )
+        .into());
+    }
+    Ok(found)
 }
 
 /// Loads a provider archive and outputs the contents of the claims
 pub(crate) async fn handle_inspect(cmd: InspectCommand) -> Result<String> {
-    let archive = match File::open(&cmd.archive) {
-        Ok(mut f) => {
-            let mut buf = Vec::new();
-            f.read_to_end(&mut buf)?;
-            ProviderArchive::try_load(&buf).map_err(|e| format!("{}", e))?
-        }
-        Err(_) => {
-            let artifact = crate::reg::pull_artifact(
-                cmd.archive,
-                cmd.digest,
-                cmd.allow_latest,
-                cmd.user,
-                cmd.password,
-                cmd.insecure,
-            )
-            .await?;
-            ProviderArchive::try_load(&artifact).map_err(|e| format!("{}", e))?
-        }
-    };
-    let claims = archive.claims().unwrap();
-    let metadata = claims.metadata.unwrap();
+    let (archive, claims) = load_archive(cmd.archive, cmd.digest, &cmd.opts).await?;
+    let name = claims.name();
+    let metadata = claims
+        .metadata
+        .ok_or("No metadata found in provider claims")?;
 
     let output = match cmd.output.kind {
         OutputKind::Json => {
@@ -309,7 +636,7 @@ pub(crate) async fn handle_inspect(cmd: InspectCommand) -> Result<String> {
             let friendly_ver = metadata.ver.unwrap_or_else(|| "None".to_string());
             format!(
                 "{}",
-                json!({"name": metadata.name.unwrap(),
+                json!({"name": name,
                     "issuer": claims.issuer,
                     "service": claims.subject,
                     "capability_contract_id": metadata.capid,
@@ -328,7 +655,7 @@ pub(crate) async fn handle_inspect(cmd: InspectCommand) -> Result<String> {
             crate::util::configure_table_style(&mut table, 2, max_width);
 
             table.add_row(Row::new(vec![TableCell::new_with_alignment(
-                format!("{} - Provider Archive", metadata.name.unwrap()),
+                format!("{} - Provider Archive", name),
                 2,
                 Alignment::Center,
             )]));
@@ -383,6 +710,226 @@ pub(crate) async fn handle_inspect(cmd: InspectCommand) -> Result<String> {
     Ok(output)
 }
 
+/// Loads a provider archive from a file, or pulls it from an OCI registry if no such file exists
+async fn load_archive(
+    archive: String,
+    digest: Option<String>,
+    opts: &PullOptions,
+) -> Result<(ProviderArchive, Claims<CapabilityProvider>)> {
+    let buf = load_archive_bytes(archive, digest, opts).await?;
+    let (par, token) = parse_archive(&buf)?;
+    Ok((par, token.claims))
+}
+
+/// Reads the raw bytes of a provider archive from a file, or pulls them from an OCI registry
+async fn load_archive_bytes(
+    archive: String,
+    digest: Option<String>,
+    opts: &PullOptions,
+) -> Result<Vec<u8>> {
+    match File::open(&archive) {
+        Ok(mut f) => {
+            let mut buf = Vec::new();
+            f.read_to_end(&mut buf)?;
+            Ok(buf)
+        }
+        Err(_) => crate::reg::pull_artifact(archive, digest, opts).await,
+    }
+}
+
+/// A single failed check of `par verify`
+#[derive(Debug, Clone, PartialEq)]
+struct VerifyFailure {
+    /// The check that failed, one of `signature`, `expiry`, `hashes` or `issuer`
+    check: &'static str,
+    message: String,
+}
+
+impl VerifyFailure {
+    fn new(check: &'static str, message: String) -> Self {
+        VerifyFailure { check, message }
+    }
+}
+
+/// Checks the claims of a provider archive against its contents, returning every failed check
+fn verify_archive(
+    jwt: &str,
+    libraries: &HashMap<String, Vec<u8>>,
+    trusted_issuers: &[String],
+) -> Result<(Claims<CapabilityProvider>, Vec<VerifyFailure>)> {
+    let claims = Claims::<CapabilityProvider>::decode(jwt)
+        .map_err(|e| format!("Invalid claims in provider archive: {}", e))?;
+    let validation = wascap::jwt::validate_token::<CapabilityProvider>(jwt)
+        .map_err(|e| format!("Invalid claims in provider archive: {}", e))?;
+    let mut failures = Vec::new();
+
+    if !validation.signature_valid {
+        failures.push(VerifyFailure::new(
+            "signature",
+            format!("Claims were not signed by their issuer {}", claims.issuer),
+        ));
+    }
+    if validation.expired {
+        failures.push(VerifyFailure::new(
+            "expiry",
+            format!("Claims expired {}", validation.expires_human),
+        ));
+    } else if validation.cannot_use_yet {
+        failures.push(VerifyFailure::new(
+            "expiry",
+            format!(
+                "Claims cannot be used until {}",
+                validation.not_before_human
+            ),
+        ));
+    }
+
+    let hashes = claims
+        .metadata
+        .as_ref()
+        .map(|md| md.target_hashes.clone())
+        .unwrap_or_default();
+    let mut targets: Vec<&String> = libraries.keys().chain(hashes.keys()).collect();
+    targets.sort();
+    targets.dedup();
+    for target in targets {
+        match (libraries.get(target), hashes.get(target)) {
+            (Some(lib), Some(hash)) if hash_bytes(lib) != *hash => {
+                failures.push(VerifyFailure::new(
+                    "hashes",
+                    format!("Binary for {} does not match its hash", target),
+                ))
+            }
+            (Some(_), None) => failures.push(VerifyFailure::new(
+                "hashes",
+                format!("No hash recorded for binary {}", target),
+            )),
+            (None, Some(_)) => failures.push(VerifyFailure::new(
+                "hashes",
+                format!("Missing binary for {}", target),
+            )),
+            _ => (),
+        }
+    }
+
+    if !trusted_issuers.is_empty() && !trusted_issuers.contains(&claims.issuer) {
+        failures.push(VerifyFailure::new(
+            "issuer",
+            format!("Issuer {} is not a trusted issuer", claims.issuer),
+        ));
+    }
+
+    Ok((claims, failures))
+}
+
+/// Verifies the signature, validity period, binary hashes and optionally the issuer of a
+/// provider archive, returning an error describing every failed check
+pub(crate) async fn handle_verify(cmd: VerifyCommand) -> Result<String> {
+    let buf = load_archive_bytes(cmd.archive.clone(), cmd.digest, &cmd.opts).await?;
+    let (jwt, libraries) = read_archive(&buf)?;
+    let (claims, failures) = verify_archive(&jwt, &libraries, &cmd.trusted_issuers)?;
+
+    if failures.is_empty() {
+        let mut targets: Vec<&String> = libraries.keys().collect();
+        targets.sort();
+        Ok(format_output(
+            format!(
+                "Verified provider archive {} issued by {} with targets {}",
+                cmd.archive,
+                claims.issuer,
+                targets
+                    .iter()
+                    .map(|t| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            json!({"valid": true,
+                "issuer": claims.issuer,
+                "service": claims.subject,
+                "targets": targets}),
+            &cmd.output.kind,
+        ))
+    } else {
+        Err(FailedOutput(format_output(
+            format!(
+                "Verification of provider archive {} failed:\n{}",
+                cmd.archive,
+                failures
+                    .iter()
+                    .map(|f| format!("  {}: {}", f.check, f.message))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            json!({"valid": false,
+                "failures": failures
+                    .iter()
+                    .map(|f| json!({"check": f.check, "message": f.message}))
+                    .collect::<Vec<_>>()}),
+            &cmd.output.kind,
+        ))
+        .into())
+    }
+}
+
+/// Extracts one or all provider binaries from a provider archive, verifying each binary
+/// against the hash in the archive's claims before it is written
+pub(crate) async fn handle_extract(cmd: ExtractCommand) -> Result<String> {
+    let archive = cmd.archive.clone();
+    let (par, claims) = load_archive(cmd.archive, cmd.digest, &cmd.opts).await?;
+    let hashes = claims
+        .metadata
+        .map(|md| md.target_hashes)
+        .ok_or("No metadata found in provider claims")?;
+
+    let mut available = par.targets();
+    available.sort();
+    let targets = match cmd.arch {
+        Some(arch) => vec![arch],
+        None => available.clone(),
+    };
+    let mut extracted = Vec::new();
+    for target in targets.iter() {
+        let lib = par.target_bytes(target).ok_or_else(|| {
+            format!(
+                "No binary for {} in {}, available targets are: {}",
+                target,
+                archive,
+                available.join(", ")
+            )
+        })?;
+        if hashes.get(target) != Some(&hash_bytes(&lib)) {
+            return Err(format!(
+                "Binary for {} does not match the hash in the archive claims",
+                target
+            )
+            .into());
+        }
+        let file_name = format!("{}.bin", target);
+        let destination = match (cmd.all, cmd.destination.as_ref()) {
+            (true, Some(dir)) => PathBuf::from(dir).join(file_name),
+            (false, Some(file)) => PathBuf::from(file),
+            (_, None) => PathBuf::from(file_name),
+        };
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        File::create(&destination)?.write_all(&lib)?;
+        extracted.push((target.to_string(), destination.display().to_string()));
+    }
+
+    let text = extracted
+        .iter()
+        .map(|(target, destination)| format!("Extracted {} to {}", target, destination))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let files: HashMap<_, _> = extracted.into_iter().collect();
+    Ok(format_output(
+        text,
+        json!({"result": "success", "files": files}),
+        &cmd.output.kind,
+    ))
+}
+
 /// Loads a provider archive and attempts to insert an additional provider into it
 pub(crate) fn handle_insert(cmd: InsertCommand) -> Result<String> {
     let mut buf = Vec::new();
@@ -391,29 +938,38 @@ pub(crate) fn handle_insert(cmd: InsertCommand) -> Result<String> {
 
     let mut par = ProviderArchive::try_load(&buf).map_err(convert_error)?;
 
-    let issuer = extract_keypair(
+    let issuer = extract_signer(
         cmd.issuer,
         Some(cmd.binary.clone()),
         cmd.directory.clone(),
         KeyPairType::Account,
         cmd.disable_keygen,
+        cmd.signer.clone(),
     )?;
-    let subject = extract_keypair(
+    let subject = extract_signer(
         cmd.subject,
         Some(cmd.binary.clone()),
         cmd.directory,
         KeyPairType::Service,
         cmd.disable_keygen,
+        cmd.signer,
     )?;
 
     let mut f = File::open(cmd.binary.clone())?;
     let mut lib = Vec::new();
     f.read_to_end(&mut lib)?;
+    let arch = resolve_target(cmd.arch.as_deref(), &lib, &cmd.binary)?;
 
-    par.add_library(&cmd.arch, &lib).map_err(convert_error)?;
+    par.add_library(&arch, &lib).map_err(convert_error)?;
 
-    par.write(&cmd.archive, &issuer, &subject, is_compressed(&buf)?)
-        .map_err(convert_error)?;
+    let claims = reissue_claims(&par, issuer.as_ref(), subject.as_ref())?;
+    write_archive(
+        &par,
+        claims,
+        &cmd.archive,
+        issuer.as_ref(),
+        is_compressed(&buf)?,
+    )?;
 
     Ok(format_output(
         format!(
@@ -425,19 +981,425 @@ pub(crate) fn handle_insert(cmd: InsertCommand) -> Result<String> {
     ))
 }
 
-/// Inspects the byte slice for a GZIP header, and returns true if the file is compressed
-fn is_compressed(input: &[u8]) -> Result<bool> {
-    if input.len() < 2 {
-        return Err("Not enough bytes to be a valid PAR file".into());
+/// Loads a provider archive and removes the provider for a target from it
+pub(crate) fn handle_remove(cmd: RemoveCommand) -> Result<String> {
+    let mut buf = Vec::new();
+    let mut f = File::open(cmd.archive.clone())?;
+    f.read_to_end(&mut buf)?;
+
+    let existing = ProviderArchive::try_load(&buf).map_err(convert_error)?;
+    let mut targets = existing.targets();
+    targets.sort();
+    if !targets.contains(&cmd.arch) {
+        return Err(format!(
+            "No binary for {} in {}, available targets are: {}",
+            cmd.arch,
+            cmd.archive,
+            targets.join(", ")
+        )
+        .into());
+    }
+    if targets.len() == 1 {
+        return Err(format!(
+            "{} is the only target in {}, a provider archive must contain at least one binary",
+            cmd.arch, cmd.archive
+        )
+        .into());
     }
-    Ok(input[0..2] == GZIP_MAGIC)
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    let (issuer, subject) = resign_signers(
+        &existing,
+        &cmd.archive,
+        cmd.issuer,
+        cmd.subject,
+        cmd.directory,
+        cmd.disable_keygen,
+        cmd.signer,
+        cmd.rotate_issuer,
+    )?;
 
-    // Uses all flags and options of the `par create` command
+    let claims = reissue_claims(&existing, issuer.as_ref(), subject.as_ref())?;
+    let metadata = claims
+        .metadata
+        .clone()
+        .ok_or("No claims found embedded in provider archive")?;
+    // Provider archives cannot drop a library, so the remaining libraries are copied into a new archive
+    let mut par = ProviderArchive::new(
+        &metadata.capid,
+        &metadata.name.unwrap_or_default(),
+        &metadata.vendor,
+        metadata.rev,
+        metadata.ver,
+    );
+    let removed = &cmd.arch;
+    for target in targets.iter().filter(|t| *t != removed) {
+        let lib = existing
+            .target_bytes(target)
+            .ok_or_else(|| format!("Missing binary for target {}", target))?;
+        par.add_library(target, &lib).map_err(convert_error)?;
+    }
+    write_archive(
+        &par,
+        claims,
+        &cmd.archive,
+        issuer.as_ref(),
+        is_compressed(&buf)?,
+    )?;
+
+    Ok(format_output(
+        format!(
+            "Successfully removed {} from archive {}",
+            cmd.arch, cmd.archive
+        ),
+        json!({"result": "success", "file": cmd.archive, "removed": cmd.arch}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Compares the claims of two provider archives, including the targets and hashes of their binaries
+pub(crate) async fn handle_diff(cmd: DiffCommand) -> Result<String> {
+    let mut claims = Vec::new();
+    for &(archive, digest) in [
+        (&cmd.left, &cmd.left_digest),
+        (&cmd.right, &cmd.right_digest),
+    ]
+    .iter()
+    {
+        let buf = load_archive_bytes(archive.to_string(), digest.clone(), &cmd.opts).await?;
+        let (jwt, _) = read_archive(&buf)?;
+        claims.push(
+            Claims::<CapabilityProvider>::decode(&jwt)
+                .map_err(|e| format!("Invalid claims in provider archive {}: {}", archive, e))?,
+        );
+    }
+    let right = claims.pop().unwrap();
+    let left = claims.pop().unwrap();
+
+    Ok(diff_provider_claims(&left, &right).render(&cmd.left, &cmd.right, &cmd.output.kind))
+}
+
+fn diff_provider_claims(
+    left: &Claims<CapabilityProvider>,
+    right: &Claims<CapabilityProvider>,
+) -> Diff {
+    let mut diff = Diff::new();
+    diff.field("name", Some(left.name()), Some(right.name()));
+    diff.field("account", Some(&left.issuer), Some(&right.issuer));
+    diff.field("service", Some(&left.subject), Some(&right.subject));
+    diff.field("expires", left.expires, right.expires);
+    diff.field("not_before", left.not_before, right.not_before);
+
+    let (lmd, rmd) = (left.metadata.as_ref(), right.metadata.as_ref());
+    diff.field("capid", lmd.map(|md| &md.capid), rmd.map(|md| &md.capid));
+    diff.field("vendor", lmd.map(|md| &md.vendor), rmd.map(|md| &md.vendor));
+    diff.field(
+        "revision",
+        lmd.and_then(|md| md.rev),
+        rmd.and_then(|md| md.rev),
+    );
+    diff.field(
+        "version",
+        lmd.and_then(|md| md.ver.as_ref()),
+        rmd.and_then(|md| md.ver.as_ref()),
+    );
+
+    let lhashes = lmd.map(|md| md.target_hashes.clone()).unwrap_or_default();
+    let rhashes = rmd.map(|md| md.target_hashes.clone()).unwrap_or_default();
+    let mut ltargets: Vec<String> = lhashes.keys().cloned().collect();
+    ltargets.sort();
+    let rtargets: Vec<String> = rhashes.keys().cloned().collect();
+    diff.list("targets", &ltargets, &rtargets);
+    // Targets only present on one side are already listed above, so only shared targets are compared
+    for target in ltargets.iter().filter(|t| rhashes.contains_key(*t)) {
+        diff.field(
+            &format!("{} hash", target),
+            lhashes.get(target),
+            rhashes.get(target),
+        );
+    }
+    diff
+}
+
+/// Changes the metadata of a provider archive, re-signing it without needing the original binaries
+pub(crate) fn handle_update(cmd: UpdateCommand) -> Result<String> {
+    if cmd.capid.is_none()
+        && cmd.name.is_none()
+        && cmd.vendor.is_none()
+        && cmd.revision.is_none()
+        && cmd.version.is_none()
+    {
+        return Err(
+            "No metadata to update, please provide --capid, --name, --vendor, --revision or --version"
+                .into(),
+        );
+    }
+
+    let mut buf = Vec::new();
+    let mut f = File::open(cmd.archive.clone())?;
+    f.read_to_end(&mut buf)?;
+    let par = ProviderArchive::try_load(&buf).map_err(convert_error)?;
+
+    let (issuer, subject) = resign_signers(
+        &par,
+        &cmd.archive,
+        cmd.issuer,
+        cmd.subject,
+        cmd.directory,
+        cmd.disable_keygen,
+        cmd.signer,
+        cmd.rotate_issuer,
+    )?;
+
+    let mut claims = reissue_claims(&par, issuer.as_ref(), subject.as_ref())?;
+    let metadata = claims
+        .metadata
+        .as_mut()
+        .ok_or("No claims found embedded in provider archive")?;
+    if let Some(capid) = cmd.capid {
+        metadata.capid = capid;
+    }
+    if let Some(name) = cmd.name {
+        metadata.name = Some(name);
+    }
+    if let Some(vendor) = cmd.vendor {
+        metadata.vendor = vendor;
+    }
+    if cmd.revision.is_some() {
+        metadata.rev = cmd.revision;
+    }
+    if cmd.version.is_some() {
+        metadata.ver = cmd.version;
+    }
+    let updated = json!({
+        "capid": metadata.capid,
+        "name": metadata.name,
+        "vendor": metadata.vendor,
+        "revision": metadata.rev,
+        "version": metadata.ver,
+    });
+
+    write_archive(
+        &par,
+        claims,
+        &cmd.archive,
+        issuer.as_ref(),
+        is_compressed(&buf)?,
+    )?;
+
+    Ok(format_output(
+        format!("Successfully updated archive {}", cmd.archive),
+        json!({"result": "success", "file": cmd.archive, "metadata": updated}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Locates the keys to re-sign an existing provider archive with. The archive keeps its subject
+/// unless another is provided, and is only re-signed by a different issuer with `rotate_issuer`
+#[allow(clippy::too_many_arguments)]
+fn resign_signers(
+    existing: &ProviderArchive,
+    archive: &str,
+    issuer: Option<String>,
+    subject: Option<String>,
+    directory: Option<String>,
+    disable_keygen: bool,
+    signer: Option<String>,
+    rotate_issuer: bool,
+) -> Result<(Box<dyn Signer>, Box<dyn Signer>)> {
+    let claims = existing
+        .claims()
+        .ok_or("No claims found embedded in provider archive")?;
+    let issuer = extract_signer(
+        issuer,
+        Some(archive.to_string()),
+        directory.clone(),
+        KeyPairType::Account,
+        disable_keygen,
+        signer.clone(),
+    )?;
+    if issuer.public_key() != claims.issuer && !rotate_issuer {
+        return Err(format!(
+            "Provider archive was signed by {}, but the provided issuer is {}. Use --rotate-issuer to re-sign the archive with a different account",
+            claims.issuer,
+            issuer.public_key()
+        )
+        .into());
+    }
+    // Only the public key of the subject is embedded in the claims, so its seed is not needed
+    let subject: Box<dyn Signer> = match subject {
+        Some(subject) => extract_signer(
+            Some(subject),
+            Some(archive.to_string()),
+            directory,
+            KeyPairType::Service,
+            disable_keygen,
+            signer,
+        )?,
+        None => Box::new(PublicKey(claims.subject)),
+    };
+    Ok((issuer, subject))
+}
+
+/// Builds the claims for an updated provider archive, keeping the metadata and validity period
+/// of its existing claims
+fn reissue_claims(
+    par: &ProviderArchive,
+    issuer: &dyn Signer,
+    subject: &dyn Signer,
+) -> Result<Claims<CapabilityProvider>> {
+    let claims = par
+        .claims()
+        .ok_or("No claims found embedded in provider archive")?;
+    let metadata = claims
+        .metadata
+        .ok_or("No claims found embedded in provider archive")?;
+    Ok(Claims::<CapabilityProvider>::with_dates(
+        metadata.name.unwrap_or_default(),
+        issuer.public_key(),
+        subject.public_key(),
+        metadata.capid,
+        metadata.vendor,
+        metadata.rev,
+        metadata.ver,
+        HashMap::new(),
+        claims.not_before,
+        claims.expires,
+    ))
+}
+
+/// Writes a provider archive in the same layout as `ProviderArchive::write`, with the claims
+/// completed with the hashes of each target and signed by the issuer
+pub(crate) fn write_archive(
+    par: &ProviderArchive,
+    mut claims: Claims<CapabilityProvider>,
+    destination: &str,
+    issuer: &dyn Signer,
+    compress: bool,
+) -> Result<()> {
+    let mut libraries = Vec::new();
+    for target in par.targets() {
+        let lib = par
+            .target_bytes(&target)
+            .ok_or_else(|| format!("Missing binary for target {}", target))?;
+        libraries.push((target, lib));
+    }
+    if let Some(ref mut metadata) = claims.metadata {
+        metadata.target_hashes = libraries
+            .iter()
+            .map(|(target, lib)| (target.to_string(), hash_bytes(lib)))
+            .collect();
+    }
+    let jwt = encode_claims(&claims, issuer)?;
+
+    let file = File::create(if compress && !destination.ends_with(".gz") {
+        format!("{}.gz", destination)
+    } else {
+        destination.to_string()
+    })?;
+    let mut archive = tar::Builder::new(if compress {
+        Box::new(GzEncoder::new(file, Compression::best())) as Box<dyn Write>
+    } else {
+        Box::new(file) as Box<dyn Write>
+    });
+
+    let mut header = tar::Header::new_gnu();
+    header.set_path(CLAIMS_JWT_FILE)?;
+    header.set_size(jwt.len() as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, CLAIMS_JWT_FILE, Cursor::new(jwt))?;
+
+    for (target, lib) in libraries {
+        let path = format!("{}.bin", target);
+        let mut header = tar::Header::new_gnu();
+        header.set_path(&path)?;
+        header.set_size(lib.len() as u64);
+        header.set_cksum();
+        archive.append_data(&mut header, path, Cursor::new(lib))?;
+    }
+    archive.into_inner()?;
+
+    Ok(())
+}
+
+/// Reads the raw signed JWT and the binaries, keyed by target, embedded in a provider archive
+/// without validating them
+fn read_archive(input: &[u8]) -> Result<(String, HashMap<String, Vec<u8>>)> {
+    let reader = if is_compressed(input)? {
+        Box::new(GzDecoder::new(input)) as Box<dyn Read>
+    } else {
+        Box::new(Cursor::new(input)) as Box<dyn Read>
+    };
+    let mut par = tar::Archive::new(reader);
+    let mut jwt = None;
+    let mut libraries = HashMap::new();
+
+    for entry in par.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if path.to_str() == Some(CLAIMS_JWT_FILE) {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            jwt = Some(contents);
+        } else if let Some(target) = path.file_stem().and_then(|s| s.to_str()) {
+            let mut lib = Vec::new();
+            entry.read_to_end(&mut lib)?;
+            libraries.insert(target.to_string(), lib);
+        }
+    }
+    let jwt = jwt.ok_or("No claims found embedded in provider archive")?;
+    Ok((jwt, libraries))
+}
+
+/// Loads a provider archive along with its claims, checking each binary against the hash in the
+/// claims. Unlike `ProviderArchive::try_load`, archives without claims metadata or with binaries
+/// missing from the claims are reported as errors rather than causing a panic
+pub(crate) fn parse_archive(input: &[u8]) -> Result<(ProviderArchive, Token<CapabilityProvider>)> {
+    let (jwt, libraries) = read_archive(input)?;
+    let claims = Claims::<CapabilityProvider>::decode(&jwt)
+        .map_err(|e| format!("Invalid claims in provider archive: {}", e))?;
+    let metadata = claims
+        .metadata
+        .as_ref()
+        .ok_or("No metadata found in provider claims")?;
+    if libraries.is_empty() {
+        return Err("No binaries found in provider archive".into());
+    }
+
+    let mut par = ProviderArchive::new(
+        &metadata.capid,
+        &claims.name(),
+        &metadata.vendor,
+        metadata.rev,
+        metadata.ver.clone(),
+    );
+    let mut targets: Vec<&String> = libraries.keys().collect();
+    targets.sort();
+    for target in targets {
+        let lib = &libraries[target];
+        match metadata.target_hashes.get(target) {
+            Some(hash) if *hash == hash_bytes(lib) => (),
+            Some(_) => return Err(format!("Binary for {} does not match its hash", target).into()),
+            None => return Err(format!("No hash found in the claims for binary {}", target).into()),
+        }
+        par.add_library(target, lib).map_err(convert_error)?;
+    }
+    Ok((par, Token { jwt, claims }))
+}
+
+/// Inspects the byte slice for a GZIP header, and returns true if the file is compressed
+pub(crate) fn is_compressed(input: &[u8]) -> Result<bool> {
+    if input.len() < 2 {
+        return Err("Not enough bytes to be a valid PAR file".into());
+    }
+    Ok(input[0..2] == GZIP_MAGIC)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nkeys::KeyPair;
+
+    // Uses all flags and options of the `par create` command
     // to ensure API does not change between versions
     #[test]
     fn test_par_create_comprehensive() {
@@ -472,6 +1434,8 @@ mod test {
             "text",
             "--disable-keygen",
             "--compress",
+            "--signer",
+            "./signer.sh",
         ])
         .unwrap();
         match create_long.command {
@@ -485,17 +1449,19 @@ mod test {
                 subject,
                 name,
                 arch,
-                binary,
+                binaries,
                 destination,
                 compress,
                 disable_keygen,
+                signer,
                 output,
             }) => {
                 assert_eq!(capid, "wasmcloud:test");
-                assert_eq!(arch, "x86_64-testrunner");
-                assert_eq!(binary, "./testrunner.so");
+                assert_eq!(arch.unwrap(), "x86_64-testrunner");
+                assert_eq!(binaries, vec!["./testrunner.so"]);
                 assert_eq!(directory.unwrap(), "./tests/fixtures");
                 assert_eq!(issuer.unwrap(), ISSUER);
+                assert_eq!(signer.unwrap(), "./signer.sh");
                 assert_eq!(subject.unwrap(), SUBJECT);
                 assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
                 assert_eq!(name, "CreateTest");
@@ -548,17 +1514,19 @@ mod test {
                 subject,
                 name,
                 arch,
-                binary,
+                binaries,
                 destination,
                 compress,
                 disable_keygen,
+                signer,
                 output,
             }) => {
                 assert_eq!(capid, "wasmcloud:test");
-                assert_eq!(arch, "x86_64-testrunner");
-                assert_eq!(binary, "./testrunner.so");
+                assert_eq!(arch.unwrap(), "x86_64-testrunner");
+                assert_eq!(binaries, vec!["./testrunner.so"]);
                 assert_eq!(directory.unwrap(), "./tests/fixtures");
                 assert_eq!(issuer.unwrap(), ISSUER);
+                assert!(signer.is_none());
                 assert_eq!(subject.unwrap(), SUBJECT);
                 assert_eq!(output.kind, OutputKind::Json);
                 assert_eq!(name, "CreateTest");
@@ -571,6 +1539,38 @@ mod test {
             }
             cmd => panic!("par insert constructed incorrect command {:?}", cmd),
         }
+
+        let create_multi = ParCli::from_iter_safe(&[
+            "par",
+            "create",
+            "-b",
+            "x86_64-linux=./x86_64-linux/libtest.so",
+            "-b",
+            "aarch64-macos=./aarch64-macos/libtest.dylib",
+            "--binary",
+            "./target",
+            "-c",
+            "wasmcloud:test",
+            "-n",
+            "CreateTest",
+            "-v",
+            "TestRunner",
+        ])
+        .unwrap();
+        match create_multi.command {
+            ParCliCommand::Create(CreateCommand { arch, binaries, .. }) => {
+                assert!(arch.is_none());
+                assert_eq!(
+                    binaries,
+                    vec![
+                        "x86_64-linux=./x86_64-linux/libtest.so",
+                        "aarch64-macos=./aarch64-macos/libtest.dylib",
+                        "./target"
+                    ]
+                );
+            }
+            cmd => panic!("par create constructed incorrect command {:?}", cmd),
+        }
     }
 
     // Uses all flags and options of the `par insert` command
@@ -608,12 +1608,14 @@ mod test {
                 subject,
                 output,
                 disable_keygen,
+                signer,
             }) => {
                 assert_eq!(archive, "libtest.par.gz");
-                assert_eq!(arch, "x86_64-testrunner");
+                assert_eq!(arch.unwrap(), "x86_64-testrunner");
                 assert_eq!(binary, "./testrunner.so");
                 assert_eq!(directory.unwrap(), "./tests/fixtures");
                 assert_eq!(issuer.unwrap(), ISSUER);
+                assert!(signer.is_none());
                 assert_eq!(subject.unwrap(), SUBJECT);
                 assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
                 assert!(disable_keygen);
@@ -636,6 +1638,8 @@ mod test {
             SUBJECT,
             "--output",
             "text",
+            "--signer",
+            "./signer.sh",
         ])
         .unwrap();
         match insert_long.command {
@@ -648,12 +1652,14 @@ mod test {
                 subject,
                 output,
                 disable_keygen,
+                signer,
             }) => {
                 assert_eq!(archive, "libtest.par.gz");
-                assert_eq!(arch, "x86_64-testrunner");
+                assert_eq!(arch.unwrap(), "x86_64-testrunner");
                 assert_eq!(binary, "./testrunner.so");
                 assert_eq!(directory.unwrap(), "./tests/fixtures");
                 assert_eq!(issuer.unwrap(), ISSUER);
+                assert_eq!(signer.unwrap(), "./signer.sh");
                 assert_eq!(subject.unwrap(), SUBJECT);
                 assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
                 assert!(!disable_keygen);
@@ -662,6 +1668,457 @@ mod test {
         }
     }
 
+    // Uses all flags and options of the `par remove` command
+    // to ensure API does not change between versions
+    #[test]
+    fn test_par_remove_comprehensive() {
+        const ISSUER: &str = "SAAJLQZDZO57THPTIIEELEY7FJYOJZQWQD7FF4J67TUYTSCOXTF7R4Y3VY";
+        const SUBJECT: &str = "SVAH7IN6QE6XODCGIIWZQDZ5LNSSS4FNEO6SNHZSSASW4BBBKSZ6KWTKWY";
+
+        let remove_long = ParCli::from_iter_safe(&[
+            "par",
+            "remove",
+            "libtest.par.gz",
+            "--arch",
+            "aarch64-linux",
+            "--directory",
+            "./tests/fixtures",
+            "--issuer",
+            ISSUER,
+            "--rotate-issuer",
+            "--subject",
+            SUBJECT,
+            "--disable-keygen",
+            "--signer",
+            "./signer.sh",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match remove_long.command {
+            ParCliCommand::Remove(RemoveCommand {
+                archive,
+                arch,
+                directory,
+                issuer,
+                rotate_issuer,
+                subject,
+                disable_keygen,
+                signer,
+                output,
+            }) => {
+                assert_eq!(archive, "libtest.par.gz");
+                assert_eq!(arch, "aarch64-linux");
+                assert_eq!(directory.unwrap(), "./tests/fixtures");
+                assert_eq!(issuer.unwrap(), ISSUER);
+                assert!(rotate_issuer);
+                assert_eq!(subject.unwrap(), SUBJECT);
+                assert!(disable_keygen);
+                assert_eq!(signer.unwrap(), "./signer.sh");
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("par remove constructed incorrect command {:?}", cmd),
+        }
+
+        let remove_short = ParCli::from_iter_safe(&[
+            "par",
+            "remove",
+            "libtest.par",
+            "-a",
+            "aarch64-linux",
+            "-d",
+            "./tests/fixtures",
+            "-i",
+            ISSUER,
+            "-s",
+            SUBJECT,
+        ])
+        .unwrap();
+        match remove_short.command {
+            ParCliCommand::Remove(RemoveCommand {
+                archive,
+                arch,
+                rotate_issuer,
+                output,
+                ..
+            }) => {
+                assert_eq!(archive, "libtest.par");
+                assert_eq!(arch, "aarch64-linux");
+                assert!(!rotate_issuer);
+                assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
+            }
+            cmd => panic!("par remove constructed incorrect command {:?}", cmd),
+        }
+    }
+
+    // Uses all flags and options of the `par verify` command
+    // to ensure API does not change between versions
+    #[test]
+    fn test_par_verify_comprehensive() {
+        const ACCOUNT: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+        const OTHER: &str = "ACOJJN6WUP4ODD75XEBKKTCCUJJCY5ZKQ56XVKYK4BEJWGVAOOQHZMCW";
+        let verify_all = ParCli::from_iter_safe(&[
+            "par",
+            "verify",
+            "wasmcloud.azurecr.io/provider:v1",
+            "--trusted-issuer",
+            ACCOUNT,
+            "--trusted-issuer",
+            OTHER,
+            "--digest",
+            "sha256:blah",
+            "--allow-latest",
+            "--user",
+            "name",
+            "--password",
+            "secret",
+            "--insecure",
+            "--no-cache",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match verify_all.command {
+            ParCliCommand::Verify(VerifyCommand {
+                archive,
+                trusted_issuers,
+                digest,
+                opts,
+                output,
+            }) => {
+                assert_eq!(archive, "wasmcloud.azurecr.io/provider:v1");
+                assert_eq!(trusted_issuers, vec![ACCOUNT, OTHER]);
+                assert_eq!(digest.unwrap(), "sha256:blah");
+                assert!(opts.allow_latest);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "secret");
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("par verify constructed incorrect command {:?}", cmd),
+        }
+
+        let verify_short =
+            ParCli::from_iter_safe(&["par", "verify", "libtest.par", "-d", "sha256:blah"]).unwrap();
+        match verify_short.command {
+            ParCliCommand::Verify(VerifyCommand {
+                archive,
+                trusted_issuers,
+                digest,
+                ..
+            }) => {
+                assert_eq!(archive, "libtest.par");
+                assert!(trusted_issuers.is_empty());
+                assert_eq!(digest.unwrap(), "sha256:blah");
+            }
+            cmd => panic!("par verify constructed incorrect command {:?}", cmd),
+        }
+    }
+
+    // Uses all flags and options of the `par update` command
+    // to ensure API does not change between versions
+    #[test]
+    fn test_par_update_comprehensive() {
+        const ISSUER: &str = "SAAJLQZDZO57THPTIIEELEY7FJYOJZQWQD7FF4J67TUYTSCOXTF7R4Y3VY";
+        const SUBJECT: &str = "SVAH7IN6QE6XODCGIIWZQDZ5LNSSS4FNEO6SNHZSSASW4BBBKSZ6KWTKWY";
+        let update_long = ParCli::from_iter_safe(&[
+            "par",
+            "update",
+            "libtest.par.gz",
+            "--capid",
+            "wasmcloud:test",
+            "--name",
+            "UpdateTest",
+            "--vendor",
+            "TestRunner",
+            "--revision",
+            "2",
+            "--version",
+            "1.2.0",
+            "--directory",
+            "./tests/fixtures",
+            "--issuer",
+            ISSUER,
+            "--rotate-issuer",
+            "--subject",
+            SUBJECT,
+            "--disable-keygen",
+            "--signer",
+            "./signer.sh",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match update_long.command {
+            ParCliCommand::Update(UpdateCommand {
+                archive,
+                capid,
+                name,
+                vendor,
+                revision,
+                version,
+                directory,
+                issuer,
+                rotate_issuer,
+                subject,
+                disable_keygen,
+                signer,
+                output,
+            }) => {
+                assert_eq!(archive, "libtest.par.gz");
+                assert_eq!(capid.unwrap(), "wasmcloud:test");
+                assert_eq!(name.unwrap(), "UpdateTest");
+                assert_eq!(vendor.unwrap(), "TestRunner");
+                assert_eq!(revision.unwrap(), 2);
+                assert_eq!(version.unwrap(), "1.2.0");
+                assert_eq!(directory.unwrap(), "./tests/fixtures");
+                assert_eq!(issuer.unwrap(), ISSUER);
+                assert!(rotate_issuer);
+                assert_eq!(subject.unwrap(), SUBJECT);
+                assert!(disable_keygen);
+                assert_eq!(signer.unwrap(), "./signer.sh");
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("par update constructed incorrect command {:?}", cmd),
+        }
+
+        let update_short = ParCli::from_iter_safe(&[
+            "par",
+            "update",
+            "libtest.par",
+            "-c",
+            "wasmcloud:test",
+            "-n",
+            "UpdateTest",
+            "-v",
+            "TestRunner",
+            "-r",
+            "3",
+            "-d",
+            "./tests/fixtures",
+            "-i",
+            ISSUER,
+            "-s",
+            SUBJECT,
+        ])
+        .unwrap();
+        match update_short.command {
+            ParCliCommand::Update(UpdateCommand {
+                archive,
+                capid,
+                name,
+                vendor,
+                revision,
+                version,
+                ..
+            }) => {
+                assert_eq!(archive, "libtest.par");
+                assert_eq!(capid.unwrap(), "wasmcloud:test");
+                assert_eq!(name.unwrap(), "UpdateTest");
+                assert_eq!(vendor.unwrap(), "TestRunner");
+                assert_eq!(revision.unwrap(), 3);
+                assert!(version.is_none());
+            }
+            cmd => panic!("par update constructed incorrect command {:?}", cmd),
+        }
+    }
+
+    // Uses all flags and options of the `par diff` command
+    // to ensure API does not change between versions
+    #[test]
+    fn test_par_diff_comprehensive() {
+        let diff_all = ParCli::from_iter_safe(&[
+            "par",
+            "diff",
+            "libtest.par",
+            "wasmcloud.azurecr.io/provider:v2",
+            "--allow-latest",
+            "--user",
+            "name",
+            "--password",
+            "secret",
+            "--insecure",
+            "--no-cache",
+            "--left-digest",
+            "sha256:left",
+            "--right-digest",
+            "sha256:right",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match diff_all.command {
+            ParCliCommand::Diff(DiffCommand {
+                left,
+                right,
+                left_digest,
+                right_digest,
+                opts,
+                output,
+            }) => {
+                assert_eq!(left, "libtest.par");
+                assert_eq!(right, "wasmcloud.azurecr.io/provider:v2");
+                assert_eq!(left_digest.unwrap(), "sha256:left");
+                assert_eq!(right_digest.unwrap(), "sha256:right");
+                assert!(opts.allow_latest);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "secret");
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("par diff constructed incorrect command {:?}", cmd),
+        }
+    }
+
+    #[test]
+    fn test_verify_archive() {
+        let account = KeyPair::new_account();
+        let service = KeyPair::new_service();
+        let mut libraries = HashMap::new();
+        libraries.insert("x86_64-linux".to_string(), b"linux".to_vec());
+        libraries.insert("aarch64-linux".to_string(), b"arm".to_vec());
+        let mut claims = Claims::<CapabilityProvider>::new(
+            "Verify".to_string(),
+            account.public_key(),
+            service.public_key(),
+            "wasmcloud:test".to_string(),
+            "Test".to_string(),
+            None,
+            None,
+            libraries
+                .iter()
+                .map(|(target, lib)| (target.to_string(), hash_bytes(lib)))
+                .collect(),
+        );
+        let jwt = claims.encode(&account).unwrap();
+
+        let (_, failures) = verify_archive(&jwt, &libraries, &[]).unwrap();
+        assert!(failures.is_empty());
+        let (_, failures) = verify_archive(&jwt, &libraries, &[account.public_key()]).unwrap();
+        assert!(failures.is_empty());
+
+        let (_, failures) = verify_archive(&jwt, &libraries, &[service.public_key()]).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].check, "issuer");
+
+        let mut tampered = libraries.clone();
+        tampered.insert("x86_64-linux".to_string(), b"tampered".to_vec());
+        tampered.remove("aarch64-linux");
+        tampered.insert("x86_64-macos".to_string(), b"macos".to_vec());
+        let (_, failures) = verify_archive(&jwt, &tampered, &[]).unwrap();
+        assert_eq!(
+            failures,
+            vec![
+                VerifyFailure::new("hashes", "Missing binary for aarch64-linux".to_string()),
+                VerifyFailure::new(
+                    "hashes",
+                    "Binary for x86_64-linux does not match its hash".to_string()
+                ),
+                VerifyFailure::new(
+                    "hashes",
+                    "No hash recorded for binary x86_64-macos".to_string()
+                ),
+            ]
+        );
+
+        claims.expires = Some(1);
+        let expired = claims.encode(&account).unwrap();
+        let (_, failures) = verify_archive(&expired, &libraries, &[]).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].check, "expiry");
+
+        let forged = claims.encode(&KeyPair::new_account()).unwrap();
+        let (_, failures) = verify_archive(&forged, &libraries, &[]).unwrap();
+        assert_eq!(failures[0].check, "signature");
+    }
+
+    #[test]
+    fn test_reissue_claims_keeps_dates() {
+        let account = KeyPair::new_account();
+        let service = KeyPair::new_service();
+        let mut par = ProviderArchive::new("wasmcloud:test", "Reissue", "Test", Some(1), None);
+        par.add_library("x86_64-linux", b"linux").unwrap();
+        let claims = Claims::<CapabilityProvider>::with_dates(
+            "Reissue".to_string(),
+            account.public_key(),
+            service.public_key(),
+            "wasmcloud:test".to_string(),
+            "Test".to_string(),
+            Some(1),
+            None,
+            HashMap::new(),
+            Some(1_600_000_000),
+            Some(4_000_000_000),
+        );
+        let archive = std::env::temp_dir().join("wash_reissue_claims.par");
+        write_archive(&par, claims, archive.to_str().unwrap(), &account, false).unwrap();
+        let par = ProviderArchive::try_load(&std::fs::read(&archive).unwrap()).unwrap();
+        std::fs::remove_file(archive).unwrap();
+
+        let reissued = reissue_claims(&par, &account, &PublicKey(service.public_key())).unwrap();
+        assert_eq!(reissued.not_before, Some(1_600_000_000));
+        assert_eq!(reissued.expires, Some(4_000_000_000));
+        assert_eq!(reissued.subject, service.public_key());
+        assert_eq!(reissued.metadata.unwrap().rev, Some(1));
+    }
+
+    #[test]
+    fn test_parse_archive_errors() {
+        let account = KeyPair::new_account();
+        let service = KeyPair::new_service();
+        let claims = Claims::<CapabilityProvider>::new(
+            "Parse".to_string(),
+            account.public_key(),
+            service.public_key(),
+            "wasmcloud:test".to_string(),
+            "Test".to_string(),
+            None,
+            None,
+            HashMap::new(),
+        );
+        // Archives are written by hand, as write_archive records the hash of every binary
+        let archive = |claims: &Claims<CapabilityProvider>| {
+            let jwt = claims.encode(&account).unwrap();
+            let mut builder = tar::Builder::new(Vec::new());
+            for (path, contents) in &[
+                (CLAIMS_JWT_FILE, jwt.as_bytes()),
+                ("x86_64-linux.bin", b"linux"),
+            ] {
+                let mut header = tar::Header::new_gnu();
+                header.set_path(path).unwrap();
+                header.set_size(contents.len() as u64);
+                header.set_cksum();
+                builder.append(&header, *contents).unwrap();
+            }
+            builder.into_inner().unwrap()
+        };
+
+        let unhashed = parse_archive(&archive(&claims)).err().unwrap();
+        assert_eq!(
+            unhashed.to_string(),
+            "No hash found in the claims for binary x86_64-linux"
+        );
+
+        let mut no_metadata = claims.clone();
+        no_metadata.metadata = None;
+        let no_metadata = parse_archive(&archive(&no_metadata)).err().unwrap();
+        assert_eq!(
+            no_metadata.to_string(),
+            "No metadata found in provider claims"
+        );
+
+        let mut hashed = claims;
+        if let Some(md) = hashed.metadata.as_mut() {
+            md.target_hashes
+                .insert("x86_64-linux".to_string(), hash_bytes(b"linux"));
+        }
+        let (par, token) = parse_archive(&archive(&hashed)).unwrap();
+        assert_eq!(par.target_bytes("x86_64-linux").unwrap(), b"linux");
+        assert_eq!(token.claims.subject, service.public_key());
+    }
+
     // Uses all flags and options of the `par inspect` command
     // to ensure API does not change between versions
     #[test]
@@ -687,18 +2144,16 @@ mod test {
             ParCliCommand::Inspect(InspectCommand {
                 archive,
                 digest,
-                allow_latest,
-                user,
-                password,
-                insecure,
+                opts,
                 output,
             }) => {
                 assert_eq!(archive, LOCAL);
                 assert_eq!(digest.unwrap(), "sha256:blah");
-                assert!(!allow_latest);
-                assert!(!insecure);
-                assert_eq!(user.unwrap(), "name");
-                assert_eq!(password.unwrap(), "secret");
+                assert!(!opts.allow_latest);
+                assert!(!opts.auth.insecure);
+                assert!(!opts.no_cache);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "secret");
                 assert_eq!(output.kind, OutputKind::Json);
             }
             cmd => panic!("par inspect constructed incorrect command {:?}", cmd),
@@ -717,27 +2172,113 @@ mod test {
             "name",
             "--allow-latest",
             "--insecure",
+            "--no-cache",
         ])
         .unwrap();
         match inspect_short.command {
             ParCliCommand::Inspect(InspectCommand {
                 archive,
                 digest,
-                allow_latest,
-                user,
-                password,
-                insecure,
+                opts,
                 output,
             }) => {
                 assert_eq!(archive, REMOTE);
                 assert_eq!(digest.unwrap(), "sha256:blah");
-                assert!(allow_latest);
-                assert!(insecure);
-                assert_eq!(user.unwrap(), "name");
-                assert_eq!(password.unwrap(), "secret");
+                assert!(opts.allow_latest);
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "secret");
                 assert_eq!(output.kind, OutputKind::Json);
             }
             cmd => panic!("par inspect constructed incorrect command {:?}", cmd),
         }
     }
+
+    // Uses all flags and options of the `par extract` command
+    // to ensure API does not change between versions
+    #[test]
+    fn test_par_extract_comprehensive() {
+        const LOCAL: &str = "./coolthing.par.gz";
+        const REMOTE: &str = "wasmcloud.azurecr.io/coolthing.par.gz";
+
+        let extract_arch = ParCli::from_iter_safe(&[
+            "par",
+            "extract",
+            LOCAL,
+            "--arch",
+            "x86_64-linux",
+            "--destination",
+            "./libcoolthing.so",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match extract_arch.command {
+            ParCliCommand::Extract(ExtractCommand {
+                archive,
+                arch,
+                all,
+                destination,
+                output,
+                ..
+            }) => {
+                assert_eq!(archive, LOCAL);
+                assert_eq!(arch.unwrap(), "x86_64-linux");
+                assert!(!all);
+                assert_eq!(destination.unwrap(), "./libcoolthing.so");
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            cmd => panic!("par extract constructed incorrect command {:?}", cmd),
+        }
+
+        let extract_all = ParCli::from_iter_safe(&[
+            "par",
+            "extract",
+            REMOTE,
+            "--all",
+            "--destination",
+            "./bin",
+            "-d",
+            "sha256:blah",
+            "-u",
+            "name",
+            "-p",
+            "secret",
+            "--allow-latest",
+            "--insecure",
+            "--no-cache",
+        ])
+        .unwrap();
+        match extract_all.command {
+            ParCliCommand::Extract(ExtractCommand {
+                archive,
+                arch,
+                all,
+                destination,
+                digest,
+                opts,
+                output,
+            }) => {
+                assert_eq!(archive, REMOTE);
+                assert!(arch.is_none());
+                assert!(all);
+                assert_eq!(destination.unwrap(), "./bin");
+                assert_eq!(digest.unwrap(), "sha256:blah");
+                assert!(opts.allow_latest);
+                assert_eq!(opts.auth.user.unwrap(), "name");
+                assert_eq!(opts.auth.password.unwrap(), "secret");
+                assert!(opts.auth.insecure);
+                assert!(opts.no_cache);
+                assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
+            }
+            cmd => panic!("par extract constructed incorrect command {:?}", cmd),
+        }
+
+        assert!(ParCli::from_iter_safe(&["par", "extract", LOCAL]).is_err());
+        assert!(
+            ParCli::from_iter_safe(&["par", "extract", LOCAL, "-a", "x86_64-linux", "--all"])
+                .is_err()
+        );
+    }
 }
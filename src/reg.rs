@@ -1,16 +1,27 @@
 extern crate oci_distribution;
+use crate::credentials::{
+    normalize_registry, resolve_auth, wash_credentials_path, CredentialsFile,
+};
+use crate::ocicache::OciCache;
 use crate::util::{format_output, output_destination, Output, OutputDestination, OutputKind};
+use data_encoding::HEXLOWER;
+use hyperx::header::Header;
 use log::{debug, info};
 use oci_distribution::client::*;
+use oci_distribution::manifest::{OciDescriptor, OciManifest};
 use oci_distribution::secrets::RegistryAuth;
 use oci_distribution::Reference;
 use provider_archive::ProviderArchive;
+use serde::Deserialize;
 use serde_json::json;
 use spinners::{Spinner, Spinners};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::prelude::*;
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
+use termion::input::TermRead;
+use www_authenticate::{RawChallenge, WwwAuthenticate};
 
 const PROVIDER_ARCHIVE_MEDIA_TYPE: &str = "application/vnd.wasmcloud.provider.archive.layer.v1+par";
 const PROVIDER_ARCHIVE_CONFIG_MEDIA_TYPE: &str =
@@ -19,8 +30,19 @@ const PROVIDER_ARCHIVE_FILE_EXTENSION: &str = ".par.gz";
 const WASM_MEDIA_TYPE: &str = "application/vnd.module.wasm.content.layer.v1+wasm";
 const WASM_CONFIG_MEDIA_TYPE: &str = "application/vnd.wasmcloud.actor.archive.config";
 const OCI_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar";
+/// Manifest types accepted by the OCI client, sent when fetching manifests directly
+const MANIFEST_MEDIA_TYPES: &str = "application/vnd.docker.distribution.manifest.v2+json,application/vnd.docker.distribution.manifest.list.v2+json,application/vnd.oci.image.manifest.v1+json";
 const WASM_FILE_EXTENSION: &str = ".wasm";
 
+const ANNOTATION_TITLE: &str = "org.opencontainers.image.title";
+const ANNOTATION_VERSION: &str = "org.opencontainers.image.version";
+const ANNOTATION_VENDOR: &str = "org.opencontainers.image.vendor";
+const ANNOTATION_REVISION: &str = "dev.wasmcloud.revision";
+const ANNOTATION_ISSUER: &str = "dev.wasmcloud.issuer";
+const ANNOTATION_SUBJECT: &str = "dev.wasmcloud.subject";
+const ANNOTATION_CAPABILITIES: &str = "dev.wasmcloud.capabilities";
+const ANNOTATION_CAPID: &str = "dev.wasmcloud.capability_contract_id";
+
 pub(crate) const SHOWER_EMOJI: &str = "\u{1F6BF}";
 
 pub(crate) enum SupportedArtifacts {
@@ -51,6 +73,15 @@ pub(crate) enum RegCliCommand {
     /// Push an artifact to an OCI compliant registry
     #[structopt(name = "push")]
     Push(PushCommand),
+    /// Inspect the manifest of an artifact in an OCI compliant registry without downloading it
+    #[structopt(name = "inspect")]
+    Inspect(InspectCommand),
+    /// Log in to an OCI compliant registry, storing the credentials for later commands
+    #[structopt(name = "login")]
+    Login(LoginCommand),
+    /// Remove the stored credentials of an OCI compliant registry
+    #[structopt(name = "logout")]
+    Logout(LogoutCommand),
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -67,15 +98,11 @@ pub(crate) struct PullCommand {
     #[structopt(short = "d", long = "digest")]
     pub(crate) digest: Option<String>,
 
-    /// Allow latest artifact tags
-    #[structopt(long = "allow-latest")]
-    pub(crate) allow_latest: bool,
-
     #[structopt(flatten)]
     pub(crate) output: Output,
 
     #[structopt(flatten)]
-    pub(crate) opts: AuthOpts,
+    pub(crate) opts: PullOptions,
 }
 
 #[derive(StructOpt, Debug, Clone)]
@@ -96,6 +123,10 @@ pub(crate) struct PushCommand {
     #[structopt(long = "allow-latest")]
     pub(crate) allow_latest: bool,
 
+    /// Annotation to add to the manifest as key=value, overriding those derived from the artifact's claims
+    #[structopt(short = "a", long = "annotation", number_of_values = 1)]
+    pub(crate) annotations: Vec<String>,
+
     #[structopt(flatten)]
     pub(crate) output: Output,
 
@@ -104,8 +135,67 @@ pub(crate) struct PushCommand {
 }
 
 #[derive(StructOpt, Debug, Clone)]
+pub(crate) struct InspectCommand {
+    /// URL of artifact
+    #[structopt(name = "url")]
+    pub(crate) url: String,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+
+    #[structopt(flatten)]
+    pub(crate) opts: AuthOpts,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct LoginCommand {
+    /// Registry host to log in to (e.g. wasmcloud.azurecr.io or localhost:5000)
+    #[structopt(name = "registry")]
+    pub(crate) registry: String,
+
+    /// OCI username
+    #[structopt(
+        short = "u",
+        long = "user",
+        env = "WASH_REG_USER",
+        hide_env_values = true
+    )]
+    pub(crate) user: Option<String>,
+
+    /// OCI password, if omitted it will be prompted for
+    #[structopt(
+        short = "p",
+        long = "password",
+        env = "WASH_REG_PASSWORD",
+        hide_env_values = true
+    )]
+    pub(crate) password: Option<String>,
+
+    /// Read the password from stdin
+    #[structopt(long = "password-stdin", conflicts_with = "password")]
+    pub(crate) password_stdin: bool,
+
+    /// Allow insecure (HTTP) registry connections
+    #[structopt(long = "insecure")]
+    pub(crate) insecure: bool,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub(crate) struct LogoutCommand {
+    /// Registry host to remove the stored credentials of
+    #[structopt(name = "registry")]
+    pub(crate) registry: String,
+
+    #[structopt(flatten)]
+    pub(crate) output: Output,
+}
+
+#[derive(StructOpt, Debug, Clone, Default)]
 pub(crate) struct AuthOpts {
-    /// OCI username, if omitted anonymous authentication will be used
+    /// OCI username, if omitted credentials are read from the wash credentials file or docker config, falling back to anonymous authentication
     #[structopt(
         short = "u",
         long = "user",
@@ -114,7 +204,7 @@ pub(crate) struct AuthOpts {
     )]
     pub(crate) user: Option<String>,
 
-    /// OCI password, if omitted anonymous authentication will be used
+    /// OCI password, if omitted credentials are read from the wash credentials file or docker config, falling back to anonymous authentication
     #[structopt(
         short = "p",
         long = "password",
@@ -128,12 +218,30 @@ pub(crate) struct AuthOpts {
     pub(crate) insecure: bool,
 }
 
+/// Options shared by every command that pulls artifacts from an OCI registry
+#[derive(StructOpt, Debug, Clone, Default)]
+pub(crate) struct PullOptions {
+    /// Allow latest artifact tags
+    #[structopt(long = "allow-latest")]
+    pub(crate) allow_latest: bool,
+
+    /// Bypass the local OCI cache, always pulling the artifact from the registry
+    #[structopt(long = "no-cache")]
+    pub(crate) no_cache: bool,
+
+    #[structopt(flatten)]
+    pub(crate) auth: AuthOpts,
+}
+
 pub(crate) async fn handle_command(
     command: RegCliCommand,
 ) -> Result<String, Box<dyn ::std::error::Error>> {
     match command {
         RegCliCommand::Pull(cmd) => handle_pull(cmd).await,
         RegCliCommand::Push(cmd) => handle_push(cmd).await,
+        RegCliCommand::Inspect(cmd) => handle_inspect(cmd).await,
+        RegCliCommand::Login(cmd) => handle_login(cmd).await,
+        RegCliCommand::Logout(cmd) => handle_logout(cmd),
     }
 }
 
@@ -149,15 +257,7 @@ pub(crate) async fn handle_pull(cmd: PullCommand) -> Result<String, Box<dyn ::st
         _ => None,
     };
     info!("Downloading {}", image.whole());
-    let artifact = pull_artifact(
-        cmd.url,
-        cmd.digest,
-        cmd.allow_latest,
-        cmd.opts.user,
-        cmd.opts.password,
-        cmd.opts.insecure,
-    )
-    .await?;
+    let artifact = pull_artifact(cmd.url, cmd.digest, &cmd.opts).await?;
 
     let outfile = write_artifact(&artifact, &image, cmd.destination)?;
 
@@ -175,36 +275,67 @@ pub(crate) async fn handle_pull(cmd: PullCommand) -> Result<String, Box<dyn ::st
     ))
 }
 
+/// Pulls an artifact, reusing a previously pulled artifact with the same digest unless `no_cache`
+/// is set. Tags are resolved to digests through the cache until their resolution expires
 pub(crate) async fn pull_artifact(
     url: String,
     digest: Option<String>,
-    allow_latest: bool,
-    user: Option<String>,
-    password: Option<String>,
-    insecure: bool,
+    opts: &PullOptions,
 ) -> Result<Vec<u8>, Box<dyn ::std::error::Error>> {
     let image: Reference = url.parse()?;
 
-    if image.tag().unwrap_or("latest") == "latest" && !allow_latest {
+    if image.digest().is_none() && image.tag().unwrap_or("latest") == "latest" && !opts.allow_latest
+    {
         return Err(
             "Pulling artifacts with tag 'latest' is prohibited. This can be overriden with a flag"
                 .into(),
         );
     };
 
-    let mut client = Client::new(ClientConfig {
-        protocol: if insecure {
-            ClientProtocol::Http
-        } else {
-            ClientProtocol::Https
-        },
-    });
+    let mut client = oci_client(opts.auth.insecure);
 
-    let auth = match (user, password) {
-        (Some(user), Some(password)) => RegistryAuth::Basic(user, password),
-        _ => RegistryAuth::Anonymous,
+    let auth = resolve_auth(
+        image.registry(),
+        opts.auth.user.clone(),
+        opts.auth.password.clone(),
+    )?;
+
+    // Reformatting digest in case the sha256: prefix is left off
+    let digest = match digest {
+        Some(d) if d.starts_with("sha256:") => Some(d),
+        Some(d) => Some(format!("sha256:{}", d)),
+        None => None,
     };
 
+    let cache = if opts.no_cache { None } else { OciCache::new() };
+    if let Some(cache) = &cache {
+        // A digest identifies the artifact directly, while a tag is resolved to a digest first.
+        // Conflicting digests are left for the registry pull below to report
+        let resolved = match (digest.as_deref(), image.digest()) {
+            (Some(digest), Some(image_digest)) if digest != image_digest => None,
+            (Some(digest), _) | (None, Some(digest)) => Some(digest.to_string()),
+            (None, None) => match cache.tag_digest(&image) {
+                Some(image_digest) => Some(image_digest),
+                // Resolving the tag only fetches the manifest, avoiding the download when the
+                // artifact it points to is already cached
+                None => match client.fetch_manifest_digest(&image, &auth).await {
+                    Ok(image_digest) => {
+                        cache.resolve_tag(&image, &image_digest);
+                        Some(image_digest)
+                    }
+                    Err(e) => {
+                        debug!("Unable to resolve digest of {}: {}", image.whole(), e);
+                        None
+                    }
+                },
+            },
+        };
+        if let Some(artifact) = resolved.as_ref().and_then(|d| cache.artifact(d)) {
+            debug!("Using cached {} ({})", image.whole(), resolved.unwrap());
+            return Ok(artifact);
+        }
+    }
+
     let image_data = client
         .pull(
             &image,
@@ -213,15 +344,8 @@ pub(crate) async fn pull_artifact(
         )
         .await?;
 
-    // Reformatting digest in case the sha256: prefix is left off
-    let digest = match digest {
-        Some(d) if d.starts_with("sha256:") => Some(d),
-        Some(d) => Some(format!("sha256:{}", d)),
-        None => None,
-    };
-
-    match (digest, image_data.digest) {
-        (Some(digest), Some(image_digest)) if digest != image_digest => {
+    match (digest, &image_data.digest) {
+        (Some(digest), Some(image_digest)) if digest != *image_digest => {
             Err("Image digest did not match provided digest, aborting")
         }
         _ => {
@@ -230,12 +354,16 @@ pub(crate) async fn pull_artifact(
         }
     }?;
 
-    Ok(image_data
+    let artifact = image_data
         .layers
         .iter()
         .map(|l| l.data.clone())
         .flatten()
-        .collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if let (Some(cache), Some(image_digest)) = (cache, image_data.digest) {
+        cache.store(&image, &image_digest, &artifact);
+    }
+    Ok(artifact)
 }
 
 pub(crate) fn write_artifact(
@@ -322,6 +450,7 @@ pub(crate) async fn handle_push(cmd: PushCommand) -> Result<String, Box<dyn ::st
         cmd.artifact,
         cmd.config,
         cmd.allow_latest,
+        cmd.annotations,
         cmd.opts.user,
         cmd.opts.password,
         cmd.opts.insecure,
@@ -341,11 +470,15 @@ pub(crate) async fn handle_push(cmd: PushCommand) -> Result<String, Box<dyn ::st
     ))
 }
 
+/// Pushes an artifact with a manifest annotated from its claims, with any `key=value`
+/// annotations taking precedence over the derived ones
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn push_artifact(
     url: String,
     artifact: String,
     config: Option<String>,
     allow_latest: bool,
+    annotations: Vec<String>,
     user: Option<String>,
     password: Option<String>,
     insecure: bool,
@@ -359,6 +492,8 @@ pub(crate) async fn push_artifact(
         );
     };
 
+    let user_annotations = parse_annotations(&annotations)?;
+
     let mut config_buf = vec![];
     match config {
         Some(config_file) => {
@@ -375,14 +510,16 @@ pub(crate) async fn push_artifact(
     let mut f = File::open(artifact.clone())?;
     f.read_to_end(&mut artifact_buf)?;
 
-    let (artifact_media_type, config_media_type) =
-        match validate_artifact(&artifact_buf, &artifact)? {
-            SupportedArtifacts::Wasm => (WASM_MEDIA_TYPE, WASM_CONFIG_MEDIA_TYPE),
-            SupportedArtifacts::Par => (
-                PROVIDER_ARCHIVE_MEDIA_TYPE,
-                PROVIDER_ARCHIVE_CONFIG_MEDIA_TYPE,
-            ),
-        };
+    let artifact_type = validate_artifact(&artifact_buf, &artifact)?;
+    let (artifact_media_type, config_media_type) = match artifact_type {
+        SupportedArtifacts::Wasm => (WASM_MEDIA_TYPE, WASM_CONFIG_MEDIA_TYPE),
+        SupportedArtifacts::Par => (
+            PROVIDER_ARCHIVE_MEDIA_TYPE,
+            PROVIDER_ARCHIVE_CONFIG_MEDIA_TYPE,
+        ),
+    };
+    let mut manifest_annotations = claims_annotations(&artifact_buf, &artifact_type)?;
+    manifest_annotations.extend(user_annotations);
 
     let image_data = ImageData {
         layers: vec![ImageLayer {
@@ -392,19 +529,16 @@ pub(crate) async fn push_artifact(
         digest: None,
     };
 
-    let mut client = Client::new(ClientConfig {
-        protocol: if insecure {
-            ClientProtocol::Http
-        } else {
-            ClientProtocol::Https
-        },
-    });
+    let mut client = oci_client(insecure);
 
-    let auth = match (user, password) {
-        (Some(user), Some(password)) => RegistryAuth::Basic(user, password),
-        _ => RegistryAuth::Anonymous,
-    };
+    let auth = resolve_auth(image.registry(), user, password)?;
 
+    let manifest = annotated_manifest(
+        &image_data,
+        &config_buf,
+        config_media_type,
+        manifest_annotations,
+    );
     client
         .push(
             &image,
@@ -412,16 +546,584 @@ pub(crate) async fn push_artifact(
             &config_buf,
             config_media_type,
             &auth,
-            None,
+            Some(manifest),
         )
         .await?;
     Ok(())
 }
 
+/// Parses `key=value` annotations
+fn parse_annotations(
+    annotations: &[String],
+) -> Result<HashMap<String, String>, Box<dyn ::std::error::Error>> {
+    annotations
+        .iter()
+        .map(|annotation| {
+            let mut parts = annotation.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if !key.trim().is_empty() => {
+                    Ok((key.trim().to_string(), value.to_string()))
+                }
+                _ => Err(format!(
+                    "Invalid annotation {}, annotations must be in the form key=value",
+                    annotation
+                )
+                .into()),
+            }
+        })
+        .collect()
+}
+
+/// Derives manifest annotations from the claims embedded in an actor module or provider archive
+fn claims_annotations(
+    artifact: &[u8],
+    artifact_type: &SupportedArtifacts,
+) -> Result<HashMap<String, String>, Box<dyn ::std::error::Error>> {
+    let mut annotations = HashMap::new();
+    let mut annotate = |key: &str, value: Option<String>| {
+        if let Some(value) = value {
+            annotations.insert(key.to_string(), value);
+        }
+    };
+    match artifact_type {
+        SupportedArtifacts::Wasm => {
+            if let Some(token) = wascap::wasm::extract_claims(artifact)? {
+                let claims = token.claims;
+                annotate(ANNOTATION_ISSUER, Some(claims.issuer));
+                annotate(ANNOTATION_SUBJECT, Some(claims.subject));
+                if let Some(metadata) = claims.metadata {
+                    annotate(ANNOTATION_TITLE, metadata.name);
+                    annotate(ANNOTATION_VERSION, metadata.ver);
+                    annotate(ANNOTATION_REVISION, metadata.rev.map(|r| r.to_string()));
+                    annotate(ANNOTATION_CAPABILITIES, metadata.caps.map(|c| c.join(",")));
+                }
+            }
+        }
+        SupportedArtifacts::Par => {
+            let archive = ProviderArchive::try_load(artifact).map_err(|e| format!("{}", e))?;
+            if let Some(claims) = archive.claims() {
+                annotate(ANNOTATION_ISSUER, Some(claims.issuer));
+                annotate(ANNOTATION_SUBJECT, Some(claims.subject));
+                if let Some(metadata) = claims.metadata {
+                    annotate(ANNOTATION_TITLE, metadata.name);
+                    annotate(ANNOTATION_VERSION, metadata.ver);
+                    annotate(ANNOTATION_REVISION, metadata.rev.map(|r| r.to_string()));
+                    annotate(ANNOTATION_VENDOR, Some(metadata.vendor));
+                    annotate(ANNOTATION_CAPID, Some(metadata.capid));
+                }
+            }
+        }
+    }
+    Ok(annotations)
+}
+
+/// Builds the manifest the client would generate for an artifact, with manifest annotations
+fn annotated_manifest(
+    image_data: &ImageData,
+    config: &[u8],
+    config_media_type: &str,
+    annotations: HashMap<String, String>,
+) -> OciManifest {
+    let layers = image_data
+        .layers
+        .iter()
+        .map(|layer| {
+            let digest = sha256_digest(&layer.data);
+            let mut layer_annotations = HashMap::new();
+            layer_annotations.insert(ANNOTATION_TITLE.to_string(), digest.clone());
+            OciDescriptor {
+                media_type: layer.media_type.clone(),
+                digest,
+                size: layer.data.len() as i64,
+                annotations: Some(layer_annotations),
+                ..Default::default()
+            }
+        })
+        .collect();
+    OciManifest {
+        config: OciDescriptor {
+            media_type: config_media_type.to_string(),
+            digest: sha256_digest(config),
+            size: config.len() as i64,
+            ..Default::default()
+        },
+        layers,
+        annotations: if annotations.is_empty() {
+            None
+        } else {
+            Some(annotations)
+        },
+        ..Default::default()
+    }
+}
+
+pub(crate) fn sha256_digest(bytes: &[u8]) -> String {
+    format!(
+        "sha256:{}",
+        HEXLOWER.encode(ring::digest::digest(&ring::digest::SHA256, bytes).as_ref())
+    )
+}
+
+/// Verifies credentials against a registry and stores them in the wash credentials file
+pub(crate) async fn handle_login(
+    cmd: LoginCommand,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let registry = normalize_registry(&cmd.registry);
+    let user = cmd
+        .user
+        .ok_or("A username is required, please provide it with --user")?;
+    let password = if cmd.password_stdin {
+        let mut password = String::new();
+        std::io::stdin().read_line(&mut password)?;
+        password.trim_end_matches(&['\r', '\n'][..]).to_string()
+    } else {
+        match cmd.password {
+            Some(password) => password,
+            None => prompt_password()?,
+        }
+    };
+
+    let verified = verify_login(&registry, &user, &password, cmd.insecure).await?;
+
+    let path = wash_credentials_path().ok_or(
+        "Unable to determine where to store credentials, please set $WASH_REG_CREDENTIALS",
+    )?;
+    let mut credentials = CredentialsFile::load(&path)?.unwrap_or_default();
+    credentials.store(&registry, &user, &password);
+    credentials.save(&path)?;
+
+    if !verified {
+        return Ok(format_output(
+            format!(
+                "Warning: {} did not authenticate you, the credentials stored in {} were not verified",
+                registry,
+                path.display()
+            ),
+            json!({"result": "unverified", "registry": registry, "file": path}),
+            &cmd.output.kind,
+        ));
+    }
+
+    Ok(format_output(
+        format!(
+            "Login succeeded, credentials for {} stored in {}",
+            registry,
+            path.display()
+        ),
+        json!({"result": "success", "registry": registry, "file": path}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Removes the credentials of a registry from the wash credentials file
+pub(crate) fn handle_logout(cmd: LogoutCommand) -> Result<String, Box<dyn ::std::error::Error>> {
+    let registry = normalize_registry(&cmd.registry);
+    let path = wash_credentials_path().ok_or(
+        "Unable to determine where credentials are stored, please set $WASH_REG_CREDENTIALS",
+    )?;
+    let mut credentials = CredentialsFile::load(&path)?.unwrap_or_default();
+    if !credentials.remove(&registry) {
+        return Err(format!("Not logged in to {}", registry).into());
+    }
+    credentials.save(&path)?;
+
+    Ok(format_output(
+        format!("Removed credentials for {}", registry),
+        json!({"result": "success", "registry": registry}),
+        &cmd.output.kind,
+    ))
+}
+
+/// Reads the registry password from the terminal without echoing it
+fn prompt_password() -> Result<String, Box<dyn ::std::error::Error>> {
+    let mut tty = termion::get_tty().map_err(|_| {
+        "A password is required, please provide it with --password or --password-stdin"
+    })?;
+    tty.write_all(b"Password: ")?;
+    tty.flush()?;
+    let password = tty.try_clone()?.read_passwd(&mut tty)?.unwrap_or_default();
+    tty.write_all(b"\n")?;
+    if password.is_empty() {
+        return Err("Password cannot be empty".into());
+    }
+    Ok(password)
+}
+
+/// Checks credentials against the `/v2/` endpoint of a registry, returning false if the registry
+/// does not require authentication and so cannot verify them. Registries using token
+/// authentication respond with a bearer challenge, in which case the credentials are checked
+/// by requesting a token from the challenge realm
+async fn verify_login(
+    registry: &str,
+    user: &str,
+    password: &str,
+    insecure: bool,
+) -> Result<bool, Box<dyn ::std::error::Error>> {
+    let url = format!(
+        "{}://{}/v2/",
+        if insecure { "http" } else { "https" },
+        registry_host(registry)
+    );
+    let client = reqwest::Client::new();
+    let unreachable = |e: reqwest::Error| format!("Unable to reach registry {}: {}", registry, e);
+    let rejected = || format!("Login to {} failed, invalid username or password", registry);
+
+    // A registry that answers without credentials never checks them
+    let res = client.get(&url).send().await.map_err(unreachable)?;
+    if res.status().is_success() {
+        return Ok(false);
+    }
+    if res.status() != reqwest::StatusCode::UNAUTHORIZED {
+        return Err(format!("Registry {} responded with {}", registry, res.status()).into());
+    }
+
+    let res = match bearer_challenge(&res) {
+        Some(challenge) => {
+            let auth = RegistryAuth::Basic(user.to_string(), password.to_string());
+            challenge
+                .request_token(&client, None, &auth)
+                .await
+                .map_err(unreachable)?
+        }
+        None => client
+            .get(&url)
+            .basic_auth(user, Some(password))
+            .send()
+            .await
+            .map_err(unreachable)?,
+    };
+    if res.status().is_success() {
+        Ok(true)
+    } else {
+        Err(rejected().into())
+    }
+}
+
+/// Docker Hub serves its registry API from a different host than its image references
+fn registry_host(registry: &str) -> &str {
+    if registry == "docker.io" {
+        "registry-1.docker.io"
+    } else {
+        registry
+    }
+}
+
+/// Fetches the manifest, config and tags of an artifact and outputs them along with the type of
+/// artifact, leaving its layers in the registry
+pub(crate) async fn handle_inspect(
+    cmd: InspectCommand,
+) -> Result<String, Box<dyn ::std::error::Error>> {
+    let url = cmd.url;
+    let image: Reference = url.parse()?;
+    let auth = resolve_auth(image.registry(), cmd.opts.user, cmd.opts.password)?;
+    let digest = oci_client(cmd.opts.insecure)
+        .fetch_manifest_digest(&image, &auth)
+        .await
+        .map_err(|e| format!("Unable to fetch manifest of {}: {}", url, e))?;
+    let session = RegistrySession::connect(&image, auth, cmd.opts.insecure).await?;
+
+    let res = session
+        .get(&format!("manifests/{}", digest), MANIFEST_MEDIA_TYPES)
+        .await?;
+    if !res.status().is_success() {
+        return Err(format!(
+            "Unable to fetch manifest of {}, registry responded with {}",
+            url,
+            res.status()
+        )
+        .into());
+    }
+    let body = res.bytes().await?;
+    if sha256_digest(&body) != digest {
+        return Err(format!("Manifest of {} did not match its digest {}", url, digest).into());
+    }
+    let manifest: OciManifest = serde_json::from_slice(&body)
+        .map_err(|e| format!("Unable to parse manifest of {}: {}", url, e))?;
+
+    // Registries may refuse to list tags or serve configs, neither of which is essential
+    let config = match session
+        .get(&format!("blobs/{}", manifest.config.digest), "*/*")
+        .await
+    {
+        Ok(res) if res.status().is_success() => res.json::<serde_json::Value>().await.ok(),
+        _ => None,
+    };
+    let mut tags = match session.get("tags/list", "application/json").await {
+        Ok(res) if res.status().is_success() => res
+            .json::<TagList>()
+            .await
+            .map(|list| list.tags.unwrap_or_default())
+            .unwrap_or_default(),
+        _ => vec![],
+    };
+    tags.sort();
+
+    let artifact_type = if manifest
+        .layers
+        .iter()
+        .any(|layer| layer.media_type == WASM_MEDIA_TYPE)
+    {
+        "Actor"
+    } else if manifest
+        .layers
+        .iter()
+        .any(|layer| layer.media_type == PROVIDER_ARCHIVE_MEDIA_TYPE)
+    {
+        "Provider Archive"
+    } else {
+        "Unknown"
+    };
+    let size: i64 = manifest.layers.iter().map(|layer| layer.size).sum();
+    let annotations: BTreeMap<String, String> = manifest
+        .annotations
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    let layers: Vec<serde_json::Value> = manifest
+        .layers
+        .iter()
+        .map(|layer| json!({"media_type": layer.media_type, "digest": layer.digest, "size": layer.size}))
+        .collect();
+    let json = json!({
+        "url": url,
+        "digest": digest,
+        "type": artifact_type,
+        "size": size,
+        "config": {
+            "media_type": manifest.config.media_type,
+            "digest": manifest.config.digest,
+            "size": manifest.config.size,
+            "contents": config,
+        },
+        "layers": layers,
+        "annotations": annotations,
+        "tags": tags,
+    });
+
+    let text = match cmd.output.kind {
+        OutputKind::Text { max_width } => {
+            use term_table::row::Row;
+            use term_table::table_cell::*;
+            use term_table::Table;
+
+            let mut table = Table::new();
+            crate::util::configure_table_style(&mut table, 2, max_width);
+            let row = |name: &str, value: String| {
+                Row::new(vec![
+                    TableCell::new(name),
+                    TableCell::new_with_alignment(value, 1, Alignment::Right),
+                ])
+            };
+            let section = |title: &str| {
+                Row::new(vec![TableCell::new_with_alignment(
+                    title,
+                    2,
+                    Alignment::Center,
+                )])
+            };
+
+            table.add_row(section(&format!("{} - {}", url, artifact_type)));
+            table.add_row(row("Digest", digest));
+            table.add_row(row("Size", format!("{} bytes", size)));
+            table.add_row(row(
+                "Config",
+                format!(
+                    "{} ({} bytes)",
+                    manifest.config.media_type, manifest.config.size
+                ),
+            ));
+            for layer in manifest.layers.iter() {
+                table.add_row(row(
+                    "Layer",
+                    format!("{} ({} bytes)", layer.media_type, layer.size),
+                ));
+            }
+
+            if !annotations.is_empty() {
+                table.add_row(section("Annotations"));
+                for (key, value) in annotations.iter() {
+                    table.add_row(row(key, value.to_string()));
+                }
+            }
+
+            table.add_row(section("Tags"));
+            table.add_row(Row::new(vec![TableCell::new_with_alignment(
+                if tags.is_empty() {
+                    "None".to_string()
+                } else {
+                    tags.join("\n")
+                },
+                2,
+                Alignment::Left,
+            )]));
+
+            table.render()
+        }
+        OutputKind::Json => String::new(),
+    };
+
+    Ok(format_output(text, json, &cmd.output.kind))
+}
+
+/// Response of the tag listing endpoint
+#[derive(Debug, Deserialize)]
+struct TagList {
+    tags: Option<Vec<String>>,
+}
+
+/// Bearer token response of a registry's token endpoint
+#[derive(Debug, Deserialize)]
+struct RegistryToken {
+    #[serde(alias = "access_token")]
+    token: String,
+}
+
+/// Authenticated requests to the registry API of a repository, for the manifests, blobs and tag
+/// lists the OCI client does not expose. The client keeps its token to itself, so a pull token is
+/// requested the same way the client does
+struct RegistrySession {
+    client: reqwest::Client,
+    base: String,
+    auth: RegistryAuth,
+    token: Option<String>,
+}
+
+impl RegistrySession {
+    async fn connect(
+        image: &Reference,
+        auth: RegistryAuth,
+        insecure: bool,
+    ) -> Result<Self, Box<dyn ::std::error::Error>> {
+        let registry = image.registry();
+        let root = format!(
+            "{}://{}/v2/",
+            if insecure { "http" } else { "https" },
+            registry_host(registry)
+        );
+        let client = reqwest::Client::new();
+        let unreachable =
+            |e: reqwest::Error| format!("Unable to reach registry {}: {}", registry, e);
+
+        let res = client.get(&root).send().await.map_err(unreachable)?;
+        let token = match bearer_challenge(&res) {
+            Some(challenge) => {
+                let scope = format!("repository:{}:pull", image.repository());
+                let res = challenge
+                    .request_token(&client, Some(scope), &auth)
+                    .await
+                    .map_err(unreachable)?;
+                if !res.status().is_success() {
+                    return Err(format!(
+                        "Unable to authenticate with registry {}, it responded with {}",
+                        registry,
+                        res.status()
+                    )
+                    .into());
+                }
+                Some(res.json::<RegistryToken>().await?.token)
+            }
+            None => None,
+        };
+
+        Ok(RegistrySession {
+            client,
+            base: format!("{}{}", root, image.repository()),
+            auth,
+            token,
+        })
+    }
+
+    async fn get(
+        &self,
+        path: &str,
+        accept: &str,
+    ) -> Result<reqwest::Response, Box<dyn ::std::error::Error>> {
+        let mut request = self
+            .client
+            .get(&format!("{}/{}", self.base, path))
+            .header(reqwest::header::ACCEPT, accept);
+        request = match (&self.token, &self.auth) {
+            (Some(token), _) => request.bearer_auth(token),
+            (None, RegistryAuth::Basic(user, password)) => request.basic_auth(user, Some(password)),
+            (None, RegistryAuth::Anonymous) => request,
+        };
+        Ok(request.send().await?)
+    }
+}
+
+/// Realm and service of a registry's bearer challenge, from which tokens are requested
+#[derive(Debug, PartialEq)]
+struct BearerChallenge {
+    realm: String,
+    service: Option<String>,
+}
+
+impl BearerChallenge {
+    /// Requests a token from the challenge realm, for the given scope if any
+    async fn request_token(
+        &self,
+        client: &reqwest::Client,
+        scope: Option<String>,
+        auth: &RegistryAuth,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let mut request = client.get(&self.realm);
+        if let Some(scope) = scope {
+            request = request.query(&[("scope", scope)]);
+        }
+        if let Some(service) = &self.service {
+            request = request.query(&[("service", service)]);
+        }
+        if let RegistryAuth::Basic(user, password) = auth {
+            request = request.basic_auth(user, Some(password));
+        }
+        request.send().await
+    }
+}
+
+/// Returns the bearer challenge of a registry response, parsing its `WWW-Authenticate` header
+/// the same way the OCI client does
+fn bearer_challenge(res: &reqwest::Response) -> Option<BearerChallenge> {
+    parse_bearer_challenge(
+        res.headers()
+            .get(reqwest::header::WWW_AUTHENTICATE)?
+            .as_bytes(),
+    )
+}
+
+fn parse_bearer_challenge(header: &[u8]) -> Option<BearerChallenge> {
+    let challenges = WwwAuthenticate::parse_header(&header.into()).ok()?;
+    match challenges.get_raw("Bearer")?.first()? {
+        RawChallenge::Fields(fields) => Some(BearerChallenge {
+            realm: fields.get("realm")?.to_string(),
+            service: fields.get("service").cloned(),
+        }),
+        RawChallenge::Token68(_) => None,
+    }
+}
+
+/// Creates an OCI client, using plain HTTP for insecure registries
+fn oci_client(insecure: bool) -> Client {
+    Client::new(ClientConfig {
+        protocol: if insecure {
+            ClientProtocol::Http
+        } else {
+            ClientProtocol::Https
+        },
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{PullCommand, PushCommand, RegCli, RegCliCommand};
+    use super::{
+        annotated_manifest, parse_annotations, parse_bearer_challenge, BearerChallenge,
+        InspectCommand, LoginCommand, LogoutCommand, PullCommand, PushCommand, RegCli,
+        RegCliCommand, WASM_CONFIG_MEDIA_TYPE, WASM_MEDIA_TYPE,
+    };
     use crate::util::OutputKind;
+    use oci_distribution::client::{ImageData, ImageLayer};
+    use std::collections::HashMap;
     use structopt::StructOpt;
 
     const ECHO_WASM: &str = "wasmcloud.azurecr.io/echo:0.2.0";
@@ -436,8 +1138,14 @@ mod tests {
         const TESTDIR: &str = "./tests/fixtures";
 
         let pull_basic = RegCli::from_iter(&["reg", "pull", ECHO_WASM]);
-        let pull_all_flags =
-            RegCli::from_iter(&["reg", "pull", ECHO_WASM, "--allow-latest", "--insecure"]);
+        let pull_all_flags = RegCli::from_iter(&[
+            "reg",
+            "pull",
+            ECHO_WASM,
+            "--allow-latest",
+            "--insecure",
+            "--no-cache",
+        ]);
         let pull_all_options = RegCli::from_iter(&[
             "reg",
             "pull",
@@ -461,15 +1169,11 @@ mod tests {
         };
 
         match pull_all_flags.command {
-            RegCliCommand::Pull(PullCommand {
-                url,
-                allow_latest,
-                opts,
-                ..
-            }) => {
+            RegCliCommand::Pull(PullCommand { url, opts, .. }) => {
                 assert_eq!(url, ECHO_WASM);
-                assert!(allow_latest);
-                assert!(opts.insecure);
+                assert!(opts.allow_latest);
+                assert!(opts.no_cache);
+                assert!(opts.auth.insecure);
             }
             _ => panic!("`reg pull` constructed incorrect command"),
         };
@@ -490,8 +1194,8 @@ mod tests {
                     "sha256:a17a163afa8447622055deb049587641a9e23243a6cc4411eb33bd4267214cf3"
                 );
                 assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
-                assert_eq!(opts.user.unwrap(), "user");
-                assert_eq!(opts.password.unwrap(), "password");
+                assert_eq!(opts.auth.user.unwrap(), "user");
+                assert_eq!(opts.auth.password.unwrap(), "password");
             }
             _ => panic!("`reg pull` constructed incorrect command"),
         };
@@ -571,6 +1275,10 @@ mod tests {
             "supers3cr3t",
             "--user",
             "localuser",
+            "--annotation",
+            "org.opencontainers.image.title=Logging",
+            "-a",
+            "com.example.team=platform",
         ]);
         match push_all_options.command {
             RegCliCommand::Push(PushCommand {
@@ -578,9 +1286,9 @@ mod tests {
                 artifact,
                 opts,
                 allow_latest,
+                annotations,
                 config,
                 output,
-                ..
             }) => {
                 assert_eq!(&url, logging_push_all_options);
                 assert_eq!(artifact, format!("{}/logging.par.gz", TESTDIR));
@@ -590,8 +1298,196 @@ mod tests {
                 assert_eq!(opts.user.unwrap(), "localuser");
                 assert_eq!(opts.password.unwrap(), "supers3cr3t");
                 assert_eq!(output.kind, OutputKind::Json);
+                assert_eq!(
+                    annotations,
+                    vec![
+                        "org.opencontainers.image.title=Logging",
+                        "com.example.team=platform"
+                    ]
+                );
             }
             _ => panic!("`reg push` constructed incorrect command"),
         };
     }
+
+    #[test]
+    fn test_inspect_comprehensive() {
+        let inspect_basic = RegCli::from_iter_safe(&["reg", "inspect", ECHO_WASM]).unwrap();
+        match inspect_basic.command {
+            RegCliCommand::Inspect(InspectCommand { url, output, opts }) => {
+                assert_eq!(url, ECHO_WASM);
+                assert_eq!(output.kind, OutputKind::Text { max_width: 0 });
+                assert!(!opts.insecure);
+            }
+            _ => panic!("`reg inspect` constructed incorrect command"),
+        };
+
+        let inspect_all = RegCli::from_iter_safe(&[
+            "reg",
+            "inspect",
+            ECHO_WASM,
+            "--insecure",
+            "-o",
+            "json",
+            "-u",
+            "user",
+            "-p",
+            "password",
+        ])
+        .unwrap();
+        match inspect_all.command {
+            RegCliCommand::Inspect(InspectCommand { url, output, opts }) => {
+                assert_eq!(url, ECHO_WASM);
+                assert_eq!(output.kind, OutputKind::Json);
+                assert!(opts.insecure);
+                assert_eq!(opts.user.unwrap(), "user");
+                assert_eq!(opts.password.unwrap(), "password");
+            }
+            _ => panic!("`reg inspect` constructed incorrect command"),
+        };
+    }
+
+    #[test]
+    fn test_annotated_manifest() {
+        let annotations = parse_annotations(&[
+            "org.opencontainers.image.title=Echo".to_string(),
+            "dev.wasmcloud.note=a=b".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(annotations["org.opencontainers.image.title"], "Echo");
+        assert_eq!(annotations["dev.wasmcloud.note"], "a=b");
+        for invalid in &["title", "=Echo"] {
+            assert_eq!(
+                parse_annotations(&[invalid.to_string()])
+                    .unwrap_err()
+                    .to_string(),
+                format!(
+                    "Invalid annotation {}, annotations must be in the form key=value",
+                    invalid
+                )
+            );
+        }
+
+        let image_data = ImageData {
+            layers: vec![ImageLayer {
+                data: b"\0asm\x01\0\0\0".to_vec(),
+                media_type: WASM_MEDIA_TYPE.to_string(),
+            }],
+            digest: None,
+        };
+        let manifest = annotated_manifest(&image_data, b"{}", WASM_CONFIG_MEDIA_TYPE, annotations);
+        // Layer and config digests match those the client uses when pushing them
+        assert_eq!(manifest.layers[0].digest, image_data.sha256_digest());
+        assert_eq!(manifest.layers[0].size, 8);
+        assert_eq!(
+            manifest.config.digest,
+            "sha256:44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        assert_eq!(
+            manifest.annotations.unwrap()["org.opencontainers.image.title"],
+            "Echo"
+        );
+        assert!(
+            annotated_manifest(&image_data, b"{}", WASM_CONFIG_MEDIA_TYPE, HashMap::new())
+                .annotations
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_login_logout_comprehensive() {
+        let login_all = RegCli::from_iter_safe(&[
+            "reg",
+            "login",
+            LOCAL_REGISTRY,
+            "--user",
+            "user",
+            "--password",
+            "secret",
+            "--insecure",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+        match login_all.command {
+            RegCliCommand::Login(LoginCommand {
+                registry,
+                user,
+                password,
+                password_stdin,
+                insecure,
+                output,
+            }) => {
+                assert_eq!(registry, LOCAL_REGISTRY);
+                assert_eq!(user.unwrap(), "user");
+                assert_eq!(password.unwrap(), "secret");
+                assert!(!password_stdin);
+                assert!(insecure);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            _ => panic!("`reg login` constructed incorrect command"),
+        };
+
+        let login_stdin = RegCli::from_iter_safe(&[
+            "reg",
+            "login",
+            LOCAL_REGISTRY,
+            "-u",
+            "user",
+            "--password-stdin",
+        ])
+        .unwrap();
+        match login_stdin.command {
+            RegCliCommand::Login(LoginCommand {
+                user,
+                password_stdin,
+                ..
+            }) => {
+                assert_eq!(user.unwrap(), "user");
+                assert!(password_stdin);
+            }
+            _ => panic!("`reg login` constructed incorrect command"),
+        };
+        assert!(RegCli::from_iter_safe(&[
+            "reg",
+            "login",
+            LOCAL_REGISTRY,
+            "-p",
+            "secret",
+            "--password-stdin"
+        ])
+        .is_err());
+
+        let logout =
+            RegCli::from_iter_safe(&["reg", "logout", LOCAL_REGISTRY, "-o", "json"]).unwrap();
+        match logout.command {
+            RegCliCommand::Logout(LogoutCommand { registry, output }) => {
+                assert_eq!(registry, LOCAL_REGISTRY);
+                assert_eq!(output.kind, OutputKind::Json);
+            }
+            _ => panic!("`reg logout` constructed incorrect command"),
+        };
+    }
+
+    #[test]
+    fn test_bearer_challenge() {
+        assert_eq!(
+            parse_bearer_challenge(
+                b"Bearer realm=\"https://auth.docker.io/token\",service=\"registry.docker.io\",scope=\"repository:wasmcloud/echo:pull,push\""
+            ),
+            Some(BearerChallenge {
+                realm: "https://auth.docker.io/token".to_string(),
+                service: Some("registry.docker.io".to_string())
+            })
+        );
+        // Quoted values may contain commas
+        assert_eq!(
+            parse_bearer_challenge(b"Bearer realm=\"https://localhost/token?a=1,b=2\""),
+            Some(BearerChallenge {
+                realm: "https://localhost/token?a=1,b=2".to_string(),
+                service: None
+            })
+        );
+        assert!(parse_bearer_challenge(b"Basic realm=\"Registry\"").is_none());
+    }
 }
@@ -0,0 +1,325 @@
+use crate::keys::extract_keypair;
+use data_encoding::HEXUPPER;
+use nkeys::{KeyPair, KeyPairType};
+use parity_wasm::elements::Module;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use wascap::jwt::{Actor, Claims, WascapEntity};
+
+const HEADER_TYPE: &str = "jwt";
+const HEADER_ALGORITHM: &str = "Ed25519";
+
+type Result<T> = ::std::result::Result<T, Box<dyn Error>>;
+
+/// A key that can sign claims. The private half of the key does not need to be held by wash.
+pub(crate) trait Signer {
+    fn public_key(&self) -> String;
+
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>>;
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> String {
+        KeyPair::public_key(self)
+    }
+
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        Ok(KeyPair::sign(self, payload)?)
+    }
+}
+
+/// A public key without access to the matching seed. This is all that is needed for the subject of a token.
+pub(crate) struct PublicKey(pub(crate) String);
+
+impl Signer for PublicKey {
+    fn public_key(&self) -> String {
+        self.0.clone()
+    }
+
+    fn sign(&self, _payload: &[u8]) -> Result<Vec<u8>> {
+        Err(format!(
+            "Unable to sign with public key {}, provide its seed or an external signer with --signer ($WASH_SIGNER)",
+            self.0
+        )
+        .into())
+    }
+}
+
+#[derive(Serialize)]
+struct ClaimsHeader {
+    typ: &'static str,
+    alg: &'static str,
+}
+
+#[derive(Serialize)]
+struct SignRequest<'a> {
+    public_key: &'a str,
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: Option<String>,
+    error: Option<String>,
+}
+
+/// Delegates signing to an external command, e.g. a client for an HSM or a local signing agent.
+/// For every signature the command is run with a JSON request on stdin,
+/// `{"public_key": "<public key>", "payload": "<base64url>"}`, and must print
+/// `{"signature": "<base64url>"}` or `{"error": "<message>"}` to stdout.
+pub(crate) struct ExternalSigner {
+    command: String,
+    public_key: String,
+}
+
+impl ExternalSigner {
+    pub(crate) fn new(command: String, public_key: String) -> Self {
+        ExternalSigner {
+            command,
+            public_key,
+        }
+    }
+
+    fn run(&self, request: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(target_family = "windows")]
+        let mut command = Command::new("cmd");
+        #[cfg(target_family = "windows")]
+        command.arg("/C");
+        #[cfg(not(target_family = "windows"))]
+        let mut command = Command::new("sh");
+        #[cfg(not(target_family = "windows"))]
+        command.arg("-c");
+
+        let mut child = command
+            .arg(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Unable to start signer \"{}\": {}", self.command, e))?;
+        child
+            .stdin
+            .take()
+            .ok_or("Unable to write to signer")?
+            .write_all(request)?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(
+                format!("Signer \"{}\" exited with {}", self.command, output.status).into(),
+            );
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl Signer for ExternalSigner {
+    fn public_key(&self) -> String {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        let request = serde_json::to_vec(&SignRequest {
+            public_key: &self.public_key,
+            payload: base64::encode_config(payload, base64::URL_SAFE_NO_PAD),
+        })?;
+        let response: SignResponse = serde_json::from_slice(&self.run(&request)?)
+            .map_err(|e| format!("Invalid response from signer: {}", e))?;
+        let signature = match (response.signature, response.error) {
+            (_, Some(error)) => return Err(format!("Signer failed: {}", error).into()),
+            (Some(signature), None) => base64::decode_config(signature, base64::URL_SAFE_NO_PAD)
+                .map_err(|e| format!("Invalid signature from signer: {}", e))?,
+            (None, None) => return Err("Signer did not return a signature".into()),
+        };
+
+        // Never embed a signature that will not validate against the claimed issuer
+        KeyPair::from_public_key(&self.public_key)?
+            .verify(payload, &signature)
+            .map_err(|_| {
+                format!(
+                    "Signature returned by signer is not valid for {}",
+                    self.public_key
+                )
+            })?;
+        Ok(signature)
+    }
+}
+
+/// Helper function to locate a signing key from user input. Seeds are located the same way as
+/// `extract_keypair`, while public keys are signed for by the external `signer` command if one is provided.
+pub(crate) fn extract_signer(
+    input: Option<String>,
+    module_path: Option<String>,
+    directory: Option<String>,
+    keygen_type: KeyPairType,
+    disable_keygen: bool,
+    signer: Option<String>,
+) -> Result<Box<dyn Signer>> {
+    if let Some(key) = input.as_ref().filter(|k| is_public_key(k)) {
+        if key_type_prefix(&keygen_type) != key.chars().next() {
+            return Err(format!("{} is not a {:?} public key", key, keygen_type).into());
+        }
+        return Ok(match signer {
+            Some(command) => Box::new(ExternalSigner::new(command, key.to_string())),
+            None => Box::new(PublicKey(key.to_string())),
+        });
+    }
+    Ok(Box::new(extract_keypair(
+        input,
+        module_path,
+        directory,
+        keygen_type,
+        disable_keygen,
+    )?))
+}
+
+fn is_public_key(key: &str) -> bool {
+    !key.starts_with('S') && KeyPair::from_public_key(key).is_ok()
+}
+
+fn key_type_prefix(keygen_type: &KeyPairType) -> Option<char> {
+    match keygen_type {
+        KeyPairType::Server => Some('N'),
+        KeyPairType::Cluster => Some('C'),
+        KeyPairType::Operator => Some('O'),
+        KeyPairType::Account => Some('A'),
+        KeyPairType::User => Some('U'),
+        KeyPairType::Module => Some('M'),
+        KeyPairType::Service => Some('V'),
+    }
+}
+
+/// Encodes and signs a set of claims as a JWT, equivalent to `Claims::encode`
+pub(crate) fn encode_claims<T>(claims: &Claims<T>, signer: &dyn Signer) -> Result<String>
+where
+    T: Serialize + serde::de::DeserializeOwned + WascapEntity,
+{
+    let header = ClaimsHeader {
+        typ: HEADER_TYPE,
+        alg: HEADER_ALGORITHM,
+    };
+    let head_and_claims = format!(
+        "{}.{}",
+        base64::encode_config(serde_json::to_vec(&header)?, base64::URL_SAFE_NO_PAD),
+        base64::encode_config(serde_json::to_vec(claims)?, base64::URL_SAFE_NO_PAD)
+    );
+    let sig = signer.sign(head_and_claims.as_bytes())?;
+    Ok(format!(
+        "{}.{}",
+        head_and_claims,
+        base64::encode_config(&sig, base64::URL_SAFE_NO_PAD)
+    ))
+}
+
+/// Embeds a set of claims in a WebAssembly module, equivalent to `wascap::wasm::embed_claims`
+pub(crate) fn embed_claims(
+    orig_bytecode: &[u8],
+    claims: &Claims<Actor>,
+    signer: &dyn Signer,
+) -> Result<Vec<u8>> {
+    let mut module: Module = parity_wasm::deserialize_buffer(orig_bytecode)?;
+    module.clear_custom_section("jwt");
+    let cleanbytes = parity_wasm::serialize(module)?;
+
+    let mut claims = claims.clone();
+    claims.metadata = claims.metadata.map(|md| Actor {
+        module_hash: HEXUPPER.encode(digest(&SHA256, &cleanbytes).as_ref()),
+        ..md
+    });
+    let jwt = encode_claims(&claims, signer)?;
+
+    let mut module: Module = parity_wasm::deserialize_buffer(orig_bytecode)?;
+    module.set_custom_section("jwt", jwt.into_bytes());
+    Ok(parity_wasm::serialize(module)?)
+}
+
+/// Computes the hash of a provider archive target, as embedded in its claims
+pub(crate) fn hash_bytes(bytes: &[u8]) -> String {
+    HEXUPPER.encode(digest(&SHA256, bytes).as_ref())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use wascap::jwt::Account;
+
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+
+    #[test]
+    fn test_encode_matches_wascap() {
+        let kp = KeyPair::from_seed(ACCOUNT).unwrap();
+        let claims =
+            Claims::<Account>::new("Test".to_string(), kp.public_key(), kp.public_key(), vec![]);
+        assert_eq!(
+            encode_claims(&claims, &kp).unwrap(),
+            claims.encode(&kp).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_extract_public_key_signer() {
+        let kp = KeyPair::from_seed(ACCOUNT).unwrap();
+        let signer = extract_signer(
+            Some(kp.public_key()),
+            None,
+            None,
+            KeyPairType::Account,
+            true,
+            None,
+        )
+        .unwrap();
+        assert_eq!(signer.public_key(), kp.public_key());
+        assert!(signer.sign(b"payload").is_err());
+
+        assert!(extract_signer(
+            Some(kp.public_key()),
+            None,
+            None,
+            KeyPairType::Operator,
+            true,
+            Some("true".to_string()),
+        )
+        .is_err());
+    }
+
+    #[cfg(not(target_family = "windows"))]
+    #[test]
+    fn test_external_signer() {
+        let kp = KeyPair::from_seed(ACCOUNT).unwrap();
+        let signature =
+            base64::encode_config(kp.sign(b"payload").unwrap(), base64::URL_SAFE_NO_PAD);
+        let respond = |response: String| format!("cat > /dev/null; echo '{}'", response);
+
+        let signer = ExternalSigner::new(
+            respond(format!("{{\"signature\": \"{}\"}}", signature)),
+            kp.public_key(),
+        );
+        let jwt_signature = signer.sign(b"payload").unwrap();
+        assert!(kp.verify(b"payload", &jwt_signature).is_ok());
+
+        // Signatures from a different key are rejected
+        let other = ExternalSigner::new(
+            respond(format!("{{\"signature\": \"{}\"}}", signature)),
+            KeyPair::new_account().public_key(),
+        );
+        assert!(other
+            .sign(b"payload")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Signature returned by signer is not valid"));
+
+        let failing = ExternalSigner::new(
+            respond("{\"error\": \"key locked\"}".to_string()),
+            kp.public_key(),
+        );
+        assert_eq!(
+            failing.sign(b"payload").unwrap_err().to_string(),
+            "Signer failed: key locked"
+        );
+        assert!(ExternalSigner::new("exit 1".to_string(), kp.public_key())
+            .sign(b"payload")
+            .is_err());
+    }
+}
@@ -544,10 +544,7 @@ async fn handle_up(cmd: UpCliCommand) -> Result<()> {
                                         (Some(new_actor_ref.clone()), crate::reg::pull_artifact(
                                             new_actor_ref.clone(),
                                             None,
-                                            false,
-                                            None,
-                                            None,
-                                            false,
+                                            &crate::reg::PullOptions::default(),
                                         )
                                         .await
                                         .unwrap_or_else(|_| vec![]))
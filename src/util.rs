@@ -76,6 +76,20 @@ impl FromStr for OutputKind {
     }
 }
 
+/// Output of a command that ran to completion with a failing result, such as a failed
+/// verification. It is printed to stdout like any other output, without an error prefix,
+/// and the command exits non-zero
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FailedOutput(pub(crate) String);
+
+impl Error for FailedOutput {}
+
+impl fmt::Display for FailedOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct OutputParseErr;
 
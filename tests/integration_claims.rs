@@ -1,6 +1,7 @@
 mod common;
 use common::{output_to_string, test_dir_file, test_dir_with_subfolder, wash};
-use std::fs::remove_dir_all;
+use std::fs::{remove_dir_all, File};
+use std::io::prelude::*;
 
 #[test]
 fn integration_claims_sign() {
@@ -240,3 +241,888 @@ fn integration_claims_call_alias() {
 
     remove_dir_all(call_alias_dir).unwrap();
 }
+
+#[test]
+fn integration_claims_verify() {
+    const SUBFOLDER: &str = "claims_verify";
+    const OPERATOR: &str = "SOALHM3VR5GETKJ6OKVW2TV7ZBVNYCARJKXKGFW3E4VYU3TR7MDYKNCVVU";
+    const OTHER_OPERATOR: &str = "SOAGLPFGB6OHHECCZXZPDTOPINRKV7YJCODWGIR7AFI3W2CEUTLBJNQA2E";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    const MOD_PKEY: &str = "MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5";
+    let verify_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    // An empty module is all that's needed to embed claims
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+    let signed_module = test_dir_file(SUBFOLDER, "empty_s.wasm");
+    let sign = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+            "--disable-keygen",
+            "--destination",
+            signed_module.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign empty module");
+    assert!(sign.status.success());
+
+    let token = |args: &[&str]| {
+        let output = wash()
+            .args(&["claims", "token"])
+            .args(args)
+            .args(&["--disable-keygen"])
+            .output()
+            .expect("failed to generate token");
+        assert!(output.status.success());
+        output_to_string(output).trim().to_string()
+    };
+    let operator = token(&["operator", "--name", "Operator", "--issuer", OPERATOR]);
+    let account = token(&[
+        "account",
+        "--name",
+        "Account",
+        "--issuer",
+        OPERATOR,
+        "--subject",
+        ACCOUNT,
+    ]);
+    let other_account = token(&[
+        "account",
+        "--name",
+        "Account",
+        "--issuer",
+        OTHER_OPERATOR,
+        "--subject",
+        ACCOUNT,
+    ]);
+
+    let verify = wash()
+        .args(&[
+            "claims",
+            "verify",
+            signed_module.to_str().unwrap(),
+            "--operator",
+            &operator,
+            "--account",
+            &account,
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to verify signed module");
+    assert!(verify.status.success());
+    let output = output_to_string(verify);
+    assert!(output.contains("\"valid\":true"));
+    assert!(output.contains(&format!("\"module\":\"{}\"", MOD_PKEY)));
+
+    let verify_untrusted = wash()
+        .args(&[
+            "claims",
+            "verify",
+            signed_module.to_str().unwrap(),
+            "--operator",
+            &operator,
+            "--account",
+            &other_account,
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to verify signed module");
+    assert!(!verify_untrusted.status.success());
+    // The failure is output like any other result, for CI to parse
+    let error = output_to_string(verify_untrusted);
+    assert!(!error.starts_with("Error"));
+    assert!(error.contains("\"valid\":false"));
+    assert!(error.contains("\"token\":\"account\""));
+    assert!(error.contains("\"reason\":\"untrusted_issuer\""));
+
+    remove_dir_all(verify_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_inspect_tokens() {
+    const SUBFOLDER: &str = "claims_inspect_tokens";
+    const OPERATOR: &str = "SOALHM3VR5GETKJ6OKVW2TV7ZBVNYCARJKXKGFW3E4VYU3TR7MDYKNCVVU";
+    const OPERATOR_PKEY: &str = "OCRNTSAXJPJCEG5DDC4IJMAYCTFIRHA7BKBFXJGY42ID4E5Y4K6RIWQB";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const ACCOUNT_PKEY: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+    const SERVICE: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    let inspect_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let operator = wash()
+        .args(&[
+            "claims",
+            "token",
+            "operator",
+            "--name",
+            "Operator",
+            "--issuer",
+            OPERATOR,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to generate operator token");
+    assert!(operator.status.success());
+    let operator = output_to_string(operator).trim().to_string();
+
+    // Inspect the raw token string
+    let inspect_operator = wash()
+        .args(&["claims", "inspect", &operator, "-o", "json"])
+        .output()
+        .expect("failed to inspect operator token");
+    assert!(inspect_operator.status.success());
+    let output = output_to_string(inspect_operator);
+    assert!(output.contains(&format!("\"operator\":\"{}\"", OPERATOR_PKEY)));
+    assert!(output.contains("\"additional_signing_keys\":[]"));
+
+    // Inspect the token from a file
+    let operator_file = test_dir_file(SUBFOLDER, "operator.jwt");
+    let mut file = File::create(operator_file.clone()).unwrap();
+    file.write_all(operator.as_bytes()).unwrap();
+    let inspect_operator_file = wash()
+        .args(&[
+            "claims",
+            "inspect",
+            operator_file.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to inspect operator token file");
+    assert!(inspect_operator_file.status.success());
+    assert!(output_to_string(inspect_operator_file)
+        .contains(&format!("\"operator\":\"{}\"", OPERATOR_PKEY)));
+
+    // Inspect a provider archive
+    let binary = test_dir_file(SUBFOLDER, "linux.so");
+    let mut bin_file = File::create(binary.clone()).unwrap();
+    bin_file.write_all(b"01100010 01110100 01110111").unwrap();
+    let archive = test_dir_file(SUBFOLDER, "inspect.par.gz");
+    let create = wash()
+        .args(&[
+            "par",
+            "create",
+            "-a",
+            "x86_64-linux",
+            "-b",
+            binary.to_str().unwrap(),
+            "-c",
+            "wasmcloud:testing",
+            "-n",
+            "Inspected",
+            "-v",
+            "TestRunner",
+            "--compress",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            SERVICE,
+            "--disable-keygen",
+            "--destination",
+            archive.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to create provider archive file");
+    assert!(create.status.success());
+
+    let inspect_archive = wash()
+        .args(&["claims", "inspect", archive.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("failed to inspect provider archive");
+    assert!(inspect_archive.status.success());
+    let output = output_to_string(inspect_archive);
+    assert!(output.contains(&format!("\"account\":\"{}\"", ACCOUNT_PKEY)));
+    assert!(output.contains("\"capability_contract_id\":\"wasmcloud:testing\""));
+    assert!(output.contains("\"targets\":[\"x86_64-linux\"]"));
+    assert!(output.contains("\"vendor\":\"TestRunner\""));
+
+    let inspect_archive_jwt = wash()
+        .args(&["claims", "inspect", archive.to_str().unwrap(), "--jwt-only"])
+        .output()
+        .expect("failed to inspect provider archive");
+    assert!(inspect_archive_jwt.status.success());
+    assert_eq!(
+        output_to_string(inspect_archive_jwt)
+            .trim()
+            .split('.')
+            .count(),
+        3
+    );
+
+    remove_dir_all(inspect_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_update() {
+    const SUBFOLDER: &str = "claims_update";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const OTHER_ACCOUNT: &str = "SAABHNRGBCQYFT3HGDQCIQO7KGCQN3DNWCULDW6TARMYBFBF36Y6HYWQT4";
+    const OTHER_ACC_PKEY: &str = "ACD2YVUYRF6NJ6KTFBUA5IMZWT6NBZDK36JHS7D5CCQP4PPGMKMBAGH4";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    const MOD_PKEY: &str = "MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5";
+    let update_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+    let signed_module = test_dir_file(SUBFOLDER, "empty_s.wasm");
+    let sign = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--logging",
+            "--tag",
+            "first",
+            "--rev",
+            "1",
+            "--ver",
+            "0.1.0",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+            "--disable-keygen",
+            "--destination",
+            signed_module.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign empty module");
+    assert!(sign.status.success());
+
+    let update = wash()
+        .args(&[
+            "claims",
+            "update",
+            signed_module.to_str().unwrap(),
+            "--cap",
+            "wasmcloud:httpserver",
+            "--tag",
+            "second",
+            "--remove-tag",
+            "first",
+            "--bump-rev",
+            "--issuer",
+            ACCOUNT,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to update signed module");
+    assert!(update.status.success());
+    assert_eq!(
+        output_to_string(update),
+        format!(
+            "Successfully updated {} with capabilities: wasmcloud:logging,wasmcloud:httpserver\n",
+            signed_module.to_str().unwrap()
+        )
+    );
+
+    let inspect = wash()
+        .args(&[
+            "claims",
+            "inspect",
+            signed_module.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to inspect updated module");
+    assert!(inspect.status.success());
+    let output = output_to_string(inspect);
+    assert!(output.contains(&format!("\"module\":\"{}\"", MOD_PKEY)));
+    assert!(output.contains("\"capabilities\":[\"Logging\",\"HTTP Server\"]"));
+    assert!(output.contains("\"tags\":\"second\""));
+    assert!(output.contains("\"revision\":2"));
+    assert!(output.contains("\"version\":\"0.1.0\""));
+
+    // Re-signing with another account requires an explicit rotation
+    let update_other_issuer = wash()
+        .args(&[
+            "claims",
+            "update",
+            signed_module.to_str().unwrap(),
+            "--issuer",
+            OTHER_ACCOUNT,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to update signed module");
+    assert!(!update_other_issuer.status.success());
+
+    let rotate_issuer = wash()
+        .args(&[
+            "claims",
+            "update",
+            signed_module.to_str().unwrap(),
+            "--issuer",
+            OTHER_ACCOUNT,
+            "--rotate-issuer",
+            "--disable-keygen",
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to update signed module");
+    assert!(rotate_issuer.status.success());
+    assert!(output_to_string(rotate_issuer).contains(&format!("\"issuer\":\"{}\"", OTHER_ACC_PKEY)));
+
+    remove_dir_all(update_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_sign_claims_file() {
+    const SUBFOLDER: &str = "claims_sign_file";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    const MOD_PKEY: &str = "MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5";
+    let sign_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+    // Keys are referenced relative to the claims file
+    let mut subject_file = File::create(test_dir_file(SUBFOLDER, "module.nk")).unwrap();
+    subject_file.write_all(MODULE.as_bytes()).unwrap();
+
+    let toml_file = test_dir_file(SUBFOLDER, "actor.toml");
+    let mut claims_file = File::create(toml_file.clone()).unwrap();
+    claims_file
+        .write_all(
+            format!(
+                r#"
+name = "FromFile"
+caps = ["wasmcloud:keyvalue", "acme:custom"]
+tags = ["file"]
+rev = 2
+ver = "0.2.0"
+issuer = "{}"
+subject = "module.nk"
+disable_keygen = true
+"#,
+                ACCOUNT
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+    let signed_module = test_dir_file(SUBFOLDER, "empty_s.wasm");
+    let sign = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--claims-file",
+            toml_file.to_str().unwrap(),
+            "--rev",
+            "3",
+            "--keyvalue",
+            "--destination",
+            signed_module.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign module with claims file");
+    assert!(sign.status.success());
+    assert_eq!(
+        output_to_string(sign),
+        format!(
+            "Successfully signed {} with capabilities: wasmcloud:keyvalue,acme:custom\n",
+            signed_module.to_str().unwrap()
+        )
+    );
+
+    let inspect = wash()
+        .args(&[
+            "claims",
+            "inspect",
+            signed_module.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to inspect signed module");
+    let output = output_to_string(inspect);
+    assert!(output.contains(&format!("\"module\":\"{}\"", MOD_PKEY)));
+    assert!(output.contains("\"revision\":3"));
+    assert!(output.contains("\"version\":\"0.2.0\""));
+    assert!(output.contains("\"tags\":\"file\""));
+
+    let yaml_file = test_dir_file(SUBFOLDER, "actor.yaml");
+    let mut claims_file = File::create(yaml_file.clone()).unwrap();
+    claims_file
+        .write_all(b"caps:\n  - wasmcloud:logging\ndisable_keygen: true\n")
+        .unwrap();
+    let missing_name = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--claims-file",
+            yaml_file.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to run claims sign");
+    assert!(!missing_name.status.success());
+    assert!(String::from_utf8_lossy(&missing_name.stderr)
+        .contains("A name must be provided with --name or in the claims file"));
+
+    remove_dir_all(sign_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_sign_batch() {
+    const SUBFOLDER: &str = "claims_sign_batch";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    let batch_dir = test_dir_with_subfolder(SUBFOLDER);
+    let modules_dir = batch_dir.join("modules");
+    let keys_dir = batch_dir.join("keys");
+    let signed_dir = batch_dir.join("signed");
+    std::fs::create_dir_all(&modules_dir).unwrap();
+
+    for module in &["one.wasm", "two.wasm", "previous_s.wasm"] {
+        let mut module_file = File::create(modules_dir.join(module)).unwrap();
+        module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+    }
+    File::create(modules_dir.join("README.md")).unwrap();
+
+    let shared_name = wash()
+        .args(&[
+            "claims",
+            "sign",
+            modules_dir.to_str().unwrap(),
+            "--name",
+            "Batch",
+            "--issuer",
+            ACCOUNT,
+            "--directory",
+            keys_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to run sign with a shared name");
+    assert!(!shared_name.status.success());
+    assert!(String::from_utf8_lossy(&shared_name.stderr)
+        .contains("A name cannot be shared between multiple modules"));
+
+    let sign_dir = wash()
+        .args(&[
+            "claims",
+            "sign",
+            modules_dir.to_str().unwrap(),
+            "--logging",
+            "--issuer",
+            ACCOUNT,
+            "--directory",
+            keys_dir.to_str().unwrap(),
+            "--destination",
+            signed_dir.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to sign directory of modules");
+    assert!(sign_dir.status.success());
+    let output = output_to_string(sign_dir);
+    assert!(output.contains(&format!(
+        "{{\"capabilities\":[\"wasmcloud:logging\"],\"destination\":\"{}\",\"module\":\"{}\",\"result\":\"signed\"}}",
+        signed_dir.join("one_s.wasm").to_str().unwrap(),
+        modules_dir.join("one.wasm").to_str().unwrap()
+    )));
+    assert!(output.contains(&format!(
+        "\"module\":\"{}\",\"reason\":\"Module is the output of a previous signing\",\"result\":\"skipped\"",
+        modules_dir.join("previous_s.wasm").to_str().unwrap()
+    )));
+    assert!(!output.contains("README.md"));
+    assert!(signed_dir.join("two_s.wasm").exists());
+    // Each module is signed with its own subject key
+    assert!(keys_dir.join("one_module.nk").exists());
+    assert!(keys_dir.join("two_module.nk").exists());
+    // Each module is named after its file
+    let inspect_one = wash()
+        .args(&[
+            "claims",
+            "inspect",
+            signed_dir.join("one_s.wasm").to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to inspect batch signed module");
+    assert!(inspect_one.status.success());
+    assert!(output_to_string(inspect_one).contains("one - Module"));
+
+    let mut broken = File::create(modules_dir.join("broken.wasm")).unwrap();
+    broken.write_all(b"not a module").unwrap();
+    File::create(modules_dir.join("todo.txt")).unwrap();
+    let sign_glob = wash()
+        .args(&[
+            "claims",
+            "sign",
+            &format!("{}/*o*", modules_dir.to_str().unwrap()),
+            "--issuer",
+            ACCOUNT,
+            "--directory",
+            keys_dir.to_str().unwrap(),
+            "--destination",
+            signed_dir.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign glob of modules");
+    assert!(!sign_glob.status.success());
+    let stderr = String::from_utf8_lossy(&sign_glob.stderr);
+    assert!(stderr.contains("broken.wasm"));
+    assert!(stderr.contains("Failed"));
+    assert!(stderr.contains("two.wasm"));
+    assert!(stderr.contains("Signed"));
+    // Globs only match modules, like directories
+    assert!(!stderr.contains("todo.txt"));
+
+    remove_dir_all(batch_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_strip_extract() {
+    const SUBFOLDER: &str = "claims_strip";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    const MOD_PKEY: &str = "MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5";
+    const UNSIGNED: &[u8] = b"\0asm\x01\0\0\0";
+    let strip_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(UNSIGNED).unwrap();
+
+    let sign = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to sign empty module");
+    assert!(sign.status.success());
+    let signed_module = test_dir_file(SUBFOLDER, "empty_s.wasm");
+
+    let extract = wash()
+        .args(&[
+            "claims",
+            "extract",
+            signed_module.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to extract claims");
+    assert!(extract.status.success());
+    let token = test_dir_file(SUBFOLDER, "empty.jwt");
+    assert_eq!(
+        output_to_string(extract),
+        format!(
+            "{{\"destination\":\"{}\",\"module\":\"{}\",\"result\":\"success\"}}\n",
+            token.to_str().unwrap(),
+            MOD_PKEY
+        )
+    );
+    let inspect_token = wash()
+        .args(&["claims", "inspect", token.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("failed to inspect extracted token");
+    assert!(output_to_string(inspect_token).contains(MOD_PKEY));
+
+    let stripped = test_dir_file(SUBFOLDER, "stripped.wasm");
+    let strip = wash()
+        .args(&[
+            "claims",
+            "strip",
+            signed_module.to_str().unwrap(),
+            "--destination",
+            stripped.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to strip claims");
+    assert!(strip.status.success());
+    assert_eq!(std::fs::read(&stripped).unwrap(), UNSIGNED);
+
+    let strip_unsigned = wash()
+        .args(&["claims", "strip", stripped.to_str().unwrap()])
+        .output()
+        .expect("failed to run claims strip");
+    assert!(!strip_unsigned.status.success());
+    assert!(String::from_utf8_lossy(&strip_unsigned.stderr).contains("No claims found"));
+
+    remove_dir_all(strip_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_caps() {
+    const SUBFOLDER: &str = "claims_caps";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    let caps_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let catalog = test_dir_file(SUBFOLDER, "capabilities.toml");
+    let mut catalog_file = File::create(catalog.clone()).unwrap();
+    catalog_file
+        .write_all(
+            b"[[capability]]\nid = \"acme:payments\"\nname = \"Payments\"\naliases = [\"pay\"]\n",
+        )
+        .unwrap();
+
+    let list = wash()
+        .args(&["claims", "caps", "list", "-o", "json"])
+        .env("WASH_CAPABILITIES", catalog.to_str().unwrap())
+        .output()
+        .expect("failed to list capabilities");
+    assert!(list.status.success());
+    let output = output_to_string(list);
+    assert!(output.contains("{\"aliases\":[\"kv\",\"keyvalue\",\"key-value\",\"key_value\"],\"id\":\"wasmcloud:keyvalue\",\"name\":\"K/V Store\",\"source\":\"built-in\"}"));
+    assert!(output.contains("{\"aliases\":[\"pay\"],\"id\":\"acme:payments\",\"name\":\"Payments\",\"source\":\"user\"}"));
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+    let sign = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--cap",
+            "kv",
+            "--cap",
+            "http-server",
+            "--cap",
+            "pay",
+            "--keyvalue",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+            "--disable-keygen",
+        ])
+        .env("WASH_CAPABILITIES", catalog.to_str().unwrap())
+        .output()
+        .expect("failed to sign module with capability aliases");
+    assert!(sign.status.success());
+    assert!(output_to_string(sign)
+        .contains("with capabilities: wasmcloud:keyvalue,wasmcloud:httpserver,acme:payments"));
+
+    let typo = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--cap",
+            "wasmcloud:keyvaleu",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to run claims sign");
+    assert!(!typo.status.success());
+    assert!(String::from_utf8_lossy(&typo.stderr).contains(
+        "Unknown capability \"wasmcloud:keyvaleu\", did you mean \"wasmcloud:keyvalue\"?"
+    ));
+
+    remove_dir_all(caps_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_sign_public_keys() {
+    const SUBFOLDER: &str = "claims_sign_public_keys";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const ACC_PKEY: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+    const MOD_PKEY: &str = "MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5";
+    let sign_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+    // Only the issuer signs, so the subject can be a public key
+    let sign = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MOD_PKEY,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to sign module");
+    assert!(sign.status.success());
+    let inspect = wash()
+        .args(&[
+            "claims",
+            "inspect",
+            test_dir_file(SUBFOLDER, "empty_s.wasm").to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to inspect signed module");
+    assert!(output_to_string(inspect).contains(&format!("\"module\":\"{}\"", MOD_PKEY)));
+
+    let no_signer = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--issuer",
+            ACC_PKEY,
+            "--subject",
+            MOD_PKEY,
+            "--disable-keygen",
+        ])
+        .env_remove("WASH_SIGNER")
+        .output()
+        .expect("failed to run claims sign");
+    assert!(!no_signer.status.success());
+    assert!(
+        String::from_utf8_lossy(&no_signer.stderr).contains(&format!(
+        "Unable to sign with public key {}, provide its seed or an external signer with --signer",
+        ACC_PKEY
+    ))
+    );
+
+    let failed_signer = wash()
+        .args(&[
+            "claims",
+            "sign",
+            module.to_str().unwrap(),
+            "--name",
+            "Empty",
+            "--issuer",
+            ACC_PKEY,
+            "--subject",
+            MOD_PKEY,
+            "--signer",
+            "cat > /dev/null; echo '{\"error\": \"key is locked\"}'",
+        ])
+        .output()
+        .expect("failed to run claims sign");
+    assert!(!failed_signer.status.success());
+    assert!(String::from_utf8_lossy(&failed_signer.stderr).contains("Signer failed: key is locked"));
+
+    remove_dir_all(sign_dir).unwrap();
+}
+
+#[test]
+fn integration_claims_diff() {
+    const SUBFOLDER: &str = "claims_diff";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    let diff_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+    let sign = |destination: &str, args: &[&str]| {
+        let signed = test_dir_file(SUBFOLDER, destination);
+        let output = wash()
+            .args(&["claims", "sign", module.to_str().unwrap()])
+            .args(&["--name", "Empty", "--issuer", ACCOUNT, "--subject", MODULE])
+            .args(&[
+                "--disable-keygen",
+                "--destination",
+                signed.to_str().unwrap(),
+            ])
+            .args(args)
+            .output()
+            .expect("failed to sign empty module");
+        assert!(output.status.success());
+        signed.to_str().unwrap().to_string()
+    };
+    let v1 = sign(
+        "v1_s.wasm",
+        &[
+            "--cap",
+            "wasmcloud:httpserver",
+            "--tag",
+            "stable",
+            "--rev",
+            "1",
+        ],
+    );
+    let v2 = sign(
+        "v2_s.wasm",
+        &[
+            "--cap",
+            "wasmcloud:keyvalue",
+            "--tag",
+            "stable",
+            "--rev",
+            "2",
+        ],
+    );
+
+    let identical = wash()
+        .args(&["claims", "diff", &v1, &v1])
+        .output()
+        .expect("failed to diff modules");
+    assert!(identical.status.success());
+    assert!(
+        output_to_string(identical).contains(&format!("No differences between {} and {}", v1, v1))
+    );
+
+    let diff = wash()
+        .args(&["claims", "diff", &v1, &v2, "-o", "json"])
+        .output()
+        .expect("failed to diff modules");
+    assert!(diff.status.success());
+    assert_eq!(
+        output_to_string(diff),
+        format!(
+            "{{\"changes\":[\
+             {{\"field\":\"revision\",\"left\":\"1\",\"right\":\"2\"}},\
+             {{\"added\":[\"wasmcloud:keyvalue\"],\"field\":\"capabilities\",\"removed\":[\"wasmcloud:httpserver\"]}}],\
+             \"identical\":false,\"left\":\"{}\",\"right\":\"{}\"}}\n",
+            v1, v2
+        )
+    );
+
+    let table = wash()
+        .args(&["claims", "diff", &v1, &v2])
+        .output()
+        .expect("failed to diff modules");
+    let table = output_to_string(table);
+    assert!(table.contains("- wasmcloud:httpserver"));
+    assert!(table.contains("+ wasmcloud:keyvalue"));
+
+    remove_dir_all(diff_dir).unwrap();
+}
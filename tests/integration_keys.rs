@@ -1,6 +1,6 @@
 mod common;
 use common::{output_to_string, test_dir_file, test_dir_with_subfolder, wash};
-use std::fs::{remove_dir_all, File};
+use std::fs::{read_to_string, remove_dir_all, File};
 use std::io::prelude::*;
 
 #[test]
@@ -169,3 +169,502 @@ fn integration_list_comprehensive() {
 
     remove_dir_all(list_comprehensive_dir).unwrap();
 }
+
+#[test]
+#[cfg(unix)]
+fn integration_keys_encrypted_keystore() {
+    use std::os::unix::fs::PermissionsExt;
+
+    const SEED: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const PASSPHRASE: &str = "correct horse battery staple";
+    const TESTDIR: &str = "integration_keys_encrypted";
+
+    let keys_dir = test_dir_with_subfolder(TESTDIR);
+    let key_file = test_dir_file(TESTDIR, "issuer_account.nk");
+
+    let import = wash()
+        .args(&[
+            "keys",
+            "import",
+            SEED,
+            "--name",
+            "issuer",
+            "--encrypt",
+            "-d",
+            keys_dir.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .env("WASH_KEYS_PASSPHRASE", PASSPHRASE)
+        .output()
+        .expect("failed to import key");
+    assert!(import.status.success());
+    let contents = read_to_string(&key_file).unwrap();
+    assert!(!contents.contains(SEED));
+    assert_eq!(
+        key_file.metadata().unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+
+    // Importing over an existing key requires --force
+    let duplicate = wash()
+        .args(&["keys", "import", SEED, "--name", "issuer"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .output()
+        .expect("failed to import key");
+    assert!(!duplicate.status.success());
+
+    // Encrypted keys are unlocked transparently with the passphrase
+    let get = wash()
+        .args(&["keys", "get", "issuer_account.nk"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", PASSPHRASE)
+        .output()
+        .expect("failed to get key");
+    assert!(get.status.success());
+    assert_eq!(output_to_string(get).trim(), SEED);
+
+    let wrong = wash()
+        .args(&["keys", "export", "issuer_account.nk"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", "wrong")
+        .output()
+        .expect("failed to export key");
+    assert!(!wrong.status.success());
+
+    let sign = wash()
+        .args(&[
+            "claims",
+            "token",
+            "account",
+            "--name",
+            "encrypted",
+            "--subject",
+            key_file.to_str().unwrap(),
+            "--directory",
+            keys_dir.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .env("WASH_KEYS_PASSPHRASE", PASSPHRASE)
+        .output()
+        .expect("failed to sign with encrypted key");
+    assert!(sign.status.success());
+    // Keys generated while a passphrase is set are encrypted too
+    let generated = test_dir_file(TESTDIR, "encrypted_operator.nk");
+    assert!(read_to_string(&generated).unwrap().starts_with('{'));
+    assert_eq!(
+        generated.metadata().unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+
+    let decrypt = wash()
+        .args(&["keys", "decrypt", "issuer_account.nk"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", PASSPHRASE)
+        .output()
+        .expect("failed to decrypt key");
+    assert!(decrypt.status.success());
+    assert_eq!(read_to_string(&key_file).unwrap().trim(), SEED);
+    assert_eq!(
+        key_file.metadata().unwrap().permissions().mode() & 0o777,
+        0o600
+    );
+
+    let encrypt = wash()
+        .args(&["keys", "encrypt", "issuer_account.nk"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", PASSPHRASE)
+        .output()
+        .expect("failed to encrypt key");
+    assert!(encrypt.status.success());
+    assert!(!read_to_string(&key_file).unwrap().contains(SEED));
+
+    let export_file = test_dir_file(TESTDIR, "exported.nk");
+    let export = wash()
+        .args(&["keys", "export", "issuer_account.nk"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .args(&["--destination", export_file.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", PASSPHRASE)
+        .output()
+        .expect("failed to export key");
+    assert!(export.status.success());
+    assert_eq!(read_to_string(&export_file).unwrap(), SEED);
+
+    remove_dir_all(keys_dir).unwrap();
+}
+
+#[test]
+fn integration_keys_gen_named() {
+    const TESTDIR: &str = "integration_keys_gen_named";
+
+    let keys_dir = test_dir_with_subfolder(TESTDIR);
+    let gen_args = [
+        "keys",
+        "gen",
+        "module",
+        "--name",
+        "echo",
+        "-d",
+        keys_dir.to_str().unwrap(),
+        "-o",
+        "json",
+    ];
+
+    let gen = wash()
+        .args(&gen_args)
+        .env_remove("WASH_KEYS_PASSPHRASE")
+        .output()
+        .expect("failed to generate named key");
+    assert!(gen.status.success());
+    let output: serde_json::Value = serde_json::from_str(&output_to_string(gen)).unwrap();
+    let key_file = test_dir_file(TESTDIR, "echo_module.nk");
+    assert_eq!(output["path"], key_file.to_str().unwrap());
+    let public_key = output["public_key"].as_str().unwrap().to_string();
+    assert!(public_key.starts_with('M'));
+    assert!(output.get("seed").is_none());
+    assert!(read_to_string(&key_file).unwrap().starts_with("SM"));
+
+    // Existing keys are only replaced with --force
+    let again = wash()
+        .args(&gen_args)
+        .output()
+        .expect("failed to generate named key");
+    assert!(!again.status.success());
+    assert!(read_to_string(&key_file).unwrap().starts_with("SM"));
+
+    let forced = wash()
+        .args(&gen_args)
+        .arg("--force")
+        .env_remove("WASH_KEYS_PASSPHRASE")
+        .output()
+        .expect("failed to generate named key");
+    assert!(forced.status.success());
+    let output: serde_json::Value = serde_json::from_str(&output_to_string(forced)).unwrap();
+    assert_ne!(output["public_key"], public_key.as_str());
+
+    // The key directory from $WASH_KEYS is only used for named keys
+    let printed = wash()
+        .args(&["keys", "gen", "module", "-o", "json"])
+        .env("WASH_KEYS", &keys_dir)
+        .output()
+        .expect("failed to generate key");
+    assert!(printed.status.success());
+    let output: serde_json::Value = serde_json::from_str(&output_to_string(printed)).unwrap();
+    assert!(output["seed"].as_str().unwrap().starts_with("SM"));
+
+    let from_env = wash()
+        .args(&["keys", "gen", "account", "--name", "env", "-o", "json"])
+        .env("WASH_KEYS", &keys_dir)
+        .env_remove("WASH_KEYS_PASSPHRASE")
+        .output()
+        .expect("failed to generate named key");
+    assert!(from_env.status.success());
+    assert!(read_to_string(test_dir_file(TESTDIR, "env_account.nk"))
+        .unwrap()
+        .starts_with("SA"));
+
+    remove_dir_all(keys_dir).unwrap();
+}
+
+#[test]
+fn integration_keys_list_types() {
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const ACCOUNT_PK: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+    const TESTDIR: &str = "integration_keys_list_types";
+
+    let keys_dir = test_dir_with_subfolder(TESTDIR);
+    let import = wash()
+        .args(&["keys", "import", ACCOUNT, "--name", "issuer", "--encrypt"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", "passphrase")
+        .output()
+        .expect("failed to import key");
+    assert!(import.status.success());
+    let gen = wash()
+        .args(&["keys", "gen", "module", "--name", "echo"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .env_remove("WASH_KEYS_PASSPHRASE")
+        .output()
+        .expect("failed to generate key");
+    assert!(gen.status.success());
+    let mut file = File::create(test_dir_file(TESTDIR, "broken.nk")).unwrap();
+    file.write_all(b"not a seed").unwrap();
+
+    let list = wash()
+        .args(&[
+            "keys",
+            "list",
+            "-d",
+            keys_dir.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .env_remove("WASH_KEYS_PASSPHRASE")
+        .output()
+        .expect("failed to list keys");
+    assert!(list.status.success());
+    let output: serde_json::Value = serde_json::from_str(&output_to_string(list)).unwrap();
+    let keys = output["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 3);
+    assert_eq!(keys[0]["name"], "broken.nk");
+    assert!(keys[0]["error"].is_string());
+    assert_eq!(keys[1]["name"], "echo_module.nk");
+    assert_eq!(keys[1]["type"], "module");
+    assert_eq!(keys[1]["encrypted"], false);
+    assert_eq!(keys[2]["name"], "issuer_account.nk");
+    assert_eq!(keys[2]["type"], "account");
+    assert_eq!(keys[2]["public_key"], ACCOUNT_PK);
+    assert_eq!(keys[2]["encrypted"], true);
+    assert_eq!(
+        keys[2]["path"],
+        test_dir_file(TESTDIR, "issuer_account.nk")
+            .to_str()
+            .unwrap()
+    );
+
+    let filtered = wash()
+        .args(&["keys", "list", "-d", keys_dir.to_str().unwrap()])
+        .args(&["--type", "account"])
+        .output()
+        .expect("failed to list keys");
+    assert!(filtered.status.success());
+    let output = output_to_string(filtered);
+    assert!(output.contains(ACCOUNT_PK));
+    assert!(output.contains("account (encrypted)"));
+    assert!(!output.contains("echo_module.nk"));
+    assert!(!output.contains("broken.nk"));
+
+    remove_dir_all(keys_dir).unwrap();
+}
+
+#[test]
+fn integration_keys_rotate() {
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const ACCOUNT_PK: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+    const OPERATOR: &str = "SOALHM3VR5GETKJ6OKVW2TV7ZBVNYCARJKXKGFW3E4VYU3TR7MDYKNCVVU";
+    const TESTDIR: &str = "integration_keys_rotate";
+
+    let rotate_dir = test_dir_with_subfolder(TESTDIR);
+    let keys_dir = rotate_dir.join("keys");
+    let artifacts_dir = rotate_dir.join("artifacts");
+    std::fs::create_dir_all(&artifacts_dir).unwrap();
+    let key_file = keys_dir.join("issuer_account.nk");
+
+    let import = wash()
+        .args(&["keys", "import", ACCOUNT, "--name", "issuer"])
+        .args(&["-d", keys_dir.to_str().unwrap()])
+        .output()
+        .expect("failed to import key");
+    assert!(import.status.success());
+
+    let token = wash()
+        .args(&["claims", "token", "account", "--name", "Account"])
+        .args(&[
+            "--issuer",
+            OPERATOR,
+            "--subject",
+            ACCOUNT,
+            "--disable-keygen",
+        ])
+        .output()
+        .expect("failed to generate account token");
+    assert!(token.status.success());
+    let token_file = rotate_dir.join("account.jwt");
+    let mut file = File::create(&token_file).unwrap();
+    file.write_all(output_to_string(token).trim().as_bytes())
+        .unwrap();
+
+    // One actor and one provider issued by the rotated key, and one actor issued by another account
+    for (module, issuer) in &[("echo", Some(ACCOUNT)), ("other", None)] {
+        let unsigned = artifacts_dir.join(format!("{}.wasm", module));
+        let mut module_file = File::create(&unsigned).unwrap();
+        module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+        let mut sign = wash();
+        sign.args(&[
+            "claims",
+            "sign",
+            unsigned.to_str().unwrap(),
+            "--name",
+            module,
+        ])
+        .args(&["--logging", "--directory", keys_dir.to_str().unwrap()]);
+        if let Some(issuer) = issuer {
+            sign.args(&["--issuer", issuer]);
+        }
+        let sign = sign.output().expect("failed to sign module");
+        assert!(sign.status.success());
+    }
+    let binary = rotate_dir.join("linux.so");
+    let mut bin_file = File::create(&binary).unwrap();
+    bin_file.write_all(b"01100010 01110100 01110111").unwrap();
+    let archive = artifacts_dir.join("provider.par");
+    let create = wash()
+        .args(&["par", "create", "-a", "x86_64-linux"])
+        .args(&["-b", binary.to_str().unwrap(), "-c", "wasmcloud:testing"])
+        .args(&["-n", "Provider", "-v", "Test", "--issuer", ACCOUNT])
+        .args(&["--directory", keys_dir.to_str().unwrap()])
+        .args(&["--destination", archive.to_str().unwrap()])
+        .output()
+        .expect("failed to create provider archive");
+    assert!(create.status.success());
+
+    let rotate_args = [
+        "keys",
+        "rotate",
+        "issuer_account.nk",
+        "-d",
+        keys_dir.to_str().unwrap(),
+        "--token",
+        token_file.to_str().unwrap(),
+        "--resign",
+        artifacts_dir.to_str().unwrap(),
+        "-o",
+        "json",
+    ];
+
+    // The account token is issued by the operator, so nothing is changed without its key
+    let missing_issuer = wash()
+        .args(&rotate_args)
+        .output()
+        .expect("failed to rotate key");
+    assert!(!missing_issuer.status.success());
+    assert_eq!(read_to_string(&key_file).unwrap(), ACCOUNT);
+
+    let rotate = wash()
+        .args(&rotate_args)
+        .args(&["--token-issuer", OPERATOR])
+        .output()
+        .expect("failed to rotate key");
+    assert!(rotate.status.success());
+    let output: serde_json::Value = serde_json::from_str(&output_to_string(rotate)).unwrap();
+    assert_eq!(output["previous_public_key"], ACCOUNT_PK);
+    let new_pk = output["public_key"].as_str().unwrap().to_string();
+    assert!(new_pk.starts_with('A'));
+    assert_ne!(new_pk, ACCOUNT_PK);
+    assert_eq!(
+        output["resigned"],
+        serde_json::json!([
+            artifacts_dir.join("echo_s.wasm").to_str().unwrap(),
+            archive.to_str().unwrap()
+        ])
+    );
+
+    // The old key is kept, the new key takes its place
+    assert_eq!(
+        read_to_string(output["backup"].as_str().unwrap()).unwrap(),
+        ACCOUNT
+    );
+    assert!(read_to_string(&key_file).unwrap().starts_with("SA"));
+    assert_ne!(read_to_string(&key_file).unwrap(), ACCOUNT);
+
+    let inspect_token = wash()
+        .args(&[
+            "claims",
+            "inspect",
+            token_file.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("failed to inspect token");
+    let inspect_token = output_to_string(inspect_token);
+    assert!(inspect_token.contains(&format!("\"account\":\"{}\"", ACCOUNT_PK)));
+    assert!(inspect_token.contains(&format!("\"additional_signing_keys\":[\"{}\"]", new_pk)));
+
+    let inspect_actor = |module: &str| {
+        let inspect = wash()
+            .args(&["claims", "inspect"])
+            .arg(artifacts_dir.join(module))
+            .args(&["-o", "json"])
+            .output()
+            .expect("failed to inspect module");
+        assert!(inspect.status.success());
+        output_to_string(inspect)
+    };
+    assert!(inspect_actor("echo_s.wasm").contains(&new_pk));
+    assert!(!inspect_actor("other_s.wasm").contains(&new_pk));
+
+    let inspect_par = wash()
+        .args(&["par", "inspect", archive.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("failed to inspect provider archive");
+    assert!(inspect_par.status.success());
+    assert!(output_to_string(inspect_par).contains(&new_pk));
+
+    remove_dir_all(rotate_dir).unwrap();
+}
+
+#[test]
+fn integration_keys_inspect() {
+    const SEED: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const PUBLIC_KEY: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+    const TESTDIR: &str = "integration_keys_inspect";
+
+    let inspect_dir = test_dir_with_subfolder(TESTDIR);
+
+    let inspect = wash()
+        .args(&["keys", "inspect", SEED])
+        .output()
+        .expect("failed to inspect seed");
+    assert!(inspect.status.success());
+    let output = output_to_string(inspect);
+    assert!(output.contains(&format!("Public Key: {}", PUBLIC_KEY)));
+    assert!(output.contains("Type: account"));
+
+    // Encrypted key files are unlocked to derive the public key
+    let import = wash()
+        .args(&["keys", "import", SEED, "--name", "inspect", "--encrypt"])
+        .args(&["-d", inspect_dir.to_str().unwrap()])
+        .env("WASH_KEYS_PASSPHRASE", "passphrase")
+        .output()
+        .expect("failed to import key");
+    assert!(import.status.success());
+    let key_file = test_dir_file(TESTDIR, "inspect_account.nk");
+    let sign = wash()
+        .args(&["keys", "inspect", key_file.to_str().unwrap()])
+        .args(&["--payload", "prove it", "-o", "json"])
+        .env("WASH_KEYS_PASSPHRASE", "passphrase")
+        .output()
+        .expect("failed to sign payload");
+    assert!(sign.status.success());
+    let output: serde_json::Value = serde_json::from_str(&output_to_string(sign)).unwrap();
+    assert_eq!(output["public_key"], PUBLIC_KEY);
+    assert_eq!(output["type"], "account");
+    assert_eq!(output["verified"], true);
+    let signature = output["signature"].as_str().unwrap().to_string();
+
+    // Signatures are verified with only the public key
+    let verify = wash()
+        .args(&["keys", "inspect", PUBLIC_KEY, "--payload", "prove it"])
+        .args(&["--signature", &signature])
+        .output()
+        .expect("failed to verify signature");
+    assert!(verify.status.success());
+    assert!(output_to_string(verify).contains("Verified: true"));
+
+    let tampered = wash()
+        .args(&["keys", "inspect", PUBLIC_KEY, "--payload", "prove that"])
+        .args(&["--signature", &signature])
+        .output()
+        .expect("failed to verify signature");
+    assert!(!tampered.status.success());
+
+    let public_sign = wash()
+        .args(&["keys", "inspect", PUBLIC_KEY, "--payload", "prove it"])
+        .output()
+        .expect("failed to sign payload");
+    assert!(!public_sign.status.success());
+
+    let invalid = wash()
+        .args(&[
+            "keys",
+            "inspect",
+            "SAADC2FGJRMZCTYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE",
+        ])
+        .output()
+        .expect("failed to inspect seed");
+    assert!(!invalid.status.success());
+
+    remove_dir_all(inspect_dir).unwrap();
+}
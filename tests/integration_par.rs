@@ -1,6 +1,6 @@
 mod common;
 use common::{output_to_string, test_dir_file, test_dir_with_subfolder, wash};
-use std::fs::{remove_dir_all, File};
+use std::fs::{create_dir_all, remove_dir_all, File};
 use std::io::prelude::*;
 
 #[test]
@@ -81,6 +81,29 @@ fn integration_par_create(issuer: &str, subject: &str, archive: &str) {
     assert!(output.contains("\"vendor\":\"TestRunner\""));
     assert!(output.contains("\"ver\":\"3.2.1\""));
 
+    let missing_dir = bin_folder.join("missing").join("test.par");
+    let create_missing = wash()
+        .args(&["par", "create", "-a", ARCH, "-b", binary.to_str().unwrap()])
+        .args(&[
+            "-c",
+            "wasmcloud:testing",
+            "-n",
+            "Test parJEEzy",
+            "-v",
+            "TestRunner",
+        ])
+        .args(&["--issuer", issuer, "--subject", subject, "--disable-keygen"])
+        .args(&["--destination", missing_dir.to_str().unwrap()])
+        .output()
+        .expect("failed to run wash");
+    assert!(!create_missing.status.success());
+    assert!(
+        String::from_utf8_lossy(&create_missing.stderr).contains(&format!(
+            "Please ensure directory {:?} exists",
+            bin_folder.join("missing")
+        ))
+    );
+
     remove_dir_all(bin_folder).unwrap();
 }
 
@@ -301,3 +324,595 @@ fn integration_par_inspect() {
 
     remove_dir_all(inspect_dir).unwrap();
 }
+
+#[test]
+fn integration_par_extract() {
+    const SUBFOLDER: &str = "par_extract";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    const LINUX: &[u8] = b"01100010 01110100 01110111";
+    const MACOS: &[u8] = b"01101001 01101111 01110011";
+    let extract_dir = test_dir_with_subfolder(SUBFOLDER);
+    let archive = test_dir_file(SUBFOLDER, "extract.par.gz");
+
+    let linux = test_dir_file(SUBFOLDER, "linux.so");
+    File::create(&linux).unwrap().write_all(LINUX).unwrap();
+    let macos = test_dir_file(SUBFOLDER, "macos.dylib");
+    File::create(&macos).unwrap().write_all(MACOS).unwrap();
+
+    let create = wash()
+        .args(&["par", "create", "-a", "x86_64-linux"])
+        .args(&["-b", linux.to_str().unwrap()])
+        .args(&["-c", "wasmcloud:testing", "-n", "Extract", "-v", "Test"])
+        .args(&["--issuer", ISSUER, "--subject", SUBJECT, "--disable-keygen"])
+        .args(&["--compress", "--destination"])
+        .arg(test_dir_file(SUBFOLDER, "extract.par"))
+        .output()
+        .expect("failed to create provider archive");
+    assert!(create.status.success());
+    let insert = wash()
+        .args(&[
+            "par",
+            "insert",
+            archive.to_str().unwrap(),
+            "-a",
+            "x86_64-macos",
+        ])
+        .args(&["-b", macos.to_str().unwrap()])
+        .args(&["-i", ISSUER, "-s", SUBJECT, "--disable-keygen"])
+        .output()
+        .expect("failed to insert binary into provider archive");
+    assert!(insert.status.success());
+
+    let single = test_dir_file(SUBFOLDER, "extracted.so");
+    let extract = wash()
+        .args(&["par", "extract", archive.to_str().unwrap()])
+        .args(&["--arch", "x86_64-linux", "--destination"])
+        .arg(&single)
+        .output()
+        .expect("failed to extract binary");
+    assert!(extract.status.success());
+    assert_eq!(
+        output_to_string(extract),
+        format!("Extracted x86_64-linux to {}\n", single.to_str().unwrap())
+    );
+    assert_eq!(std::fs::read(&single).unwrap(), LINUX);
+
+    let all_dir = extract_dir.join("all");
+    let extract_all = wash()
+        .args(&["par", "extract", archive.to_str().unwrap(), "--all"])
+        .args(&["--destination", all_dir.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("failed to extract binaries");
+    assert!(extract_all.status.success());
+    let output = output_to_string(extract_all);
+    assert!(output.contains("\"result\":\"success\""));
+    assert!(output.contains(&format!(
+        "\"x86_64-macos\":\"{}\"",
+        all_dir.join("x86_64-macos.bin").to_str().unwrap()
+    )));
+    assert_eq!(
+        std::fs::read(all_dir.join("x86_64-linux.bin")).unwrap(),
+        LINUX
+    );
+    assert_eq!(
+        std::fs::read(all_dir.join("x86_64-macos.bin")).unwrap(),
+        MACOS
+    );
+
+    let missing = wash()
+        .args(&["par", "extract", archive.to_str().unwrap()])
+        .args(&["--arch", "aarch64-linux"])
+        .output()
+        .expect("failed to extract binary");
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr)
+        .contains("available targets are: x86_64-linux, x86_64-macos"));
+
+    remove_dir_all(extract_dir).unwrap();
+}
+
+#[test]
+fn integration_par_remove() {
+    const SUBFOLDER: &str = "par_remove";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    const OTHER_ISSUER: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    let remove_dir = test_dir_with_subfolder(SUBFOLDER);
+
+    let linux = test_dir_file(SUBFOLDER, "linux.so");
+    File::create(&linux)
+        .unwrap()
+        .write_all(b"01100010 01110100 01110111")
+        .unwrap();
+    let arm = test_dir_file(SUBFOLDER, "arm.so");
+    File::create(&arm)
+        .unwrap()
+        .write_all(b"01100001 01110010 01101101")
+        .unwrap();
+
+    for (archive, compress) in &[("remove.par", false), ("remove.par.gz", true)] {
+        let archive = test_dir_file(SUBFOLDER, archive);
+        let mut create = wash();
+        create
+            .args(&["par", "create", "-a", "x86_64-linux"])
+            .args(&["-b", linux.to_str().unwrap()])
+            .args(&["-c", "wasmcloud:testing", "-n", "Remove", "-v", "Test"])
+            .args(&["--issuer", ISSUER, "--subject", SUBJECT, "--disable-keygen"])
+            .args(&["--destination", archive.to_str().unwrap()]);
+        if *compress {
+            create.arg("--compress");
+        }
+        assert!(create.output().unwrap().status.success());
+        let insert = wash()
+            .args(&[
+                "par",
+                "insert",
+                archive.to_str().unwrap(),
+                "-a",
+                "aarch64-linux",
+            ])
+            .args(&["-b", arm.to_str().unwrap()])
+            .args(&["-i", ISSUER, "-s", SUBJECT, "--disable-keygen"])
+            .output()
+            .expect("failed to insert binary into provider archive");
+        assert!(insert.status.success());
+        let service = |archive: &std::path::Path| {
+            let inspect = wash()
+                .args(&["par", "inspect", archive.to_str().unwrap(), "-o", "json"])
+                .output()
+                .expect("failed to inspect provider archive");
+            let output: serde_json::Value =
+                serde_json::from_str(&output_to_string(inspect)).unwrap();
+            output["service"].as_str().unwrap().to_string()
+        };
+        let original_service = service(&archive);
+
+        // Re-signing with another account must be requested explicitly
+        let other_issuer = wash()
+            .args(&[
+                "par",
+                "remove",
+                archive.to_str().unwrap(),
+                "-a",
+                "aarch64-linux",
+            ])
+            .args(&["-i", OTHER_ISSUER, "--disable-keygen"])
+            .output()
+            .expect("failed to remove binary from provider archive");
+        assert!(!other_issuer.status.success());
+        assert!(String::from_utf8_lossy(&other_issuer.stderr)
+            .contains("Use --rotate-issuer to re-sign the archive with a different account"));
+
+        // Without a subject key the archive keeps its service identity
+        let remove = wash()
+            .args(&[
+                "par",
+                "remove",
+                archive.to_str().unwrap(),
+                "-a",
+                "aarch64-linux",
+            ])
+            .args(&["-i", ISSUER, "--disable-keygen"])
+            .output()
+            .expect("failed to remove binary from provider archive");
+        assert!(remove.status.success());
+        assert_eq!(
+            output_to_string(remove),
+            format!(
+                "Successfully removed aarch64-linux from archive {}\n",
+                archive.to_str().unwrap()
+            )
+        );
+
+        // Compression of the original archive is preserved
+        let bytes = std::fs::read(&archive).unwrap();
+        assert_eq!(bytes[0..2] == [0x1f, 0x8b], *compress);
+
+        let inspect = wash()
+            .args(&["par", "inspect", archive.to_str().unwrap(), "-o", "json"])
+            .output()
+            .expect("failed to inspect provider archive");
+        assert!(inspect.status.success());
+        let output = output_to_string(inspect);
+        assert!(output.contains("\"targets\":[\"x86_64-linux\"]"));
+        assert!(output.contains("\"name\":\"Remove\""));
+        assert!(output.contains("\"capability_contract_id\":\"wasmcloud:testing\""));
+        assert_eq!(service(&archive), original_service);
+
+        // The last binary of an archive cannot be removed
+        let remove_last = wash()
+            .args(&[
+                "par",
+                "remove",
+                archive.to_str().unwrap(),
+                "-a",
+                "x86_64-linux",
+            ])
+            .args(&["-i", ISSUER, "-s", SUBJECT, "--disable-keygen"])
+            .output()
+            .expect("failed to remove binary from provider archive");
+        assert!(!remove_last.status.success());
+
+        let remove_missing = wash()
+            .args(&[
+                "par",
+                "remove",
+                archive.to_str().unwrap(),
+                "-a",
+                "aarch64-linux",
+            ])
+            .args(&["-i", ISSUER, "-s", SUBJECT, "--disable-keygen"])
+            .output()
+            .expect("failed to remove binary from provider archive");
+        assert!(!remove_missing.status.success());
+    }
+
+    remove_dir_all(remove_dir).unwrap();
+}
+
+#[test]
+fn integration_par_detect_arch() {
+    const SUBFOLDER: &str = "par_detect_arch";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    let detect_dir = test_dir_with_subfolder(SUBFOLDER);
+    let archive = test_dir_file(SUBFOLDER, "detect.par");
+    let host_target = format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS);
+
+    // The headers of this test binary are enough to detect the host target
+    let exe = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+    let binary = test_dir_file(SUBFOLDER, "native.bin");
+    File::create(&binary)
+        .unwrap()
+        .write_all(&exe[..exe.len().min(4096)])
+        .unwrap();
+
+    let create = |arch: Option<&str>| {
+        let mut create = wash();
+        create
+            .args(&["par", "create", "-b", binary.to_str().unwrap()])
+            .args(&["-c", "wasmcloud:testing", "-n", "Detect", "-v", "Test"])
+            .args(&["--issuer", ISSUER, "--subject", SUBJECT, "--disable-keygen"])
+            .args(&["--destination", archive.to_str().unwrap()]);
+        if let Some(arch) = arch {
+            create.args(&["--arch", arch]);
+        }
+        create.output().expect("failed to create provider archive")
+    };
+
+    assert!(create(None).status.success());
+    let inspect = wash()
+        .args(&["par", "inspect", archive.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("failed to inspect provider archive");
+    assert!(output_to_string(inspect).contains(&format!("\"targets\":[\"{}\"]", host_target)));
+
+    assert!(create(Some(&host_target)).status.success());
+
+    let mismatch = create(Some("sparc64-solaris"));
+    assert!(!mismatch.status.success());
+    assert!(String::from_utf8_lossy(&mismatch.stderr).contains(&format!(
+        "is a {} binary, but was provided as sparc64-solaris",
+        host_target
+    )));
+
+    let typo = create(Some("x86_64-linx"));
+    assert!(!typo.status.success());
+    assert!(String::from_utf8_lossy(&typo.stderr)
+        .contains("Unknown target x86_64-linx, did you mean x86_64-linux?"));
+
+    remove_dir_all(detect_dir).unwrap();
+}
+
+#[test]
+fn integration_par_create_multi() {
+    const SUBFOLDER: &str = "par_create_multi";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    let multi_dir = test_dir_with_subfolder(SUBFOLDER);
+    let archive = test_dir_file(SUBFOLDER, "multi.par");
+
+    // Binaries laid out like the output of `cargo build --target <triple> --release`
+    let build = multi_dir.join("target");
+    let linux = build.join("x86_64-unknown-linux-gnu").join("release");
+    let macos = build.join("aarch64-apple-darwin").join("release");
+    for dir in &[&linux, &macos, &build.join("release")] {
+        create_dir_all(dir).unwrap();
+    }
+    std::fs::write(linux.join("libtest.so"), b"linux").unwrap();
+    std::fs::write(linux.join("libtest.d"), b"deps").unwrap();
+    std::fs::write(macos.join("libtest.dylib"), b"macos").unwrap();
+    let windows = test_dir_file(SUBFOLDER, "test.dll");
+    std::fs::write(&windows, b"windows").unwrap();
+
+    let create = |binaries: &[String]| {
+        let mut create = wash();
+        create
+            .args(&["par", "create", "-c", "wasmcloud:testing", "-n", "Multi"])
+            .args(&["-v", "Test", "--issuer", ISSUER, "--subject", SUBJECT])
+            .args(&["--disable-keygen", "-o", "json"])
+            .args(&["--destination", archive.to_str().unwrap()]);
+        for binary in binaries {
+            create.args(&["--binary", binary]);
+        }
+        create.output().expect("failed to create provider archive")
+    };
+
+    let multi = create(&[
+        build.to_str().unwrap().to_string(),
+        format!("x86_64-windows={}", windows.to_str().unwrap()),
+    ]);
+    assert!(multi.status.success());
+    assert_eq!(
+        output_to_string(multi),
+        format!(
+            "{{\"file\":\"{}\",\"result\":\"success\",\"targets\":[\"aarch64-macos\",\"x86_64-linux\",\"x86_64-windows\"]}}\n",
+            archive.to_str().unwrap()
+        )
+    );
+
+    let extracted = test_dir_file(SUBFOLDER, "extracted.bin");
+    for (target, contents) in &[
+        ("aarch64-macos", "macos"),
+        ("x86_64-linux", "linux"),
+        ("x86_64-windows", "windows"),
+    ] {
+        let extract = wash()
+            .args(&["par", "extract", archive.to_str().unwrap(), "-a", target])
+            .args(&["--destination", extracted.to_str().unwrap()])
+            .output()
+            .expect("failed to extract from provider archive");
+        assert!(extract.status.success());
+        assert_eq!(std::fs::read_to_string(&extracted).unwrap(), *contents);
+    }
+
+    let duplicate = create(&[
+        build.to_str().unwrap().to_string(),
+        format!("x86_64-linux={}", windows.to_str().unwrap()),
+    ]);
+    assert!(!duplicate.status.success());
+    assert!(String::from_utf8_lossy(&duplicate.stderr)
+        .contains("Multiple binaries provided for target x86_64-linux"));
+
+    let ambiguous = wash()
+        .args(&["par", "create", "-c", "wasmcloud:testing", "-n", "Multi"])
+        .args(&["-v", "Test", "--disable-keygen", "--arch", "x86_64-linux"])
+        .args(&["-b", build.to_str().unwrap()])
+        .output()
+        .expect("failed to create provider archive");
+    assert!(!ambiguous.status.success());
+    assert!(String::from_utf8_lossy(&ambiguous.stderr)
+        .contains("--arch can only be used with a single binary path"));
+
+    remove_dir_all(multi_dir).unwrap();
+}
+
+#[test]
+fn integration_par_verify() {
+    const SUBFOLDER: &str = "par_verify";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const ISSUER_PUBLIC: &str = "AA7R5L74E45BJ4XVUYTELQ56P5VCOSPOAA474L7QWH4ZAILLKTZFWYYW";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    const UNTRUSTED: &str = "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS";
+    let verify_dir = test_dir_with_subfolder(SUBFOLDER);
+    let archive = test_dir_file(SUBFOLDER, "verify.par");
+    let binary = test_dir_file(SUBFOLDER, "libverify.so");
+    std::fs::write(&binary, b"original provider binary").unwrap();
+
+    let create = wash()
+        .args(&["par", "create", "-b", binary.to_str().unwrap()])
+        .args(&["--arch", "x86_64-linux", "-c", "wasmcloud:testing"])
+        .args(&["-n", "Verify", "-v", "Test", "--disable-keygen"])
+        .args(&["--issuer", ISSUER, "--subject", SUBJECT])
+        .args(&["--destination", archive.to_str().unwrap()])
+        .output()
+        .expect("failed to create provider archive");
+    assert!(create.status.success());
+
+    let verify = |args: &[&str]| {
+        wash()
+            .args(&["par", "verify", archive.to_str().unwrap()])
+            .args(args)
+            .output()
+            .expect("failed to verify provider archive")
+    };
+
+    let valid = verify(&["--trusted-issuer", ISSUER_PUBLIC]);
+    assert!(valid.status.success());
+    assert!(output_to_string(valid).contains(&format!(
+        "Verified provider archive {} issued by {} with targets x86_64-linux",
+        archive.to_str().unwrap(),
+        ISSUER_PUBLIC
+    )));
+
+    let untrusted = verify(&["--trusted-issuer", UNTRUSTED, "-o", "json"]);
+    assert!(!untrusted.status.success());
+    assert!(output_to_string(untrusted).contains(&format!(
+        "{{\"check\":\"issuer\",\"message\":\"Issuer {} is not a trusted issuer\"}}",
+        ISSUER_PUBLIC
+    )));
+
+    // Swap the binary for one of the same length, leaving the tar headers intact
+    let contents = std::fs::read(&archive).unwrap();
+    let original: &[u8] = b"original provider binary";
+    let offset = contents
+        .windows(original.len())
+        .position(|window| window == original)
+        .unwrap();
+    let mut tampered = contents.clone();
+    tampered[offset..offset + original.len()].copy_from_slice(b"modified provider binary");
+    std::fs::write(&archive, tampered).unwrap();
+
+    let invalid = verify(&[]);
+    assert!(!invalid.status.success());
+    assert!(output_to_string(invalid)
+        .contains("hashes: Binary for x86_64-linux does not match its hash"));
+
+    remove_dir_all(verify_dir).unwrap();
+}
+
+#[test]
+fn integration_par_update() {
+    const SUBFOLDER: &str = "par_update";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    let update_dir = test_dir_with_subfolder(SUBFOLDER);
+    let binary = test_dir_file(SUBFOLDER, "libupdate.so");
+    std::fs::write(&binary, b"provider binary").unwrap();
+
+    for archive in &[
+        test_dir_file(SUBFOLDER, "update.par"),
+        test_dir_file(SUBFOLDER, "update.par.gz"),
+    ] {
+        let mut create = wash();
+        create
+            .args(&["par", "create", "-b", binary.to_str().unwrap()])
+            .args(&["--arch", "x86_64-linux", "-c", "wasmcloud:testing"])
+            .args(&[
+                "-n",
+                "Update",
+                "-v",
+                "Test",
+                "-r",
+                "1",
+                "--version",
+                "1.0.0",
+            ])
+            .args(&["--issuer", ISSUER, "--subject", SUBJECT, "--disable-keygen"])
+            .args(&["--destination", archive.to_str().unwrap()]);
+        if archive.to_str().unwrap().ends_with(".gz") {
+            create.arg("--compress");
+        }
+        assert!(create.output().unwrap().status.success());
+        let service = || {
+            let inspect = wash()
+                .args(&["par", "inspect", archive.to_str().unwrap(), "-o", "json"])
+                .output()
+                .expect("failed to inspect provider archive");
+            let output: serde_json::Value =
+                serde_json::from_str(&output_to_string(inspect)).unwrap();
+            output["service"].as_str().unwrap().to_string()
+        };
+        let original_service = service();
+
+        // Without a subject key the archive keeps its service identity
+        let update = wash()
+            .args(&["par", "update", archive.to_str().unwrap()])
+            .args(&["-v", "Updated", "-r", "2", "--version", "1.1.0"])
+            .args(&["--issuer", ISSUER, "--disable-keygen"])
+            .args(&["-o", "json"])
+            .output()
+            .expect("failed to update provider archive");
+        assert!(update.status.success());
+        assert_eq!(
+            output_to_string(update),
+            format!(
+                "{{\"file\":\"{}\",\"metadata\":{{\"capid\":\"wasmcloud:testing\",\"name\":\"Update\",\"revision\":2,\"vendor\":\"Updated\",\"version\":\"1.1.0\"}},\"result\":\"success\"}}\n",
+                archive.to_str().unwrap()
+            )
+        );
+
+        assert_eq!(service(), original_service);
+
+        let verify = wash()
+            .args(&["par", "verify", archive.to_str().unwrap()])
+            .output()
+            .expect("failed to verify provider archive");
+        assert!(verify.status.success());
+
+        let extracted = test_dir_file(SUBFOLDER, "extracted.bin");
+        let extract = wash()
+            .args(&[
+                "par",
+                "extract",
+                archive.to_str().unwrap(),
+                "-a",
+                "x86_64-linux",
+            ])
+            .args(&["--destination", extracted.to_str().unwrap()])
+            .output()
+            .expect("failed to extract from provider archive");
+        assert!(extract.status.success());
+        assert_eq!(
+            std::fs::read_to_string(&extracted).unwrap(),
+            "provider binary"
+        );
+    }
+
+    let nothing = wash()
+        .args(&["par", "update", "update.par", "--disable-keygen"])
+        .output()
+        .expect("failed to run par update");
+    assert!(!nothing.status.success());
+    assert!(String::from_utf8_lossy(&nothing.stderr).contains("No metadata to update"));
+
+    remove_dir_all(update_dir).unwrap();
+}
+
+#[test]
+fn integration_par_diff() {
+    const SUBFOLDER: &str = "par_diff";
+    const ISSUER: &str = "SAACTTUPKR55VUWUDK7GJ5SU5KGED455FR7BDO46RUVOTHUWKBLECLH2UU";
+    const SUBJECT: &str = "SVAOZUSBWWFL65P255DOHIETPTXUQMM5ETLSYPITI5G4K4HI6M2CDAPWAU";
+    let diff_dir = test_dir_with_subfolder(SUBFOLDER);
+    let linux = test_dir_file(SUBFOLDER, "linux.so");
+    let linux_v2 = test_dir_file(SUBFOLDER, "linux_v2.so");
+    let macos = test_dir_file(SUBFOLDER, "macos.dylib");
+    std::fs::write(&linux, b"linux").unwrap();
+    std::fs::write(&linux_v2, b"linux v2").unwrap();
+    std::fs::write(&macos, b"macos").unwrap();
+
+    let create = |destination: &str, vendor: &str, binaries: &[String]| {
+        let archive = test_dir_file(SUBFOLDER, destination);
+        let mut create = wash();
+        create
+            .args(&["par", "create", "-c", "wasmcloud:testing", "-n", "Diff"])
+            .args(&["-v", vendor, "--issuer", ISSUER, "--subject", SUBJECT])
+            .args(&[
+                "--disable-keygen",
+                "--destination",
+                archive.to_str().unwrap(),
+            ]);
+        for binary in binaries {
+            create.args(&["-b", binary]);
+        }
+        assert!(create.output().unwrap().status.success());
+        archive.to_str().unwrap().to_string()
+    };
+    let v1 = create(
+        "v1.par",
+        "Test",
+        &[format!("x86_64-linux={}", linux.to_str().unwrap())],
+    );
+    let v2 = create(
+        "v2.par",
+        "Updated",
+        &[
+            format!("x86_64-linux={}", linux_v2.to_str().unwrap()),
+            format!("aarch64-macos={}", macos.to_str().unwrap()),
+        ],
+    );
+
+    let identical = wash()
+        .args(&["par", "diff", &v1, &v1])
+        .output()
+        .expect("failed to diff provider archives");
+    assert!(identical.status.success());
+    assert!(
+        output_to_string(identical).contains(&format!("No differences between {} and {}", v1, v1))
+    );
+
+    let diff = wash()
+        .args(&["par", "diff", &v1, &v2, "-o", "json"])
+        .output()
+        .expect("failed to diff provider archives");
+    assert!(diff.status.success());
+    let diff = output_to_string(diff);
+    assert!(diff.contains("{\"field\":\"vendor\",\"left\":\"Test\",\"right\":\"Updated\"}"));
+    assert!(diff.contains("{\"added\":[\"aarch64-macos\"],\"field\":\"targets\",\"removed\":[]}"));
+    assert!(diff.contains("{\"field\":\"x86_64-linux hash\""));
+    assert!(!diff.contains("aarch64-macos hash"));
+
+    remove_dir_all(diff_dir).unwrap();
+}
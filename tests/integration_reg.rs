@@ -209,3 +209,718 @@ fn integration_push_comprehensive() {
 
     remove_dir_all(push_dir).unwrap();
 }
+
+#[test]
+fn integration_reg_credentials() {
+    const SUBFOLDER: &str = "reg_credentials";
+    let creds_dir = test_dir_with_subfolder(SUBFOLDER);
+    let broken = test_dir_file(SUBFOLDER, "broken.json");
+    std::fs::write(&broken, "{\"auths\": ").unwrap();
+    let helper = test_dir_file(SUBFOLDER, "helper.json");
+    std::fs::write(
+        &helper,
+        "{\"credHelpers\": {\"localhost:5000\": \"wash-test-missing\"}}",
+    )
+    .unwrap();
+    let docker_broken = creds_dir.join("docker_broken");
+    std::fs::create_dir_all(&docker_broken).unwrap();
+    std::fs::copy(&broken, docker_broken.join("config.json")).unwrap();
+    let docker_helper = creds_dir.join("docker_helper");
+    std::fs::create_dir_all(&docker_helper).unwrap();
+    std::fs::copy(&helper, docker_helper.join("config.json")).unwrap();
+    let no_credentials = test_dir_file(SUBFOLDER, "none.json");
+    let url = format!("{}/credentials:0.1.0", LOCAL_REGISTRY);
+
+    // Credentials are resolved the same way for every command that pulls artifacts
+    for args in &[
+        vec!["reg", "pull", url.as_str()],
+        vec!["par", "inspect", url.as_str()],
+        vec!["claims", "inspect", url.as_str()],
+    ] {
+        let parse = wash()
+            .args(args)
+            .env("WASH_REG_CREDENTIALS", &broken)
+            .output()
+            .expect("failed to run wash");
+        assert!(!parse.status.success());
+        assert!(String::from_utf8_lossy(&parse.stderr).contains(&format!(
+            "Unable to parse registry credentials {}",
+            broken.to_str().unwrap()
+        )));
+
+        let missing_helper = wash()
+            .args(args)
+            .env("WASH_REG_CREDENTIALS", &helper)
+            .output()
+            .expect("failed to run wash");
+        assert!(!missing_helper.status.success());
+        assert!(String::from_utf8_lossy(&missing_helper.stderr)
+            .contains("Unable to run credential helper docker-credential-wash-test-missing"));
+
+        // A docker config that cannot be used is skipped rather than failing the command
+        for docker_config in &[&docker_broken, &docker_helper] {
+            let skipped = wash()
+                .args(args)
+                .env("WASH_REG_CREDENTIALS", &no_credentials)
+                .env("DOCKER_CONFIG", docker_config)
+                .output()
+                .expect("failed to run wash");
+            let stderr = String::from_utf8_lossy(&skipped.stderr);
+            assert!(!stderr.contains("Unable to parse registry credentials"));
+            assert!(!stderr.contains("Unable to run credential helper"));
+        }
+    }
+
+    // Explicit credentials take precedence over any credentials file
+    let explicit = wash()
+        .args(&["reg", "pull", &url, "-u", "user", "-p", "password"])
+        .env("WASH_REG_CREDENTIALS", &broken)
+        .output()
+        .expect("failed to run wash");
+    assert!(!String::from_utf8_lossy(&explicit.stderr).contains("Unable to parse"));
+
+    remove_dir_all(creds_dir).unwrap();
+}
+
+#[derive(Clone, Copy)]
+enum FakeAuth {
+    Basic,
+    Bearer,
+    Open,
+}
+
+/// Starts a registry on a local port that only accepts `user:secret`, either checking basic
+/// authentication on `/v2/` or issuing a bearer challenge with a token endpoint. An open
+/// registry accepts every request without asking for credentials
+fn fake_registry(auth: FakeAuth) -> String {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+    let realm = format!("http://{}/token", host);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                request.push(line.to_ascii_lowercase());
+            }
+            // base64 of user:secret
+            let authorized = request
+                .iter()
+                .any(|line| line.trim() == "authorization: basic dxnlcjpzzwnyzxq=");
+            let token_request = request[0].starts_with("get /token");
+            let response = match (auth, token_request, authorized) {
+                (FakeAuth::Open, _, _)
+                | (FakeAuth::Bearer, true, true)
+                | (FakeAuth::Basic, false, true) => {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}".to_string()
+                }
+                (FakeAuth::Bearer, false, _) => format!(
+                    "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Bearer realm=\"{}\",service=\"fake\"\r\nContent-Length: 0\r\n\r\n",
+                    realm
+                ),
+                _ => "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"fake\"\r\nContent-Length: 0\r\n\r\n".to_string(),
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    host
+}
+
+#[test]
+fn integration_reg_login_logout() {
+    const SUBFOLDER: &str = "reg_login";
+    let login_dir = test_dir_with_subfolder(SUBFOLDER);
+    let credentials = test_dir_file(SUBFOLDER, "credentials.json");
+
+    for auth in &[FakeAuth::Basic, FakeAuth::Bearer] {
+        let registry = fake_registry(*auth);
+        let login = |password: &str| {
+            wash()
+                .args(&["reg", "login", &registry, "-u", "user", "-p", password])
+                .args(&["--insecure", "-o", "json"])
+                .env("WASH_REG_CREDENTIALS", &credentials)
+                .output()
+                .expect("failed to log in")
+        };
+
+        let rejected = login("wrong");
+        assert!(!rejected.status.success());
+        assert!(String::from_utf8_lossy(&rejected.stderr).contains(&format!(
+            "Login to {} failed, invalid username or password",
+            registry
+        )));
+        assert!(!credentials.exists());
+
+        let accepted = login("secret");
+        assert!(accepted.status.success());
+        assert_eq!(
+            output_to_string(accepted),
+            format!(
+                "{{\"file\":\"{}\",\"registry\":\"{}\",\"result\":\"success\"}}\n",
+                credentials.to_str().unwrap(),
+                registry
+            )
+        );
+        assert!(std::fs::read_to_string(&credentials)
+            .unwrap()
+            .contains(&format!(
+                "\"{}\": {{\n      \"auth\": \"dXNlcjpzZWNyZXQ=\"",
+                registry
+            )));
+
+        let logout = |registry: &str| {
+            wash()
+                .args(&["reg", "logout", registry])
+                .env("WASH_REG_CREDENTIALS", &credentials)
+                .output()
+                .expect("failed to log out")
+        };
+        let logged_out = logout(&format!("http://{}", registry));
+        assert!(logged_out.status.success());
+        assert!(
+            output_to_string(logged_out).contains(&format!("Removed credentials for {}", registry))
+        );
+        assert!(!std::fs::read_to_string(&credentials)
+            .unwrap()
+            .contains(&registry));
+
+        let not_logged_in = logout(&registry);
+        assert!(!not_logged_in.status.success());
+        assert!(String::from_utf8_lossy(&not_logged_in.stderr)
+            .contains(&format!("Not logged in to {}", registry)));
+        std::fs::remove_file(&credentials).unwrap();
+    }
+
+    // A registry that never asks for credentials cannot verify them
+    let registry = fake_registry(FakeAuth::Open);
+    let unverified = wash()
+        .args(&["reg", "login", &registry, "-u", "user", "-p", "anything"])
+        .args(&["--insecure"])
+        .env("WASH_REG_CREDENTIALS", &credentials)
+        .output()
+        .expect("failed to log in");
+    assert!(unverified.status.success());
+    let output = output_to_string(unverified);
+    assert!(output.contains(&format!("Warning: {} did not authenticate you", registry)));
+    assert!(!output.contains("Login succeeded"));
+
+    remove_dir_all(login_dir).unwrap();
+}
+
+#[test]
+fn integration_reg_cache() {
+    const SUBFOLDER: &str = "reg_cache";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    const DIGEST: &str = "sha256:5790f650cff526fcbc1271107a05111a6647002098b74a9a5e2e26e3c0a116b8";
+    // Nothing listens on this registry, so artifacts can only come from the cache
+    const CACHED: &str = "127.0.0.1:1/cached:0.1.0";
+    let cache_dir = test_dir_with_subfolder(SUBFOLDER);
+    let tmp_dir = test_dir_with_subfolder(&format!("{}/tmp", SUBFOLDER));
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+    let signed = test_dir_file(SUBFOLDER, "empty_s.wasm");
+    let sign = wash()
+        .args(&["claims", "sign", module.to_str().unwrap()])
+        .args(&["--name", "Cached", "--issuer", ACCOUNT, "--subject", MODULE])
+        .args(&[
+            "--disable-keygen",
+            "--destination",
+            signed.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign empty module");
+    assert!(sign.status.success());
+
+    // Populate the cache as a previous pull of the tag would have
+    let wash_cache = tmp_dir.join("wasmcloud_ocicache").join("wash");
+    std::fs::create_dir_all(wash_cache.join("blobs")).unwrap();
+    std::fs::create_dir_all(wash_cache.join("manifests")).unwrap();
+    std::fs::create_dir_all(wash_cache.join("tags")).unwrap();
+    let contents = std::fs::read(&signed).unwrap();
+    let content_digest = format!(
+        "sha256:{}",
+        data_encoding::HEXLOWER
+            .encode(ring::digest::digest(&ring::digest::SHA256, &contents).as_ref())
+    );
+    let blob = wash_cache
+        .join("blobs")
+        .join(content_digest.replace(':', "_"));
+    std::fs::write(&blob, &contents).unwrap();
+    std::fs::write(
+        wash_cache
+            .join("manifests")
+            .join(format!("{}.json", DIGEST.replace(':', "_"))),
+        format!("{{\"content\":\"{}\"}}", content_digest),
+    )
+    .unwrap();
+    let resolved_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    std::fs::write(
+        wash_cache.join("tags").join(format!(
+            "sha256_{}.json",
+            data_encoding::HEXLOWER
+                .encode(ring::digest::digest(&ring::digest::SHA256, CACHED.as_bytes()).as_ref())
+        )),
+        format!(
+            "{{\"digest\":\"{}\",\"resolved_at\":{}}}",
+            DIGEST, resolved_at
+        ),
+    )
+    .unwrap();
+
+    let inspect = wash()
+        .args(&["claims", "inspect", CACHED, "-o", "json"])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to inspect cached module");
+    assert!(inspect.status.success());
+    assert!(output_to_string(inspect)
+        .contains("\"module\":\"MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5\""));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&wash_cache).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    let pulled = test_dir_file(SUBFOLDER, "pulled.wasm");
+    let pull_by_digest = wash()
+        .args(&["reg", "pull", &format!("127.0.0.1:1/cached@{}", DIGEST)])
+        .args(&["--destination", pulled.to_str().unwrap()])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to pull cached module");
+    assert!(pull_by_digest.status.success());
+    assert_eq!(
+        std::fs::read(&pulled).unwrap(),
+        std::fs::read(&signed).unwrap()
+    );
+
+    // A provided digest is looked up directly, whatever the tag was last resolved to
+    let by_digest = wash()
+        .args(&["reg", "pull", CACHED, "--digest", DIGEST])
+        .args(&["--destination", pulled.to_str().unwrap()])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to pull cached module");
+    assert!(by_digest.status.success());
+    let other_digest = wash()
+        .args(&["reg", "pull", CACHED, "--digest", "sha256:0000"])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to run wash");
+    assert!(!other_digest.status.success());
+    let stderr = String::from_utf8_lossy(&other_digest.stderr).to_string();
+    assert!(!stderr.contains("Image digest did not match provided digest"));
+    assert!(stderr.contains("127.0.0.1:1"));
+
+    // Cached contents that no longer match their digest are pulled from the registry again
+    std::fs::write(&blob, b"\0asm\x01\0\0\0").unwrap();
+    let tampered = wash()
+        .args(&["claims", "inspect", CACHED])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to run wash");
+    assert!(!tampered.status.success());
+    std::fs::write(&blob, &contents).unwrap();
+
+    // Bypassing the cache, or letting the tag resolution expire, goes to the registry
+    let no_cache = wash()
+        .args(&["claims", "inspect", CACHED, "--no-cache"])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to run wash");
+    assert!(!no_cache.status.success());
+    let expired = wash()
+        .args(&["claims", "inspect", CACHED])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .env("WASH_OCI_CACHE_TTL", "0")
+        .output()
+        .expect("failed to run wash");
+    assert!(!expired.status.success());
+
+    let drain = wash()
+        .args(&["drain", "oci"])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to drain oci cache");
+    assert!(drain.status.success());
+    let drained = wash()
+        .args(&["claims", "inspect", CACHED])
+        .env("TMPDIR", &tmp_dir)
+        .env("TMP", &tmp_dir)
+        .output()
+        .expect("failed to run wash");
+    assert!(!drained.status.success());
+
+    remove_dir_all(cache_dir).unwrap();
+}
+
+/// Starts an in-memory registry on a local port that accepts pushes without authentication and
+/// serves pushed manifests, blobs and tags. Returns its host and the manifests pushed to it
+fn memory_registry() -> (
+    String,
+    std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+) {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+    let manifests = Arc::new(Mutex::new(HashMap::new()));
+    let pushed = manifests.clone();
+    std::thread::spawn(move || {
+        let mut uploads: HashMap<String, Vec<u8>> = HashMap::new();
+        let mut blobs: HashMap<String, Vec<u8>> = HashMap::new();
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                let line = line.to_ascii_lowercase();
+                if let Some(length) = line.strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut parts = request_line.split_whitespace();
+            let (method, target) = (parts.next().unwrap(), parts.next().unwrap());
+            let (path, query) = match target.find('?') {
+                Some(i) => (&target[..i], &target[i + 1..]),
+                None => (target, ""),
+            };
+            let repository = ["/manifests/", "/blobs/", "/tags/"]
+                .iter()
+                .find_map(|endpoint| path.find(endpoint))
+                .map(|end| path[4..end].to_string())
+                .unwrap_or_default();
+            let reference = path.rsplit('/').next().unwrap().to_string();
+            let (status, headers, content) = match method {
+                "GET" if path == "/v2/" => ("200 OK", vec![], b"{}".to_vec()),
+                "POST" => {
+                    let upload = uploads.len().to_string();
+                    uploads.insert(upload.clone(), vec![]);
+                    let location = format!("/v2/{}/blobs/uploads/{}?session=1", repository, upload);
+                    ("202 Accepted", vec![("Location", location)], vec![])
+                }
+                "PATCH" => {
+                    uploads.get_mut(&reference).unwrap().extend(body);
+                    (
+                        "202 Accepted",
+                        vec![("Location", target.to_string())],
+                        vec![],
+                    )
+                }
+                "PUT" if path.contains("/blobs/uploads/") => {
+                    let digest = query.split("digest=").nth(1).unwrap().to_string();
+                    blobs.insert(digest.clone(), uploads.remove(&reference).unwrap());
+                    let location = format!("/v2/{}/blobs/{}", repository, digest);
+                    ("201 Created", vec![("Location", location)], vec![])
+                }
+                "PUT" => {
+                    pushed.lock().unwrap().insert(
+                        format!("{}:{}", repository, reference),
+                        String::from_utf8(body).unwrap(),
+                    );
+                    let location = format!("/v2/{}/manifests/{}", repository, reference);
+                    ("201 Created", vec![("Location", location)], vec![])
+                }
+                "GET" if path.contains("/manifests/") => {
+                    let digest_of = |manifest: &str| {
+                        format!(
+                            "sha256:{}",
+                            data_encoding::HEXLOWER.encode(
+                                ring::digest::digest(&ring::digest::SHA256, manifest.as_bytes())
+                                    .as_ref()
+                            )
+                        )
+                    };
+                    // Manifests are served by tag or by digest
+                    let tagged = format!("{}:{}", repository, reference);
+                    let prefix = format!("{}:", repository);
+                    let manifest = pushed
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .find(|(key, manifest)| {
+                            **key == tagged
+                                || (key.starts_with(&prefix) && digest_of(manifest) == reference)
+                        })
+                        .map(|(_, manifest)| manifest.clone());
+                    match manifest {
+                        Some(manifest) => {
+                            let digest = digest_of(&manifest);
+                            let headers = vec![
+                                (
+                                    "Content-Type",
+                                    "application/vnd.oci.image.manifest.v1+json".to_string(),
+                                ),
+                                ("Docker-Content-Digest", digest),
+                            ];
+                            ("200 OK", headers, manifest.as_bytes().to_vec())
+                        }
+                        None => (
+                            "404 Not Found",
+                            vec![],
+                            b"{\"errors\":[{\"code\":\"MANIFEST_UNKNOWN\",\"message\":\"manifest unknown\",\"detail\":{}}]}".to_vec(),
+                        ),
+                    }
+                }
+                "GET" if path.ends_with("/tags/list") => {
+                    let prefix = format!("{}:", repository);
+                    let tags: Vec<String> = pushed
+                        .lock()
+                        .unwrap()
+                        .keys()
+                        .filter_map(|key| key.strip_prefix(&prefix).map(|t| format!("\"{}\"", t)))
+                        .collect();
+                    let list = format!(
+                        "{{\"name\":\"{}\",\"tags\":[{}]}}",
+                        repository,
+                        tags.join(",")
+                    );
+                    ("200 OK", vec![], list.into_bytes())
+                }
+                _ => match blobs.get(&reference) {
+                    Some(blob) => ("200 OK", vec![], blob.clone()),
+                    None => ("404 Not Found", vec![], vec![]),
+                },
+            };
+            let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+            for (name, value) in headers {
+                response.push_str(&format!("{}: {}\r\n", name, value));
+            }
+            response.push_str(&format!("Content-Length: {}\r\n\r\n", content.len()));
+            stream.write_all(response.as_bytes()).unwrap();
+            stream.write_all(&content).unwrap();
+        }
+    });
+    (host, manifests)
+}
+
+#[test]
+fn integration_reg_push_annotations() {
+    const SUBFOLDER: &str = "reg_push_annotations";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    let push_dir = test_dir_with_subfolder(SUBFOLDER);
+    let (registry, manifests) = memory_registry();
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+    let signed = test_dir_file(SUBFOLDER, "empty_s.wasm");
+    let sign = wash()
+        .args(&["claims", "sign", module.to_str().unwrap()])
+        .args(&[
+            "--name",
+            "Annotated",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+        ])
+        .args(&[
+            "--cap",
+            "wasmcloud:httpserver",
+            "--rev",
+            "3",
+            "--ver",
+            "0.1.0",
+        ])
+        .args(&[
+            "--disable-keygen",
+            "--destination",
+            signed.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign empty module");
+    assert!(sign.status.success());
+
+    let url = format!("{}/annotated:0.1.0", registry);
+    let invalid = wash()
+        .args(&["reg", "push", &url, signed.to_str().unwrap(), "--insecure"])
+        .args(&["--annotation", "no-value"])
+        .output()
+        .expect("failed to run wash");
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr)
+        .contains("Invalid annotation no-value, annotations must be in the form key=value"));
+
+    let push = wash()
+        .args(&["reg", "push", &url, signed.to_str().unwrap(), "--insecure"])
+        .args(&["-a", "org.opencontainers.image.title=Renamed"])
+        .args(&["--annotation", "com.example.team=platform"])
+        .output()
+        .expect("failed to push annotated module");
+    assert!(push.status.success());
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&manifests.lock().unwrap()["annotated:0.1.0"]).unwrap();
+    let annotations = &manifest["annotations"];
+    assert_eq!(annotations["org.opencontainers.image.title"], "Renamed");
+    assert_eq!(annotations["org.opencontainers.image.version"], "0.1.0");
+    assert_eq!(annotations["dev.wasmcloud.revision"], "3");
+    assert_eq!(
+        annotations["dev.wasmcloud.issuer"],
+        "AAEFBQUMJEGKN6N3QI24LZZOLO3AE357BQNPBZRLGZA2O6FU7SQF6PTS"
+    );
+    assert_eq!(
+        annotations["dev.wasmcloud.subject"],
+        "MCTESKXXF3O3UHCGSNBTNJTQNQJY5WH6URLYAMSP4HSF5TIDT5ZAWJY5"
+    );
+    assert_eq!(
+        annotations["dev.wasmcloud.capabilities"],
+        "wasmcloud:httpserver"
+    );
+    assert_eq!(annotations["com.example.team"], "platform");
+    assert_eq!(
+        manifest["layers"][0]["mediaType"],
+        "application/vnd.module.wasm.content.layer.v1+wasm"
+    );
+
+    // The annotated manifest still pulls like any other
+    let pulled = test_dir_file(SUBFOLDER, "pulled.wasm");
+    let pull = wash()
+        .args(&["reg", "pull", &url, "--insecure", "--no-cache"])
+        .args(&["--destination", pulled.to_str().unwrap()])
+        .output()
+        .expect("failed to pull annotated module");
+    assert!(pull.status.success());
+    assert_eq!(
+        std::fs::read(&pulled).unwrap(),
+        std::fs::read(&signed).unwrap()
+    );
+
+    remove_dir_all(push_dir).unwrap();
+}
+
+#[test]
+fn integration_reg_inspect() {
+    const SUBFOLDER: &str = "reg_inspect";
+    const ACCOUNT: &str = "SAADC2FGJRMZCSYUMAMWCPOQRGZKUQVWBFJ23C3VNGZJMEHA2NUWBXKKQE";
+    const MODULE: &str = "SMABRDB5JEM6UT5LOWA3XTMYCENYE2XC672X2ZEBGMEHFBXPQQDPGH5HDE";
+    let inspect_dir = test_dir_with_subfolder(SUBFOLDER);
+    let (registry, manifests) = memory_registry();
+
+    let module = test_dir_file(SUBFOLDER, "empty.wasm");
+    let mut module_file = File::create(module.clone()).unwrap();
+    module_file.write_all(b"\0asm\x01\0\0\0").unwrap();
+    let signed = test_dir_file(SUBFOLDER, "empty_s.wasm");
+    let sign = wash()
+        .args(&["claims", "sign", module.to_str().unwrap()])
+        .args(&[
+            "--name",
+            "Inspected",
+            "--issuer",
+            ACCOUNT,
+            "--subject",
+            MODULE,
+        ])
+        .args(&["--rev", "2", "--ver", "0.2.0"])
+        .args(&[
+            "--disable-keygen",
+            "--destination",
+            signed.to_str().unwrap(),
+        ])
+        .output()
+        .expect("failed to sign empty module");
+    assert!(sign.status.success());
+    for tag in &["0.2.0", "stable"] {
+        let push = wash()
+            .args(&["reg", "push", &format!("{}/inspected:{}", registry, tag)])
+            .args(&[signed.to_str().unwrap(), "--insecure"])
+            .output()
+            .expect("failed to push module");
+        assert!(push.status.success());
+    }
+
+    let url = format!("{}/inspected:0.2.0", registry);
+    let inspect = wash()
+        .args(&["reg", "inspect", &url, "--insecure", "-o", "json"])
+        .output()
+        .expect("failed to inspect module");
+    assert!(inspect.status.success());
+    let inspected: serde_json::Value = serde_json::from_str(&output_to_string(inspect)).unwrap();
+    let manifest = manifests.lock().unwrap()["inspected:0.2.0"].clone();
+    let digest = format!(
+        "sha256:{}",
+        data_encoding::HEXLOWER
+            .encode(ring::digest::digest(&ring::digest::SHA256, manifest.as_bytes()).as_ref())
+    );
+    let size = std::fs::metadata(&signed).unwrap().len();
+    assert_eq!(inspected["url"], url);
+    assert_eq!(inspected["digest"], digest);
+    assert_eq!(inspected["type"], "Actor");
+    assert_eq!(inspected["size"], size);
+    assert_eq!(
+        inspected["layers"][0]["media_type"],
+        "application/vnd.module.wasm.content.layer.v1+wasm"
+    );
+    assert_eq!(
+        inspected["config"]["media_type"],
+        "application/vnd.wasmcloud.actor.archive.config"
+    );
+    assert_eq!(inspected["config"]["contents"], serde_json::json!({}));
+    assert_eq!(
+        inspected["annotations"]["org.opencontainers.image.title"],
+        "Inspected"
+    );
+    assert_eq!(inspected["annotations"]["dev.wasmcloud.revision"], "2");
+    assert_eq!(inspected["tags"], serde_json::json!(["0.2.0", "stable"]));
+
+    let inspect_text = wash()
+        .args(&["reg", "inspect", &url, "--insecure"])
+        .output()
+        .expect("failed to inspect module");
+    assert!(inspect_text.status.success());
+    let text = output_to_string(inspect_text);
+    assert!(text.contains(&format!("{} - Actor", url)));
+    assert!(text.contains(&digest));
+    assert!(text.contains(&format!("{} bytes", size)));
+    assert!(text.contains("org.opencontainers.image.version"));
+
+    let missing = wash()
+        .args(&["reg", "inspect", &format!("{}/inspected:0.3.0", registry)])
+        .arg("--insecure")
+        .output()
+        .expect("failed to run wash");
+    assert!(!missing.status.success());
+    let stderr = String::from_utf8_lossy(&missing.stderr).to_string();
+    assert!(stderr.contains(&format!(
+        "Unable to fetch manifest of {}/inspected:0.3.0",
+        registry
+    )));
+    assert!(stderr.contains("manifest unknown"));
+
+    remove_dir_all(inspect_dir).unwrap();
+}